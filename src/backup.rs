@@ -1,4 +1,10 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
 use anyhow::Result;
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
 use crate::{
@@ -6,64 +12,111 @@ use crate::{
     traits::*,
     types::{
         author::Author, binding::Binding, book::Book, book_author::BookAuthor,
-        book_genre::BookGenre, edition::Edition, edition_language::EditionLanguage,
+        book_content_warning::BookContentWarning, book_genre::BookGenre, book_shelf::BookShelf,
+        content_warning::ContentWarning, edition::Edition, edition_language::EditionLanguage,
         edition_publisher::EditionPublisher, edition_review::EditionReview, format::EditionFormat,
         genre::Genre, language::Language, mood::Mood, pace::Pace, progress::Progress,
-        publisher::Publisher, review::Review, review_mood::ReviewMood, series::Series, uuid::Uuid,
+        publisher::Publisher, review::Review, review_mood::ReviewMood, series::Series,
+        shelf::Shelf, text::Text, timestamp::OptionalTimestamp, uuid::Uuid,
     },
 };
 
 /// Contains the entire state of the database
 #[derive(Serialize, Deserialize, Default, PartialEq)]
 pub struct State {
-    moods:              Vec<Mood>,
-    paces:              Vec<Pace>,
-    genres:             Vec<Genre>,
-    languages:          Vec<Language>,
-    publishers:         Vec<Publisher>,
-    books:              Vec<Book>,
-    editions:           Vec<Edition>,
-    authors:            Vec<Author>,
-    reviews:            Vec<Review>,
-    edition_reviews:    Vec<EditionReview>,
-    progress:           Vec<Progress>,
-    series:             Vec<Series>,
-    bindings:           Vec<Binding>,
-    edition_formats:    Vec<EditionFormat>,
-    book_authors:       Vec<BookAuthor>,
-    book_genres:        Vec<BookGenre>,
-    edition_languages:  Vec<EditionLanguage>,
-    edition_publishers: Vec<EditionPublisher>,
-    review_moods:       Vec<ReviewMood>,
+    moods:                 Vec<Mood>,
+    paces:                 Vec<Pace>,
+    genres:                Vec<Genre>,
+    languages:             Vec<Language>,
+    publishers:            Vec<Publisher>,
+    books:                 Vec<Book>,
+    editions:              Vec<Edition>,
+    authors:               Vec<Author>,
+    reviews:               Vec<Review>,
+    edition_reviews:       Vec<EditionReview>,
+    progress:              Vec<Progress>,
+    series:                Vec<Series>,
+    bindings:              Vec<Binding>,
+    edition_formats:       Vec<EditionFormat>,
+    book_authors:          Vec<BookAuthor>,
+    book_genres:           Vec<BookGenre>,
+    edition_languages:     Vec<EditionLanguage>,
+    edition_publishers:    Vec<EditionPublisher>,
+    review_moods:          Vec<ReviewMood>,
+    // Missing in backups from before shelves existed
+    #[serde(default)]
+    shelves:               Vec<Shelf>,
+    #[serde(default)]
+    book_shelves:          Vec<BookShelf>,
+    // Missing in backups from before content warnings existed
+    #[serde(default)]
+    content_warnings:      Vec<ContentWarning>,
+    #[serde(default)]
+    book_content_warnings: Vec<BookContentWarning>,
 }
 
 impl State {
     /// Generate [State] struct from database
     pub async fn load(conn: &sqlx::SqlitePool) -> Result<Self> {
         Ok(Self {
-            moods:              Mood::get_all(conn).await?,
-            paces:              Pace::get_all(conn).await?,
-            genres:             Genre::get_all(conn).await?,
-            languages:          Language::get_all(conn).await?,
-            publishers:         Publisher::get_all(conn).await?,
-            books:              Book::get_all(conn).await?,
-            editions:           Edition::get_all(conn).await?,
-            authors:            Author::get_all(conn).await?,
-            reviews:            Review::get_all(conn).await?,
-            edition_reviews:    EditionReview::get_all(conn).await?,
-            progress:           Progress::get_all(conn).await?,
-            series:             Series::get_all(conn).await?,
-            bindings:           Binding::get_all(conn).await?,
-            edition_formats:    EditionFormat::get_all(conn).await?,
-            book_authors:       BookAuthor::get_all(conn).await?,
-            book_genres:        BookGenre::get_all(conn).await?,
-            edition_languages:  EditionLanguage::get_all(conn).await?,
-            edition_publishers: EditionPublisher::get_all(conn).await?,
-            review_moods:       ReviewMood::get_all(conn).await?,
+            moods:                 Mood::get_all(conn).await?,
+            paces:                 Pace::get_all(conn).await?,
+            genres:                Genre::get_all(conn).await?,
+            languages:             Language::get_all(conn).await?,
+            publishers:            Publisher::get_all(conn).await?,
+            books:                 Book::get_all(conn).await?,
+            editions:              Edition::get_all(conn).await?,
+            authors:               Author::get_all(conn).await?,
+            reviews:               Review::get_all(conn).await?,
+            edition_reviews:       EditionReview::get_all(conn).await?,
+            progress:              Progress::get_all(conn).await?,
+            series:                Series::get_all(conn).await?,
+            bindings:              Binding::get_all(conn).await?,
+            edition_formats:       EditionFormat::get_all(conn).await?,
+            book_authors:          BookAuthor::get_all(conn).await?,
+            book_genres:           BookGenre::get_all(conn).await?,
+            edition_languages:     EditionLanguage::get_all(conn).await?,
+            edition_publishers:    EditionPublisher::get_all(conn).await?,
+            review_moods:          ReviewMood::get_all(conn).await?,
+            shelves:               Shelf::get_all(conn).await?,
+            book_shelves:          BookShelf::get_all(conn).await?,
+            content_warnings:      ContentWarning::get_all(conn).await?,
+            book_content_warnings: BookContentWarning::get_all(conn).await?,
         })
     }
 
-    /// Sort all fields on [State]
+    /// Like [State::load], but with the relations of editions filled in to
+    /// make the backup easier to read. The junction tables stay authoritative
+    /// for [State::rebuild]
+    pub async fn load_hydrated(conn: &sqlx::SqlitePool) -> Result<Self> {
+        let mut state = Self::load(conn).await?;
+        for edition in &mut state.editions {
+            edition.hydrate(conn).await?;
+        }
+        Ok(state)
+    }
+
+    /// Sort all fields on [State], so the same data always serializes to the
+    /// same output
+    ///
+    /// ```
+    /// use tomex::backup::State;
+    ///
+    /// let mut value = serde_json::to_value(State::default()).unwrap();
+    /// let a = "00000000-0000-0000-0000-00000000000a";
+    /// let b = "00000000-0000-0000-0000-00000000000b";
+    /// value["book_authors"] = serde_json::json!([
+    ///     { "book_id": a, "author_id": b },
+    ///     { "book_id": a, "author_id": a },
+    ///     { "book_id": b, "author_id": a },
+    /// ]);
+    /// let mut first = State::deserialize(value.to_string()).unwrap();
+    /// value["book_authors"].as_array_mut().unwrap().reverse();
+    /// let mut second = State::deserialize(value.to_string()).unwrap();
+    /// first.sort();
+    /// second.sort();
+    /// assert_eq!(first.serialize().unwrap(), second.serialize().unwrap());
+    /// ```
     pub fn sort(&mut self) {
         self.moods.sort_by_key(|x| x.id.clone());
         self.paces.sort_by_key(|x| x.id.clone());
@@ -79,12 +132,136 @@ impl State {
         self.series.sort_by_key(|x| x.id.clone());
         self.bindings.sort_by_key(|x| x.id.clone());
         self.edition_formats.sort_by_key(|x| x.id.clone());
-        self.book_authors.sort_by_key(|x| x.book_id.clone());
-        self.book_genres.sort_by_key(|x| x.book_id.clone());
-        self.edition_languages.sort_by_key(|x| x.edition_id.clone());
+        self.book_authors
+            .sort_by_key(|x| (x.book_id.clone(), x.author_id.clone()));
+        self.book_genres
+            .sort_by_key(|x| (x.book_id.clone(), x.genre_id.clone()));
+        self.edition_languages
+            .sort_by_key(|x| (x.edition_id.clone(), x.language_id.clone()));
         self.edition_publishers
-            .sort_by_key(|x| x.edition_id.clone());
-        self.review_moods.sort_by_key(|x| x.review_id.clone());
+            .sort_by_key(|x| (x.edition_id.clone(), x.publisher_id.clone()));
+        self.review_moods
+            .sort_by_key(|x| (x.review_id.clone(), x.mood_id.clone()));
+        self.shelves.sort_by_key(|x| x.id.clone());
+        self.book_shelves
+            .sort_by_key(|x| (x.book_id.clone(), x.shelf_id.clone()));
+        self.content_warnings.sort_by_key(|x| x.id.clone());
+        self.book_content_warnings
+            .sort_by_key(|x| (x.book_id.clone(), x.contentwarning_id.clone()));
+        // Only set in hydrated backups
+        for edition in &mut self.editions {
+            if let Some(languages) = &mut edition.languages {
+                languages.sort_by_key(|x| x.id.clone());
+            }
+            if let Some(publishers) = &mut edition.publishers {
+                publishers.sort_by_key(|x| x.id.clone());
+            }
+        }
+    }
+
+    /// Replace everything that could identify the library with placeholders,
+    /// for sharing real-shaped data in bug reports. Call [State::sort] first
+    /// to get the same placeholders for the same data
+    ///
+    /// Scrubbed:
+    /// - titles, summaries and descriptions of books and editions
+    /// - names, websites, dates and OpenLibrary keys of authors, since any of
+    ///   them gives the author away
+    /// - isbns, covers, OpenLibrary keys, notes and where editions were
+    ///   acquired
+    /// - names of series and shelves
+    /// - text of reviews, edition reviews (including private notes) and
+    ///   progress notes
+    ///
+    /// Preserved: ids and so every relation, ratings, dates other than
+    /// those of authors, page counts, dimensions, prices, series indexes and
+    /// genres, moods, paces, languages, publishers, formats, bindings and
+    /// content warnings. Free text keeps its length
+    ///
+    /// ```
+    /// use tomex::{backup::State, types::{book::Book, edition::Edition, text::Text}};
+    ///
+    /// let book = Book {
+    ///     title: Text("Dune".into()),
+    ///     summary: Some(Text("Spice".into())),
+    ///     ..Default::default()
+    /// };
+    /// let edition = Edition {
+    ///     book_id: book.id.clone(),
+    ///     book_title: book.title.clone(),
+    ///     ..Default::default()
+    /// };
+    /// let mut value = serde_json::to_value(State::default()).unwrap();
+    /// value["books"] = serde_json::json!([book]);
+    /// value["editions"] = serde_json::json!([edition]);
+    /// let mut state = State::deserialize(value.to_string()).unwrap();
+    /// state.anonymize();
+    /// let value = serde_json::to_value(&state).unwrap();
+    /// assert_eq!(value["books"][0]["title"], "Book 1");
+    /// assert_eq!(value["books"][0]["summary"], "lorem");
+    /// assert_eq!(value["books"][0]["id"], serde_json::json!(book.id));
+    /// assert_eq!(value["editions"][0]["book_title"], "Book 1");
+    /// ```
+    pub fn anonymize(&mut self) {
+        let scrub = |x: &mut Option<Text>| {
+            if let Some(text) = x {
+                *text = placeholder(text);
+            }
+        };
+        let mut titles = HashMap::new();
+        for (i, book) in self.books.iter_mut().enumerate() {
+            book.title = Text(format!("Book {}", i + 1));
+            scrub(&mut book.summary);
+            titles.insert(book.id.clone(), book.title.clone());
+        }
+        let title_of = |id: &Uuid| titles.get(id).cloned().unwrap_or_default();
+        let mut edition_books = HashMap::new();
+        for edition in &mut self.editions {
+            scrub(&mut edition.edition_title);
+            scrub(&mut edition.edition_description);
+            edition.isbn = None;
+            edition.cover = None;
+            edition.acquired_from = None;
+            edition.openlibrary_key = None;
+            scrub(&mut edition.notes);
+            edition.book_title = title_of(&edition.book_id);
+            edition_books.insert(edition.id.clone(), edition.book_id.clone());
+        }
+        for (i, author) in self.authors.iter_mut().filter(|x| !x.special).enumerate() {
+            author.name = Some(Text(format!("Author {}", i + 1)));
+            author.date_born = OptionalTimestamp(None);
+            author.date_died = OptionalTimestamp(None);
+            author.openlibrary_key = None;
+            author.website = None;
+        }
+        for review in &mut self.reviews {
+            scrub(&mut review.content);
+            scrub(&mut review.private_notes);
+            scrub(&mut review.plot_text);
+            scrub(&mut review.characters_text);
+            scrub(&mut review.prose_text);
+            scrub(&mut review.pacing_text);
+            review.book_title = title_of(&review.book_id);
+        }
+        for review in &mut self.edition_reviews {
+            scrub(&mut review.content);
+            scrub(&mut review.cover_text);
+            scrub(&mut review.typesetting_text);
+            scrub(&mut review.material_text);
+            scrub(&mut review.price_text);
+            if let Some(book_id) = edition_books.get(&review.edition_id) {
+                review.book_title = title_of(book_id);
+            }
+        }
+        for progress in &mut self.progress {
+            scrub(&mut progress.progress_note);
+        }
+        for (i, series) in self.series.iter_mut().enumerate() {
+            series.name = Text(format!("Series {}", i + 1));
+        }
+        for (i, shelf) in self.shelves.iter_mut().enumerate() {
+            shelf.name = Text(format!("Shelf {}", i + 1));
+        }
     }
 
     /// Return true if the database is in default state
@@ -99,7 +276,8 @@ impl State {
         Ok([
             (current.books.len(), 0),
             (current.editions.len(), 0),
-            (current.authors.len(), 1), // The authors table has one entry in it by default
+            // The unknown author is there by default, unless seeding was skipped
+            (current.authors.iter().filter(|x| !x.special).count(), 0),
             (current.reviews.len(), 0),
             (current.edition_reviews.len(), 0),
             (current.progress.len(), 0),
@@ -120,311 +298,321 @@ impl State {
         Ok(serde_json::from_str(&s)?)
     }
 
-    /// Rebuild the database from state
-    pub async fn rebuild(&self, conn: &sqlx::SqlitePool) -> Result<()> {
-        if !State::is_fresh(conn).await? {
+    /// Rebuild the database from state, `force` allows restoring into a
+    /// database that already holds data (existing records are kept)
+    ///
+    /// Everything is inserted in a single transaction, so a backup that fails
+    /// to restore halfway through leaves the database as it was
+    ///
+    /// ```
+    /// use tomex::{backup::State, traits::*, types::{author::Author, book::Book, *}};
+    ///
+    /// # tokio_test();
+    /// # #[tokio::main]
+    /// # async fn tokio_test() {
+    /// let conn = sqlx::sqlite::SqlitePoolOptions::new()
+    ///     .max_connections(1)
+    ///     .connect("sqlite::memory:")
+    ///     .await
+    ///     .unwrap();
+    /// # let seed = Some(&tomex::config::SeedConfig::default());
+    /// # Author::init_table(&conn, seed).await.unwrap();
+    /// # Book::init_table(&conn, seed).await.unwrap();
+    /// # series::Series::init_table(&conn, seed).await.unwrap();
+    /// # review::Review::init_table(&conn, seed).await.unwrap();
+    /// # edition::Edition::init_table(&conn, seed).await.unwrap();
+    /// # edition_review::EditionReview::init_table(&conn, seed).await.unwrap();
+    /// # publisher::Publisher::init_table(&conn, seed).await.unwrap();
+    /// # shelf::Shelf::init_table(&conn, seed).await.unwrap();
+    /// # genre::Genre::init_table(&conn, seed).await.unwrap();
+    /// # content_warning::ContentWarning::init_table(&conn, seed).await.unwrap();
+    /// # mood::Mood::init_table(&conn, seed).await.unwrap();
+    /// # pace::Pace::init_table(&conn, seed).await.unwrap();
+    /// # language::Language::init_table(&conn, seed).await.unwrap();
+    /// # progress::Progress::init_table(&conn, seed).await.unwrap();
+    /// # binding::Binding::init_table(&conn, seed).await.unwrap();
+    /// # format::EditionFormat::init_table(&conn, seed).await.unwrap();
+    /// # book_author::BookAuthor::create_table(&conn).await.unwrap();
+    /// # book_genre::BookGenre::create_table(&conn).await.unwrap();
+    /// # book_content_warning::BookContentWarning::create_table(&conn).await.unwrap();
+    /// # book_shelf::BookShelf::create_table(&conn).await.unwrap();
+    /// # edition_language::EditionLanguage::create_table(&conn).await.unwrap();
+    /// # edition_publisher::EditionPublisher::create_table(&conn).await.unwrap();
+    /// # review_mood::ReviewMood::create_table(&conn).await.unwrap();
+    /// let book = Book {
+    ///     id: uuid::Uuid(::uuid::uuid!("00000000-0000-0000-0000-00000000000b")),
+    ///     title: text::Text("Dune".into()),
+    ///     ..Default::default()
+    /// };
+    /// let mut value = serde_json::to_value(State::default()).unwrap();
+    /// value["books"] = serde_json::json!([book]);
+    /// // Links the book to an author that isn't in the backup
+    /// value["book_authors"] = serde_json::json!([
+    ///     { "book_id": book.id, "author_id": "00000000-0000-0000-0000-00000000000a" },
+    /// ]);
+    /// let state = State::deserialize(value.to_string()).unwrap();
+    /// assert!(state.rebuild(&conn, false).await.is_err());
+    /// assert!(Book::get_all(&conn).await.unwrap().is_empty());
+    /// # }
+    /// ```
+    pub async fn rebuild(&self, conn: &sqlx::SqlitePool, force: bool) -> Result<()> {
+        if !force && !State::is_fresh(conn).await? {
             anyhow::bail!("Database seems to hold data, refusing to overwrite.");
         }
 
-        let all: Vec<Uuid> = Mood::get_all(&conn)
-            .await?
-            .into_iter()
-            .map(|x| x.id)
-            .collect();
+        // Read everything up front, the transaction holds the connection
+        let current = State::load(conn).await?;
+        let mut tx = conn.begin().await?;
+
         for x in &self.moods {
-            if !all.contains(&x.id) {
-                x.insert(&conn).await?;
+            if !current.moods.iter().any(|y| y.id == x.id) {
+                x.insert_with(&mut tx).await?;
             }
         }
-
-        let all: Vec<Uuid> = Pace::get_all(&conn)
-            .await?
-            .into_iter()
-            .map(|x| x.id)
-            .collect();
         for x in &self.paces {
-            if !all.contains(&x.id) {
-                x.insert(&conn).await?;
+            if !current.paces.iter().any(|y| y.id == x.id) {
+                x.insert_with(&mut tx).await?;
             }
         }
-
-        let all: Vec<Uuid> = Genre::get_all(&conn)
-            .await?
-            .into_iter()
-            .map(|x| x.id)
-            .collect();
         for x in &self.genres {
-            if !all.contains(&x.id) {
-                x.insert(&conn).await?;
+            if !current.genres.iter().any(|y| y.id == x.id) {
+                x.insert_with(&mut tx).await?;
             }
         }
-
-        let all: Vec<Uuid> = Language::get_all(&conn)
-            .await?
-            .into_iter()
-            .map(|x| x.id)
-            .collect();
         for x in &self.languages {
-            if !all.contains(&x.id) {
-                x.insert(&conn).await?;
+            if !current.languages.iter().any(|y| y.id == x.id) {
+                x.insert_with(&mut tx).await?;
             }
         }
-
-        let all: Vec<Uuid> = Publisher::get_all(&conn)
-            .await?
-            .into_iter()
-            .map(|x| x.id)
-            .collect();
         for x in &self.publishers {
-            if !all.contains(&x.id) {
-                x.insert(&conn).await?;
+            if !current.publishers.iter().any(|y| y.id == x.id) {
+                x.insert_with(&mut tx).await?;
+            }
+        }
+        for x in &self.shelves {
+            if !current.shelves.iter().any(|y| y.id == x.id) {
+                x.insert_with(&mut tx).await?;
+            }
+        }
+        for x in &self.content_warnings {
+            if !current.content_warnings.iter().any(|y| y.id == x.id) {
+                x.insert_with(&mut tx).await?;
             }
         }
-
-        let all: Vec<Uuid> = Book::get_all(&conn)
-            .await?
-            .into_iter()
-            .map(|x| x.id)
-            .collect();
         for x in &self.books {
-            if !all.contains(&x.id) {
-                x.insert(&conn).await?;
+            if !current.books.iter().any(|y| y.id == x.id) {
+                x.insert_with(&mut tx).await?;
             }
         }
-
-        let all: Vec<Uuid> = Edition::get_all(&conn)
-            .await?
-            .into_iter()
-            .map(|x| x.id)
-            .collect();
         for x in &self.editions {
-            if !all.contains(&x.id) {
-                x.insert(&conn).await?;
+            if !current.editions.iter().any(|y| y.id == x.id) {
+                // Relations are restored from the junction tables, these are
+                // only there if the backup was hydrated
+                Edition {
+                    languages: None,
+                    publishers: None,
+                    ..x.clone()
+                }
+                .insert_with(&mut tx)
+                .await?;
             }
         }
-
-        let all: Vec<Uuid> = Author::get_all(&conn)
-            .await?
-            .into_iter()
-            .map(|x| x.id)
-            .collect();
         for x in &self.authors {
-            if !all.contains(&x.id) {
-                x.insert(&conn).await?;
+            if !current.authors.iter().any(|y| y.id == x.id) {
+                x.insert_with(&mut tx).await?;
             }
         }
-
-        let all: Vec<Uuid> = Review::get_all(&conn)
-            .await?
-            .into_iter()
-            .map(|x| x.id)
-            .collect();
         for x in &self.reviews {
-            if !all.contains(&x.id) {
-                x.insert(&conn).await?;
+            if !current.reviews.iter().any(|y| y.id == x.id) {
+                x.insert_with(&mut tx).await?;
             }
         }
-
-        let all: Vec<Uuid> = EditionReview::get_all(&conn)
-            .await?
-            .into_iter()
-            .map(|x| x.id)
-            .collect();
         for x in &self.edition_reviews {
-            if !all.contains(&x.id) {
-                x.insert(&conn).await?;
+            if !current.edition_reviews.iter().any(|y| y.id == x.id) {
+                x.insert_with(&mut tx).await?;
             }
         }
-
-        let all: Vec<Uuid> = Progress::get_all(&conn)
-            .await?
-            .into_iter()
-            .map(|x| x.id)
-            .collect();
         for x in &self.progress {
-            if !all.contains(&x.id) {
-                x.insert(&conn).await?;
+            if !current.progress.iter().any(|y| y.id == x.id) {
+                x.insert_with(&mut tx).await?;
             }
         }
-
-        let all: Vec<Uuid> = Series::get_all(&conn)
-            .await?
-            .into_iter()
-            .map(|x| x.id)
-            .collect();
         for x in &self.series {
-            if !all.contains(&x.id) {
-                x.insert(&conn).await?;
+            if !current.series.iter().any(|y| y.id == x.id) {
+                x.insert_with(&mut tx).await?;
             }
         }
-
-        let all: Vec<Uuid> = Binding::get_all(&conn)
-            .await?
-            .into_iter()
-            .map(|x| x.id)
-            .collect();
         for x in &self.bindings {
-            if !all.contains(&x.id) {
-                x.insert(&conn).await?;
+            if !current.bindings.iter().any(|y| y.id == x.id) {
+                x.insert_with(&mut tx).await?;
             }
         }
-
-        let all: Vec<Uuid> = EditionFormat::get_all(&conn)
-            .await?
-            .into_iter()
-            .map(|x| x.id)
-            .collect();
         for x in &self.edition_formats {
-            if !all.contains(&x.id) {
-                x.insert(&conn).await?;
+            if !current.edition_formats.iter().any(|y| y.id == x.id) {
+                x.insert_with(&mut tx).await?;
             }
         }
 
-        let all: Vec<(Uuid, Uuid)> = BookAuthor::get_all(&conn)
-            .await?
-            .into_iter()
-            .map(|x| (x.book_id, x.author_id))
-            .collect();
         for x in &self.book_authors {
-            if !all.contains(&(x.book_id.clone(), x.author_id.clone())) {
-                let x1 = self
-                    .books
-                    .iter()
-                    .filter(|y| y.id == x.book_id)
-                    .next()
-                    .ok_or(anyhow::anyhow!(
-                        "Inconsistency in database, couldn't find book with id {}",
-                        x.book_id
-                    ))?;
-                let x2 = self
-                    .authors
-                    .iter()
-                    .filter(|y| y.id == x.author_id)
-                    .next()
-                    .ok_or(anyhow::anyhow!(
-                        "Inconsistency in database, couldn't find author with id {}",
-                        x.author_id
-                    ))?;
-                BookAuthor::insert(&conn, x1, x2).await?;
+            let exists = current
+                .book_authors
+                .iter()
+                .any(|y| y.book_id == x.book_id && y.author_id == x.author_id);
+            if !exists {
+                let x1 = find(&self.books, &x.book_id, Book::NAME_SINGULAR).await?;
+                let x2 = find(&self.authors, &x.author_id, Author::NAME_SINGULAR).await?;
+                BookAuthor::insert(&mut tx, x1, x2).await?;
             }
         }
-
-        let all: Vec<(Uuid, Uuid)> = BookGenre::get_all(&conn)
-            .await?
-            .into_iter()
-            .map(|x| (x.book_id, x.genre_id))
-            .collect();
         for x in &self.book_genres {
-            if !all.contains(&(x.book_id.clone(), x.genre_id.clone())) {
-                let x1 = self
-                    .books
-                    .iter()
-                    .filter(|y| y.id == x.book_id)
-                    .next()
-                    .ok_or(anyhow::anyhow!(
-                        "Inconsistency in database, couldn't find book with id {}",
-                        x.book_id
-                    ))?;
-                let x2 = self
-                    .genres
-                    .iter()
-                    .filter(|y| y.id == x.genre_id)
-                    .next()
-                    .ok_or(anyhow::anyhow!(
-                        "Inconsistency in database, couldn't find genre with id {}",
-                        x.genre_id
-                    ))?;
-                BookGenre::insert(&conn, x1, x2).await?;
+            let exists = current
+                .book_genres
+                .iter()
+                .any(|y| y.book_id == x.book_id && y.genre_id == x.genre_id);
+            if !exists {
+                let x1 = find(&self.books, &x.book_id, Book::NAME_SINGULAR).await?;
+                let x2 = find(&self.genres, &x.genre_id, Genre::NAME_SINGULAR).await?;
+                BookGenre::insert(&mut tx, x1, x2).await?;
             }
         }
-
-        let all: Vec<(Uuid, Uuid)> = EditionLanguage::get_all(&conn)
-            .await?
-            .into_iter()
-            .map(|x| (x.edition_id, x.language_id))
-            .collect();
         for x in &self.edition_languages {
-            if !all.contains(&(x.edition_id.clone(), x.language_id.clone())) {
-                let x1 = self
-                    .editions
-                    .iter()
-                    .filter(|y| y.id == x.edition_id)
-                    .next()
-                    .ok_or(anyhow::anyhow!(
-                        "Inconsistency in database, couldn't find edition with id {}",
-                        x.edition_id
-                    ))?;
-                let x2 = self
-                    .languages
-                    .iter()
-                    .filter(|y| y.id == x.language_id)
-                    .next()
-                    .ok_or(anyhow::anyhow!(
-                        "Inconsistency in database, couldn't find language with id {}",
-                        x.language_id
-                    ))?;
-                EditionLanguage::insert(&conn, x1, x2).await?;
+            let exists = current
+                .edition_languages
+                .iter()
+                .any(|y| y.edition_id == x.edition_id && y.language_id == x.language_id);
+            if !exists {
+                let x1 = find(&self.editions, &x.edition_id, Edition::NAME_SINGULAR).await?;
+                let x2 = find(&self.languages, &x.language_id, Language::NAME_SINGULAR).await?;
+                EditionLanguage::insert(&mut tx, x1, x2).await?;
             }
         }
-
-        let all: Vec<(Uuid, Uuid)> = EditionPublisher::get_all(&conn)
-            .await?
-            .into_iter()
-            .map(|x| (x.edition_id, x.publisher_id))
-            .collect();
         for x in &self.edition_publishers {
-            if !all.contains(&(x.edition_id.clone(), x.publisher_id.clone())) {
-                let x1 = self
-                    .editions
-                    .iter()
-                    .filter(|y| y.id == x.edition_id)
-                    .next()
-                    .ok_or(anyhow::anyhow!(
-                        "Inconsistency in database, couldn't find edition with id {}",
-                        x.edition_id
-                    ))?;
-                let x2 = self
-                    .publishers
-                    .iter()
-                    .filter(|y| y.id == x.publisher_id)
-                    .next()
-                    .ok_or(anyhow::anyhow!(
-                        "Inconsistency in database, couldn't find publisher with id {}",
-                        x.publisher_id
-                    ))?;
-                EditionPublisher::insert(&conn, x1, x2).await?;
+            let exists = current
+                .edition_publishers
+                .iter()
+                .any(|y| y.edition_id == x.edition_id && y.publisher_id == x.publisher_id);
+            if !exists {
+                let x1 = find(&self.editions, &x.edition_id, Edition::NAME_SINGULAR).await?;
+                let x2 = find(&self.publishers, &x.publisher_id, Publisher::NAME_SINGULAR).await?;
+                EditionPublisher::insert(&mut tx, x1, x2).await?;
             }
         }
-
-        let all: Vec<(Uuid, Uuid)> = ReviewMood::get_all(&conn)
-            .await?
-            .into_iter()
-            .map(|x| (x.review_id, x.mood_id))
-            .collect();
         for x in &self.review_moods {
-            if !all.contains(&(x.review_id.clone(), x.mood_id.clone())) {
-                let x1 = self
-                    .reviews
-                    .iter()
-                    .filter(|y| y.id == x.review_id)
-                    .next()
-                    .ok_or(anyhow::anyhow!(
-                        "Inconsistency in database, couldn't find review with id {}",
-                        x.review_id
-                    ))?;
-                let x2 = self
-                    .moods
-                    .iter()
-                    .filter(|y| y.id == x.mood_id)
-                    .next()
-                    .ok_or(anyhow::anyhow!(
-                        "Inconsistency in database, couldn't find mood with id {}",
-                        x.mood_id
-                    ))?;
-                ReviewMood::insert(&conn, x1, x2).await?;
+            let exists = current
+                .review_moods
+                .iter()
+                .any(|y| y.review_id == x.review_id && y.mood_id == x.mood_id);
+            if !exists {
+                let x1 = find(&self.reviews, &x.review_id, Review::NAME_SINGULAR).await?;
+                let x2 = find(&self.moods, &x.mood_id, Mood::NAME_SINGULAR).await?;
+                ReviewMood::insert(&mut tx, x1, x2).await?;
+            }
+        }
+        for x in &self.book_shelves {
+            let exists = current
+                .book_shelves
+                .iter()
+                .any(|y| y.book_id == x.book_id && y.shelf_id == x.shelf_id);
+            if !exists {
+                let x1 = find(&self.books, &x.book_id, Book::NAME_SINGULAR).await?;
+                let x2 = find(&self.shelves, &x.shelf_id, Shelf::NAME_SINGULAR).await?;
+                BookShelf::insert(&mut tx, x1, x2).await?;
+            }
+        }
+        for x in &self.book_content_warnings {
+            let exists = current
+                .book_content_warnings
+                .iter()
+                .any(|y| y.book_id == x.book_id && y.contentwarning_id == x.contentwarning_id);
+            if !exists {
+                let x1 = find(&self.books, &x.book_id, Book::NAME_SINGULAR).await?;
+                let x2 = find(
+                    &self.content_warnings,
+                    &x.contentwarning_id,
+                    ContentWarning::NAME_SINGULAR,
+                )
+                .await?;
+                BookContentWarning::insert(&mut tx, x1, x2).await?;
             }
         }
 
+        tx.commit().await?;
         Ok(())
     }
 }
+
+/// Find the record with `id` among the records of a backup, for restoring the
+/// links of a junction table
+async fn find<'a, T: Id>(xs: &'a [T], id: &Uuid, name: &str) -> Result<&'a T> {
+    for x in xs {
+        if &x.id().await == id {
+            return Ok(x);
+        }
+    }
+    anyhow::bail!("Inconsistency in database, couldn't find {name} with id {id}")
+}
+
+/// Backups older than this many days prompt before destructive operations
+const BACKUP_MAX_AGE_DAYS: i64 = 7;
+
+/// File next to the database recording when it was last backed up
+fn last_backup_file(database: &Path) -> PathBuf {
+    let mut path = database.as_os_str().to_owned();
+    path.push(".last_backup");
+    PathBuf::from(path)
+}
+
+/// Remember that the database was just backed up
+pub fn record_backup(database: &Path) -> Result<()> {
+    std::fs::write(last_backup_file(database), Utc::now().to_rfc3339())?;
+    Ok(())
+}
+
+/// When the database was last backed up, if ever
+pub fn last_backup(database: &Path) -> Result<Option<DateTime<Utc>>> {
+    let path = last_backup_file(database);
+    if !path.exists() {
+        return Ok(None);
+    }
+    let content = std::fs::read_to_string(path)?;
+    Ok(Some(
+        DateTime::parse_from_rfc3339(content.trim())?.with_timezone(&Utc),
+    ))
+}
+
+/// Ask before a destructive operation if there's no recent backup, `yes`
+/// skips the prompt
+pub fn confirm_recent_backup(database: &Path, yes: bool) -> Result<()> {
+    let message = match last_backup(database)? {
+        Some(time) => {
+            let days = (Utc::now() - time).num_days();
+            if days < BACKUP_MAX_AGE_DAYS {
+                return Ok(());
+            }
+            format!("Last backup was {days} days ago, continue?")
+        }
+        None => "No backup of this database found, continue?".to_string(),
+    };
+    if yes {
+        return Ok(());
+    }
+    if !prompt_or_abort(|| inquire::Confirm::new(&message).with_default(false).prompt())? {
+        anyhow::bail!(crate::error::Error::Aborted("Aborted".into()));
+    }
+    Ok(())
+}
+
+/// Placeholder as long as `text`, so bugs depending on the length still show
+fn placeholder(text: &Text) -> Text {
+    const LOREM: &str = "lorem ipsum dolor sit amet ";
+    let length = text.0.chars().count();
+    Text(
+        LOREM
+            .chars()
+            .cycle()
+            .take(length)
+            .collect::<String>()
+            .trim_end()
+            .to_string(),
+    )
+}
@@ -1,4 +1,8 @@
-use clap::{Arg, Command};
+use std::path::PathBuf;
+
+use clap::{Arg, ArgAction, Command, value_parser};
+use clap_complete::Shell;
+use tomex::export::CsvOptions;
 
 pub fn arg_parser_types() -> Vec<Command> {
     vec![
@@ -16,6 +20,12 @@ pub fn arg_parser_types() -> Vec<Command> {
         Command::new("author").about("An author").alias("a"),
         Command::new("publisher").about("A publisher").alias("pub"),
         Command::new("genre").about("Genres of a book").alias("g"),
+        Command::new("content-warning")
+            .about("Content warnings of a book")
+            .alias("cw"),
+        Command::new("shelf")
+            .about("Shelves a book is on")
+            .alias("sh"),
         Command::new("mood").about("Mood of a book").alias("m"),
         Command::new("pace").about("Pace of a book"),
         Command::new("language")
@@ -27,6 +37,272 @@ pub fn arg_parser_types() -> Vec<Command> {
     ]
 }
 
+/// Adding reference data like genres without going through the prompts
+fn arg_parser_add_reference(cmd: Command) -> Command {
+    cmd.arg(
+        Arg::new("names")
+            .required(false)
+            .num_args(1)
+            .long("names")
+            .value_delimiter(',')
+            .action(ArgAction::Append)
+            .help("Comma separated names to add, existing ones are skipped"),
+    )
+}
+
+/// Assigning books while adding a series
+fn arg_parser_add_series(cmd: Command) -> Command {
+    cmd.arg(
+        Arg::new("books")
+            .required(false)
+            .num_args(0)
+            .long("books")
+            .help("Pick existing books for the new series and their indices"),
+    )
+}
+
+/// Filters only available when querying books
+fn arg_parser_query_book(cmd: Command) -> Command {
+    cmd.arg(
+        Arg::new("has-review")
+            .required(false)
+            .num_args(0)
+            .long("has-review")
+            .conflicts_with("no-review")
+            .help("Only show books with a review"),
+    )
+    .arg(
+        Arg::new("no-review")
+            .required(false)
+            .num_args(0)
+            .long("no-review")
+            .help("Only show books without a review"),
+    )
+    .arg(
+        Arg::new("tbr")
+            .required(false)
+            .num_args(0)
+            .long("tbr")
+            .conflicts_with_all(["has-review", "no-review"])
+            .help("Only show books that weren't finished, ordered by to-read priority"),
+    )
+    .arg(
+        Arg::new("editions-count")
+            .required(false)
+            .num_args(0)
+            .long("editions-count")
+            .help("Show how many editions each book has"),
+    )
+    .arg(
+        Arg::new("cw")
+            .required(false)
+            .num_args(1)
+            .long("cw")
+            .action(ArgAction::Append)
+            .help("Only show books with this content warning"),
+    )
+    .arg(
+        Arg::new("no-cw")
+            .required(false)
+            .num_args(1)
+            .long("no-cw")
+            .action(ArgAction::Append)
+            .help("Only show books without this content warning"),
+    )
+    .arg(
+        Arg::new("series")
+            .required(false)
+            .num_args(1)
+            .long("series")
+            .conflicts_with("no-series")
+            .help("Only show books of the series with this name, in series order"),
+    )
+    .arg(
+        Arg::new("no-series")
+            .required(false)
+            .num_args(0)
+            .long("no-series")
+            .help("Only show books that aren't part of a series"),
+    )
+}
+
+/// Filters only available when querying editions
+fn arg_parser_query_edition(cmd: Command) -> Command {
+    cmd.arg(
+        Arg::new("book")
+            .required(false)
+            .num_args(1)
+            .short('b')
+            .long("book")
+            .help("Only show editions of the book with this title"),
+    )
+    .arg(
+        Arg::new("open-cover")
+            .required(false)
+            .num_args(0)
+            .long("open-cover")
+            .requires("uuid")
+            .help("Open the cover of the edition with the default image viewer"),
+    )
+}
+
+/// Filters only available when querying progress
+fn arg_parser_query_progress(cmd: Command) -> Command {
+    cmd.arg(
+        Arg::new("edition")
+            .required(false)
+            .num_args(1)
+            .short('e')
+            .long("edition")
+            .help("Show the reading history of one edition, by ISBN or (partial) uuid"),
+    )
+}
+
+/// Filters only available when querying authors
+fn arg_parser_query_author(cmd: Command) -> Command {
+    let year = |name: &'static str, help: &'static str| {
+        Arg::new(name)
+            .required(false)
+            .num_args(1)
+            .long(name)
+            .value_name("YEAR")
+            .value_parser(value_parser!(i32))
+            .help(help)
+    };
+    cmd.arg(year("born-after", "Only show authors born after this year"))
+        .arg(year(
+            "born-before",
+            "Only show authors born before this year",
+        ))
+        .arg(year(
+            "died-after",
+            "Only show authors who died after this year",
+        ))
+        .arg(year(
+            "died-before",
+            "Only show authors who died before this year",
+        ))
+        .arg(
+            Arg::new("alive")
+                .required(false)
+                .num_args(0)
+                .long("alive")
+                .conflicts_with_all(["died-after", "died-before"])
+                .help("Only show authors without a date of death"),
+        )
+}
+
+/// Flags only available when querying reviews
+fn arg_parser_query_review(cmd: Command) -> Command {
+    cmd.arg(
+        Arg::new("show-spoilers")
+            .required(false)
+            .num_args(0)
+            .long("show-spoilers")
+            .help("Show the content of reviews containing spoilers"),
+    )
+    .arg(
+        Arg::new("show-private")
+            .required(false)
+            .num_args(0)
+            .long("show-private")
+            .help("Show the private notes of reviews"),
+    )
+    .arg(
+        Arg::new("book")
+            .required(false)
+            .num_args(1)
+            .short('b')
+            .long("book")
+            .help("Only show reviews of the book with this title, oldest first"),
+    )
+}
+
+/// Hidden escape hatch for fixing special authors like the unknown author
+fn arg_parser_special_author(cmd: Command) -> Command {
+    cmd.arg(
+        Arg::new("allow-special")
+            .required(false)
+            .num_args(0)
+            .long("allow-special")
+            .requires("yes")
+            .hide(true),
+    )
+    .arg(
+        Arg::new("yes")
+            .required(false)
+            .num_args(0)
+            .long("yes")
+            .hide(true),
+    )
+}
+
+/// Flags for setting edition fields without going through the prompts
+fn arg_parser_edit_edition(cmd: Command) -> Command {
+    cmd.arg(
+        Arg::new("uuid")
+            .required(false)
+            .num_args(1)
+            .help("Uuid of the edition to edit"),
+    )
+    .arg(
+        Arg::new("pages")
+            .required(false)
+            .num_args(1)
+            .long("pages")
+            .value_parser(value_parser!(u32))
+            .help("Number of pages"),
+    )
+    .arg(
+        Arg::new("height")
+            .required(false)
+            .num_args(1)
+            .long("height")
+            .value_parser(value_parser!(u32))
+            .help("Height in mm"),
+    )
+    .arg(
+        Arg::new("width")
+            .required(false)
+            .num_args(1)
+            .long("width")
+            .value_parser(value_parser!(u32))
+            .help("Width in mm"),
+    )
+    .arg(
+        Arg::new("thickness")
+            .required(false)
+            .num_args(1)
+            .long("thickness")
+            .value_parser(value_parser!(u32))
+            .help("Thickness in mm"),
+    )
+    .arg(
+        Arg::new("weight")
+            .required(false)
+            .num_args(1)
+            .long("weight")
+            .value_parser(value_parser!(u32))
+            .help("Weight in grams"),
+    )
+    .arg(
+        Arg::new("cover")
+            .required(false)
+            .num_args(1)
+            .long("cover")
+            .value_parser(value_parser!(PathBuf))
+            .conflicts_with("cover-clear")
+            .help("Image file to use as the cover, copied into cover_dir"),
+    )
+    .arg(
+        Arg::new("cover-clear")
+            .required(false)
+            .num_args(0)
+            .long("cover-clear")
+            .help("Remove the cover"),
+    )
+}
+
 pub fn arg_parser() -> Command {
     Command::new("tomex")
         .about("Personal book management")
@@ -39,10 +315,55 @@ pub fn arg_parser() -> Command {
                 .alias("insert")
                 .subcommand_required(true)
                 .subcommands(arg_parser_types())
+                .mut_subcommand("genre", arg_parser_add_reference)
+                .mut_subcommand("content-warning", arg_parser_add_reference)
+                .mut_subcommand("mood", arg_parser_add_reference)
+                .mut_subcommand("pace", arg_parser_add_reference)
+                .mut_subcommand("language", arg_parser_add_reference)
+                .mut_subcommand("publisher", arg_parser_add_reference)
+                .mut_subcommand("shelf", arg_parser_add_reference)
+                .mut_subcommand("series", arg_parser_add_series)
                 .subcommand(
                     Command::new("by_isbn")
                         .about("Add a book by querying OpenLibrary for an ISBN")
-                        .alias("isbn"),
+                        .alias("isbn")
+                        .arg(
+                            clap::Arg::new("isbn")
+                                .required(false)
+                                .num_args(1)
+                                .help("ISBN to look up, prompted for if missing"),
+                        )
+                        .arg(
+                            clap::Arg::new("dry-run")
+                                .required(false)
+                                .num_args(0)
+                                .long("dry-run")
+                                .help("Print what would be added without saving anything"),
+                        )
+                        .arg(
+                            clap::Arg::new("json")
+                                .required(false)
+                                .num_args(0)
+                                .long("json")
+                                .requires("dry-run")
+                                .help("Print the preview as JSON"),
+                        )
+                        .arg(
+                            clap::Arg::new("pretty")
+                                .required(false)
+                                .num_args(0)
+                                .long("pretty")
+                                .overrides_with("no-pretty")
+                                .help("Indent JSON output (default if stdout is a terminal)"),
+                        )
+                        .arg(
+                            clap::Arg::new("no-pretty")
+                                .required(false)
+                                .num_args(0)
+                                .long("no-pretty")
+                                .overrides_with("pretty")
+                                .help("Print JSON output on a single line"),
+                        ),
                 ),
         )
         .subcommand(
@@ -51,7 +372,9 @@ pub fn arg_parser() -> Command {
                 .alias("e")
                 .alias("update")
                 .subcommand_required(true)
-                .subcommands(arg_parser_types()),
+                .subcommands(arg_parser_types())
+                .mut_subcommand("edition", arg_parser_edit_edition)
+                .mut_subcommand("author", arg_parser_special_author),
         )
         .subcommand(
             Command::new("remove")
@@ -59,7 +382,8 @@ pub fn arg_parser() -> Command {
                 .alias("r")
                 .alias("delete")
                 .subcommand_required(true)
-                .subcommands(arg_parser_types()),
+                .subcommands(arg_parser_types())
+                .mut_subcommand("author", arg_parser_special_author),
         )
         .subcommand(
             Command::new("query")
@@ -93,29 +417,579 @@ pub fn arg_parser() -> Command {
                         .long("uuid")
                         .help("Get record by uuid"),
                 )
+                .arg(
+                    clap::Arg::new("count-only")
+                        .global(true)
+                        .required(false)
+                        .num_args(0)
+                        .long("count-only")
+                        .conflicts_with_all(["interactive", "uuid", "group-by"])
+                        .help("Only print the number of matching records"),
+                )
+                .arg(
+                    clap::Arg::new("include-deleted")
+                        .global(true)
+                        .required(false)
+                        .num_args(0)
+                        .long("include-deleted")
+                        .help("Also show removed records, marked as such and not filtered"),
+                )
+                .arg(
+                    clap::Arg::new("group-by")
+                        .global(true)
+                        .required(false)
+                        .num_args(1)
+                        .long("group-by")
+                        .help("Print records under a header per group, like a book's series"),
+                )
+                .arg(
+                    clap::Arg::new("json")
+                        .global(true)
+                        .required(false)
+                        .num_args(0)
+                        .long("json")
+                        .help("Print records as JSON"),
+                )
+                .arg(
+                    clap::Arg::new("format")
+                        .global(true)
+                        .required(false)
+                        .num_args(1)
+                        .long("format")
+                        .value_parser(["list", "table"])
+                        .default_value("list")
+                        .conflicts_with("json")
+                        .help("Print records as a list or as aligned columns"),
+                )
+                .arg(
+                    clap::Arg::new("fields")
+                        .global(true)
+                        .required(false)
+                        .num_args(1)
+                        .long("fields")
+                        .value_delimiter(',')
+                        .requires("json")
+                        .help("Only include these comma separated fields in JSON output"),
+                )
+                .arg(
+                    clap::Arg::new("pretty")
+                        .global(true)
+                        .required(false)
+                        .num_args(0)
+                        .long("pretty")
+                        .overrides_with("no-pretty")
+                        .help("Indent JSON output (default if stdout is a terminal)"),
+                )
+                .arg(
+                    clap::Arg::new("no-pretty")
+                        .global(true)
+                        .required(false)
+                        .num_args(0)
+                        .long("no-pretty")
+                        .overrides_with("pretty")
+                        .help("Print JSON output on a single line"),
+                )
+                .arg(
+                    clap::Arg::new("output-file")
+                        .global(true)
+                        .required(false)
+                        .num_args(1)
+                        .long("output-file")
+                        .visible_alias("out")
+                        .value_parser(clap::value_parser!(std::path::PathBuf))
+                        .help("Write the results to a file instead of stdout, without styling"),
+                )
                 .subcommand_required(true)
-                .subcommands(arg_parser_types()),
+                .subcommands(arg_parser_types())
+                .mut_subcommand("book", arg_parser_query_book)
+                .mut_subcommand("author", arg_parser_query_author)
+                .mut_subcommand("edition", arg_parser_query_edition)
+                .mut_subcommand("progress", arg_parser_query_progress)
+                .mut_subcommand("review", arg_parser_query_review),
+        )
+        .subcommand(
+            Command::new("start").about("Start reading an edition").arg(
+                Arg::new("edition")
+                    .required(true)
+                    .help("Isbn or (short) uuid of the edition"),
+            ),
+        )
+        .subcommand(
+            Command::new("finish")
+                .about("Finish reading an edition")
+                .arg(
+                    Arg::new("edition")
+                        .required(true)
+                        .help("Isbn or (short) uuid of the edition"),
+                ),
+        )
+        .subcommand(
+            Command::new("calendar")
+                .about("Show a heatmap of reading activity")
+                .arg(
+                    Arg::new("year")
+                        .required(false)
+                        .value_parser(value_parser!(i32))
+                        .help("Year to show (defaults to the current year)"),
+                )
+                .arg(
+                    Arg::new("edition")
+                        .required(false)
+                        .num_args(1)
+                        .long("edition")
+                        .help("Only show activity for this edition (isbn or uuid)"),
+                ),
+        )
+        .subcommand(
+            Command::new("reading")
+                .about("Show the editions currently being read and when they'll be finished")
+                .arg(
+                    Arg::new("watch")
+                        .long("watch")
+                        .action(ArgAction::SetTrue)
+                        .help("Keep showing it, updated every --interval seconds, until Ctrl-C"),
+                )
+                .arg(
+                    Arg::new("interval")
+                        .long("interval")
+                        .num_args(1)
+                        .value_parser(value_parser!(u64).range(1..))
+                        .default_value("60")
+                        .requires("watch")
+                        .help("Seconds between updates with --watch"),
+                )
+                .arg(
+                    Arg::new("sort")
+                        .long("sort")
+                        .num_args(1)
+                        .value_parser(value_parser!(tomex::config::ReadingSort))
+                        .help("Order by latest progress, percent complete or title"),
+                )
+                .arg(
+                    Arg::new("limit")
+                        .long("limit")
+                        .num_args(1)
+                        .value_parser(value_parser!(usize))
+                        .help("Show at most this many editions, 0 for all"),
+                ),
+        )
+        .subcommand(
+            Command::new("stats")
+                .about("Show statistics about the library, all of them without any flags")
+                .arg(
+                    Arg::new("totals")
+                        .long("totals")
+                        .action(ArgAction::SetTrue)
+                        .help("Count books, editions, authors, reviews and pages read"),
+                )
+                .arg(
+                    Arg::new("genres")
+                        .long("genres")
+                        .action(ArgAction::SetTrue)
+                        .help("List genres by number of books"),
+                )
+                .arg(
+                    Arg::new("months")
+                        .long("months")
+                        .action(ArgAction::SetTrue)
+                        .help("Show how many pages you read each month"),
+                )
+                .arg(
+                    Arg::new("authors")
+                        .long("authors")
+                        .action(ArgAction::SetTrue)
+                        .help("List authors by number of books"),
+                )
+                .arg(
+                    Arg::new("ratings")
+                        .long("ratings")
+                        .action(ArgAction::SetTrue)
+                        .help("Show how your review ratings are distributed"),
+                )
+                .arg(
+                    Arg::new("edition-reviews")
+                        .long("edition-reviews")
+                        .action(ArgAction::SetTrue)
+                        .requires("ratings")
+                        .help("Include the ratings of edition reviews"),
+                )
+                .arg(
+                    Arg::new("decades")
+                        .long("decades")
+                        .action(ArgAction::SetTrue)
+                        .help("Count books by the decade they were released in"),
+                )
+                .arg(
+                    Arg::new("limit")
+                        .long("limit")
+                        .num_args(1)
+                        .value_parser(value_parser!(usize))
+                        .default_value("10")
+                        .help("Only show this many entries, 0 shows all"),
+                )
+                .arg(
+                    Arg::new("format")
+                        .long("format")
+                        .num_args(1)
+                        .value_parser(["text", "json"])
+                        .default_value("text")
+                        .help("Print the statistics as text or as one JSON object"),
+                ),
+        )
+        .subcommand(
+            Command::new("merge")
+                .about("Merge duplicate records into one")
+                .subcommand_required(true)
+                .subcommand(
+                    Command::new("series")
+                        .about("Move the books of one series into another and remove it")
+                        .arg(
+                            Arg::new("yes")
+                                .required(false)
+                                .num_args(0)
+                                .short('y')
+                                .long("yes")
+                                .help("Don't ask for confirmation if there's no recent backup"),
+                        ),
+                ),
+        )
+        .subcommand(
+            Command::new("refresh")
+                .about("Fetch records from OpenLibrary again and review the changes")
+                .subcommand_required(true)
+                .subcommand(
+                    Command::new("edition").about("Refresh an edition").arg(
+                        Arg::new("edition")
+                            .required(true)
+                            .help("Isbn or (short) uuid of the edition"),
+                    ),
+                ),
+        )
+        .subcommand(
+            Command::new("doctor")
+                .about("Find problems with the data in the database")
+                .subcommand_required(true)
+                .subcommand(
+                    Command::new("unused")
+                        .about("List genres, moods, etc. which nothing uses")
+                        .arg(
+                            Arg::new("prune")
+                                .long("prune")
+                                .action(ArgAction::SetTrue)
+                                .help("Remove the unused ones, except for the defaults"),
+                        ),
+                )
+                .subcommand(
+                    Command::new("orphaned-editions")
+                        .about("List editions whose book was removed or doesn't exist")
+                        .arg(
+                            Arg::new("reassign")
+                                .long("reassign")
+                                .action(ArgAction::SetTrue)
+                                .help("Pick a new book for each of them"),
+                        ),
+                )
+                .subcommand(
+                    Command::new("incomplete")
+                        .about("List books and editions missing metadata, by default all of it")
+                        .arg(
+                            Arg::new("no-author")
+                                .long("no-author")
+                                .action(ArgAction::SetTrue)
+                                .help("Books without an author"),
+                        )
+                        .arg(
+                            Arg::new("no-release-date")
+                                .long("no-release-date")
+                                .action(ArgAction::SetTrue)
+                                .help("Books without a release date"),
+                        )
+                        .arg(
+                            Arg::new("no-pages")
+                                .long("no-pages")
+                                .action(ArgAction::SetTrue)
+                                .help("Editions without a page count"),
+                        )
+                        .arg(
+                            Arg::new("no-isbn")
+                                .long("no-isbn")
+                                .action(ArgAction::SetTrue)
+                                .help("Books without an isbn on any edition"),
+                        ),
+                )
+                .subcommand(
+                    Command::new("covers")
+                        .about("List editions whose cover file is missing or empty"),
+                ),
+        )
+        .subcommand(
+            Command::new("seed")
+                .about("Add default genres, moods, etc. to an existing database")
+                .arg_required_else_help(true)
+                .arg(
+                    Arg::new("refresh")
+                        .long("refresh")
+                        .action(ArgAction::SetTrue)
+                        .help("Insert the built-in defaults that are missing, like new ones"),
+                ),
         )
         .subcommand(Command::new("listen").about("Start a web server for scanning isbn numbers"))
 }
 
 pub fn arg_parser_repl() -> Command {
-    arg_parser().subcommand(Command::new("exit").about("Exit the repl"))
+    arg_parser()
+        .subcommand(
+            Command::new("exit").about("Exit the repl").arg(
+                Arg::new("code")
+                    .required(false)
+                    .value_parser(value_parser!(i32))
+                    .help("Exit code, 0 by default"),
+            ),
+        )
+        .subcommand(
+            Command::new("parent")
+                .about("Show the book of the edition or review last queried by --uuid"),
+        )
 }
 
 pub fn arg_parser_cli() -> Command {
     arg_parser()
+        .multicall(false)
+        .arg(
+            Arg::new("config")
+                .global(true)
+                .required(false)
+                .num_args(1)
+                .long("config")
+                .value_parser(value_parser!(PathBuf))
+                .help("Path to the config file (defaults to $TOMEX_CONFIG or ~/.config/tomex/config.toml)"),
+        )
+        .arg(
+            Arg::new("no-fallback")
+                .global(true)
+                .required(false)
+                .num_args(0)
+                .long("no-fallback")
+                .help("Fail instead of using the default database if database_location can't be used"),
+        )
+        .arg(
+            Arg::new("no-seed")
+                .global(true)
+                .required(false)
+                .num_args(0)
+                .long("no-seed")
+                .help("Don't fill new tables with default genres, moods, etc."),
+        )
+        .arg(
+            Arg::new("read-only")
+                .global(true)
+                .required(false)
+                .num_args(0)
+                .long("read-only")
+                .alias("db-readonly")
+                .help("Open the database read-only, refusing commands that would change it"),
+        )
+        .arg(
+            Arg::new("no-network")
+                .global(true)
+                .required(false)
+                .num_args(0)
+                .long("no-network")
+                .help("Fail instead of contacting OpenLibrary, even for a single lookup"),
+        )
+        .arg(
+            Arg::new("verbose")
+                .global(true)
+                .required(false)
+                .num_args(0)
+                .short('v')
+                .long("verbose")
+                .help("Log more details, for example dates that couldn't be parsed on import"),
+        )
+        .arg(
+            Arg::new("json-errors")
+                .global(true)
+                .required(false)
+                .num_args(0)
+                .long("json-errors")
+                .help("Print errors as JSON objects with an `error` field"),
+        )
         .subcommand(Command::new("repl").about("Launch a read eval print loop"))
-        .subcommand(Command::new("backup").about("Backup the database to JSON"))
+        .subcommand(
+            Command::new("backup")
+                .about("Backup the database to JSON")
+                .arg(
+                    Arg::new("hydrated")
+                        .required(false)
+                        .num_args(0)
+                        .long("hydrated")
+                        .help("Include languages, publishers, format and binding of editions"),
+                ),
+        )
         .subcommand(
             Command::new("restore")
                 .about("Turn JSON from backup command to new sqlite database")
-                .arg(Arg::new("file").required(true)),
+                .arg(Arg::new("file").required(true))
+                .arg(
+                    Arg::new("force")
+                        .required(false)
+                        .num_args(0)
+                        .short('f')
+                        .long("force")
+                        .help("Restore into a database that already holds data"),
+                )
+                .arg(
+                    Arg::new("yes")
+                        .required(false)
+                        .num_args(0)
+                        .short('y')
+                        .long("yes")
+                        .help("Don't ask for confirmation if there's no recent backup"),
+                ),
         )
         .subcommand(
             Command::new("export")
-                .about("Export to a format you can import in goodreads/storygraph/bookwyrm"),
+                .about("Export to a format you can import in goodreads/storygraph/bookwyrm")
+                .arg(
+                    Arg::new("format")
+                        .long("format")
+                        .num_args(1)
+                        .value_parser(["goodreads", "bookwyrm", "json-lines"])
+                        .default_value("goodreads")
+                        .help(
+                            "Which service's CSV to write, storygraph reads goodreads'. \
+                             json-lines writes one book per line with its editions and reviews",
+                        ),
+                )
+                .arg(
+                    Arg::new("delimiter")
+                        .long("delimiter")
+                        .num_args(1)
+                        .value_parser(CsvOptions::parse_delimiter)
+                        .default_value(",")
+                        .help("Character separating fields, like ; or tab"),
+                )
+                .arg(
+                    Arg::new("bom")
+                        .long("bom")
+                        .action(ArgAction::SetTrue)
+                        .help("Start with a UTF-8 byte order mark, for Excel"),
+                )
+                .arg(
+                    Arg::new("crlf")
+                        .long("crlf")
+                        .action(ArgAction::SetTrue)
+                        .help("End lines with CRLF instead of LF"),
+                )
+                .arg(
+                    Arg::new("anonymize")
+                        .long("anonymize")
+                        .action(ArgAction::SetTrue)
+                        .conflicts_with_all(["format", "delimiter", "bom", "crlf"])
+                        .help(
+                            "Write a backup with titles, names and notes replaced by \
+                             placeholders instead, for sharing in bug reports",
+                        ),
+                ),
         )
+        .subcommand(
+            Command::new("import")
+                .about("Import books from another service")
+                .subcommand_required(true)
+                .subcommand(
+                    Command::new("storygraph")
+                        .about("Import a CSV export from StoryGraph")
+                        .arg(
+                            Arg::new("file")
+                                .required(true)
+                                .value_parser(value_parser!(PathBuf)),
+                        )
+                        .arg(
+                            Arg::new("strict")
+                                .long("strict")
+                                .action(ArgAction::SetTrue)
+                                .help("Leave out isbns that aren't valid instead of keeping them"),
+                        ),
+                )
+                .subcommand(
+                    Command::new("progress")
+                        .about(
+                            "Import progress from a CSV reading log with book (isbn or title), \
+                             date and progress (page or status) columns",
+                        )
+                        .arg(
+                            Arg::new("file")
+                                .required(true)
+                                .value_parser(value_parser!(PathBuf)),
+                        ),
+                ),
+        )
+        .subcommand(
+            Command::new("config")
+                .about("Manage the config file")
+                .subcommand_required(true)
+                .subcommand(
+                    Command::new("init")
+                        .about("Write the default config to the config file location")
+                        .arg(
+                            Arg::new("force")
+                                .required(false)
+                                .num_args(0)
+                                .short('f')
+                                .long("force")
+                                .help("Overwrite an existing config file"),
+                        ),
+                )
+                .subcommand(Command::new("path").about("Print the location of the config file"))
+                .subcommand(Command::new("show").about("Print the effective config"))
+                .subcommand(
+                    Command::new("check")
+                        .about("Check the config file for unknown keys and invalid values"),
+                ),
+        )
+        .subcommand(
+            Command::new("open-db")
+                .about("Print the location of the database")
+                .arg(
+                    Arg::new("launch")
+                        .required(false)
+                        .num_args(0)
+                        .long("launch")
+                        .help("Open it in $TOMEX_SQLITE_GUI, or sqlite3 if that isn't set"),
+                ),
+        )
+        .subcommand(
+            Command::new("completions")
+                .about("Print a shell completion script")
+                .hide(true)
+                .arg(
+                    Arg::new("shell")
+                        .required(true)
+                        .value_parser(value_parser!(Shell)),
+                ),
+        )
+        .subcommand(
+            Command::new("man")
+                .about("Print a man page in roff format")
+                .hide(true),
+        )
+        .subcommand(
+            Command::new("schema")
+                .about("Print the CREATE TABLE statements of the database as SQL"),
+        )
+}
+
+/// Write a completion script for `shell` to stdout
+pub fn print_completions(shell: Shell) {
+    let mut cmd = arg_parser_cli();
+    let name = cmd.get_name().to_string();
+    clap_complete::generate(shell, &mut cmd, name, &mut std::io::stdout());
+}
+
+/// Write a man page for the cli to stdout
+pub fn print_man_page() -> std::io::Result<()> {
+    let cmd = arg_parser_cli();
+    clap_mangen::Man::new(cmd).render(&mut std::io::stdout())
 }
 
 pub fn generate_completions() -> Vec<String> {
@@ -4,7 +4,7 @@ use sqlx::{
     sqlite::{SqliteConnectOptions, SqliteJournalMode},
     Pool, SqlitePool,
 };
-use std::{env, fs, path::PathBuf, process::exit};
+use std::{env, fmt::Display, fs, path::PathBuf, process::exit};
 use tracing::Level;
 use tracing_subscriber::FmtSubscriber;
 
@@ -16,19 +16,47 @@ mod repl;
 mod server;
 
 use tomex::{
-    backup, config,
-    export::Export,
+    backup, cache, config, doctor, error,
+    export::{BookWyrm, CsvOptions, Export},
+    import::{ImportedBook, ReadingLog, StoryGraph},
+    stats,
     traits::*,
     types::{
-        author::Author, binding::Binding, book::Book, book_author::BookAuthor,
-        book_genre::BookGenre, edition::Edition, edition_language::EditionLanguage,
-        edition_publisher::EditionPublisher, edition_review::EditionReview, format::EditionFormat,
-        genre::Genre, language::Language, mood::Mood, pace::Pace, progress::Progress,
-        publisher::Publisher, review::Review, review_mood::ReviewMood, series::Series,
+        author::Author,
+        binding::Binding,
+        book::Book,
+        book_author::BookAuthor,
+        book_content_warning::BookContentWarning,
+        book_genre::BookGenre,
+        book_shelf::BookShelf,
+        content_warning::ContentWarning,
+        edition::Edition,
+        edition_language::EditionLanguage,
+        edition_publisher::EditionPublisher,
+        edition_review::EditionReview,
+        format::EditionFormat,
+        genre::Genre,
+        language::Language,
+        mood::Mood,
+        pace::Pace,
+        progress::{PagesProgress, Progress},
+        publisher::Publisher,
+        review::Review,
+        review_mood::ReviewMood,
+        series::Series,
+        shelf::Shelf,
     },
 };
 
-async fn handle_command(command: String, conn: &SqlitePool, config: &config::Config) -> Result<()> {
+async fn handle_command(
+    command: String,
+    conn: &SqlitePool,
+    config: &config::Config,
+    read_only: bool,
+    network: bool,
+    database_path: &std::path::Path,
+    last_shown: &mut Option<repl::Shown>,
+) -> Result<()> {
     let args = command_parser::arg_parser_repl();
     let command = shlex::split(&command);
     if command.is_none() {
@@ -40,22 +68,118 @@ async fn handle_command(command: String, conn: &SqlitePool, config: &config::Con
         anyhow::bail!(e);
     }
     let matches = matches.unwrap();
+    if let Some(("parent", _)) = matches.subcommand() {
+        let Some(shown) = last_shown else {
+            let message = "Nothing shown yet, query an edition or review by --uuid first";
+            return Err(tomex::error::Error::Usage(message.into()).into());
+        };
+        let book = shown.parent(conn).await?;
+        let mut buf = String::from(" ");
+        book.info_card(&mut buf, conn, config).await?;
+        println!("{buf}");
+        *last_shown = Some(repl::Shown::Book);
+        return Ok(());
+    }
+    check_writable(&matches, read_only)?;
+    run_command(&matches, conn, config, network, database_path).await?;
+    if let Some(shown) = repl::Shown::from_matches(&matches) {
+        *last_shown = Some(shown);
+    }
+    Ok(())
+}
+
+/// Refuse commands which modify the database or the config with --read-only
+fn check_writable(matches: &clap::ArgMatches, read_only: bool) -> Result<()> {
+    const WRITING: [&str; 11] = [
+        "add", "edit", "remove", "restore", "start", "finish", "import", "listen", "merge",
+        "refresh", "seed",
+    ];
+    if !read_only {
+        return Ok(());
+    }
+    let writing = match matches.subcommand() {
+        // A dry run only reads
+        Some(("add", x)) => !x
+            .subcommand_matches("by_isbn")
+            .is_some_and(|x| x.get_flag("dry-run")),
+        Some(("doctor", x)) => match x.subcommand() {
+            Some(("unused", x)) => x.get_flag("prune"),
+            Some(("orphaned-editions", x)) => x.get_flag("reassign"),
+            _ => false,
+        },
+        Some(("config", x)) => x.subcommand_name() == Some("init"),
+        Some((name, _)) => WRITING.contains(&name),
+        None => false,
+    };
+    if !writing {
+        return Ok(());
+    }
+    let message = match matches.subcommand() {
+        Some(("config", _)) => "Can't write the config with --read-only".to_string(),
+        Some(("doctor", x)) => match x.subcommand_name() {
+            Some("unused") => "Can't prune with --read-only, the database is opened read-only",
+            _ => "Can't reassign with --read-only, the database is opened read-only",
+        }
+        .to_string(),
+        Some((name, _)) => {
+            format!("Can't {name} with --read-only, the database is opened read-only")
+        }
+        None => unreachable!("Only subcommands write"),
+    };
+    Err(tomex::error::Error::Usage(message).into())
+}
+
+async fn run_command(
+    matches: &clap::ArgMatches,
+    conn: &SqlitePool,
+    config: &config::Config,
+    network: bool,
+    database_path: &std::path::Path,
+) -> Result<()> {
     match matches.subcommand() {
         Some(("add", _matches)) => match _matches.subcommand() {
             Some(("by_isbn", _matches)) => {
-                let isbn = PromptType::create_by_prompt(
-                    "What is the ISBN?",
-                    None::<&tomex::types::isbn::Isbn>,
-                    conn,
-                )
-                .await?;
-                openlibrary::create_by_isbn(&isbn.0.to_string(), conn).await?;
+                let isbn = match _matches.get_one::<String>("isbn") {
+                    Some(isbn) => match isbn.parse::<isbn2::Isbn>() {
+                        Ok(isbn) => isbn.to_string(),
+                        Err(e) => {
+                            return Err(tomex::error::Error::Usage(format!(
+                                "{isbn} is not an isbn: {e}"
+                            ))
+                            .into());
+                        }
+                    },
+                    None => PromptType::create_by_prompt(
+                        "What is the ISBN?",
+                        None::<&tomex::types::isbn::Isbn>,
+                        conn,
+                    )
+                    .await?
+                    .0
+                    .to_string(),
+                };
+                let fetched = openlibrary::fetch_by_isbn(&isbn, conn, network).await?;
+                if _matches.get_flag("dry-run") {
+                    let preview = openlibrary::preview(fetched).await;
+                    if _matches.get_flag("json") {
+                        println!("{}", to_json_by_clap(&preview, _matches)?);
+                    } else {
+                        println!("Book: {}", preview.book);
+                        println!("Edition: {}", preview.edition);
+                    }
+                } else {
+                    openlibrary::persist(fetched, conn).await?;
+                }
             }
             Some(("book", _matches)) => {
                 Book::insert_by_prompt(conn).await?;
             }
             Some(("series", _matches)) => {
-                Series::insert_by_prompt(conn).await?;
+                let series = Series::insert_by_prompt(conn).await?;
+                if _matches.get_flag("books") {
+                    let assigned = series.assign_books_by_prompt(conn).await?;
+                    println!("Added {assigned} books to {series}");
+                }
             }
             Some(("review", _matches)) => {
                 Review::insert_by_prompt(conn).await?;
@@ -70,19 +194,25 @@ async fn handle_command(command: String, conn: &SqlitePool, config: &config::Con
                 Author::insert_by_prompt(conn).await?;
             }
             Some(("genre", _matches)) => {
-                Genre::insert_by_prompt(conn).await?;
+                add_reference::<Genre>(_matches, conn).await?;
+            }
+            Some(("content-warning", _matches)) => {
+                add_reference::<ContentWarning>(_matches, conn).await?;
             }
             Some(("mood", _matches)) => {
-                Mood::insert_by_prompt(conn).await?;
+                add_reference::<Mood>(_matches, conn).await?;
             }
             Some(("pace", _matches)) => {
-                Pace::insert_by_prompt(conn).await?;
+                add_reference::<Pace>(_matches, conn).await?;
             }
             Some(("language", _matches)) => {
-                Language::insert_by_prompt(conn).await?;
+                add_reference::<Language>(_matches, conn).await?;
             }
             Some(("publisher", _matches)) => {
-                Publisher::insert_by_prompt(conn).await?;
+                add_reference::<Publisher>(_matches, conn).await?;
+            }
+            Some(("shelf", _matches)) => {
+                add_reference::<Shelf>(_matches, conn).await?;
             }
             Some(("progress", _matches)) => {
                 Progress::insert_by_prompt(conn).await?;
@@ -101,17 +231,42 @@ async fn handle_command(command: String, conn: &SqlitePool, config: &config::Con
                 Review::update_by_prompt_by_prompt(conn).await?;
             }
             Some(("edition", _matches)) => {
-                Edition::update_by_prompt_by_prompt(conn).await?;
+                let mut edition = match _matches.get_one::<String>("uuid") {
+                    Some(uuid) => {
+                        let uuid = tomex::types::uuid::Uuid(uuid::Uuid::parse_str(uuid)?);
+                        if !Edition::exists(conn, &uuid).await? {
+                            let message = format!("No edition with id {}", uuid.0);
+                            return Err(tomex::error::Error::NotFound(message).into());
+                        }
+                        Edition::get_by_id(conn, &uuid).await?
+                    }
+                    None => Edition::query_by_prompt(conn).await?,
+                };
+                edition.hydrate(conn).await?;
+                let mut new = edition.clone();
+                let cover_changed = new.apply_cover_clap(_matches, config)?;
+                if !new.apply_clap(_matches) && !cover_changed {
+                    new = PromptType::update_by_prompt(&edition, "", conn).await?;
+                }
+                if edition.book_id != new.book_id {
+                    let book = Book::get_by_id(conn, &new.book_id).await?;
+                    edition.update_dependents_by_prompt(conn, &book).await?;
+                }
+                edition.update(conn, new).await?;
             }
             Some(("edition-review", _matches)) => {
                 EditionReview::update_by_prompt_by_prompt(conn).await?;
             }
             Some(("author", _matches)) => {
-                Author::update_by_prompt_by_prompt(conn).await?;
+                Author::update_by_prompt_allow_special(conn, _matches.get_flag("allow-special"))
+                    .await?;
             }
             Some(("genre", _matches)) => {
                 Genre::update_by_prompt_by_prompt(conn).await?;
             }
+            Some(("content-warning", _matches)) => {
+                ContentWarning::update_by_prompt_by_prompt(conn).await?;
+            }
             Some(("mood", _matches)) => {
                 Mood::update_by_prompt_by_prompt(conn).await?;
             }
@@ -124,6 +279,9 @@ async fn handle_command(command: String, conn: &SqlitePool, config: &config::Con
             Some(("publisher", _matches)) => {
                 Publisher::update_by_prompt_by_prompt(conn).await?;
             }
+            Some(("shelf", _matches)) => {
+                Shelf::update_by_prompt_by_prompt(conn).await?;
+            }
             Some(("progress", _matches)) => {
                 Progress::update_by_prompt_by_prompt(conn).await?;
             }
@@ -147,11 +305,15 @@ async fn handle_command(command: String, conn: &SqlitePool, config: &config::Con
                 EditionReview::remove_by_prompt(conn).await?;
             }
             Some(("author", _matches)) => {
-                Author::remove_by_prompt(conn).await?;
+                Author::remove_by_prompt_allow_special(conn, _matches.get_flag("allow-special"))
+                    .await?;
             }
             Some(("genre", _matches)) => {
                 Genre::remove_by_prompt(conn).await?;
             }
+            Some(("content-warning", _matches)) => {
+                ContentWarning::remove_by_prompt(conn).await?;
+            }
             Some(("mood", _matches)) => {
                 Mood::remove_by_prompt(conn).await?;
             }
@@ -164,71 +326,406 @@ async fn handle_command(command: String, conn: &SqlitePool, config: &config::Con
             Some(("publisher", _matches)) => {
                 Publisher::remove_by_prompt(conn).await?;
             }
+            Some(("shelf", _matches)) => {
+                Shelf::remove_by_prompt(conn).await?;
+            }
             Some(("progress", _matches)) => {
                 Progress::remove_by_prompt(conn).await?;
             }
             Some((name, _matches)) => unimplemented!("{}", name),
             None => unreachable!("subcommand required"),
         },
-        Some(("query", _matches)) => match _matches.subcommand() {
-            Some(("book", _matches)) => {
-                Book::query_by_clap(conn, _matches, config).await?;
-            }
-            Some(("series", _matches)) => {
-                Series::query_by_clap(conn, _matches, config).await?;
+        Some(("query", _matches)) => {
+            cache::scope(run_query(_matches, conn, config)).await?;
+        }
+        Some(("start", _matches)) => {
+            start_or_finish(_matches, PagesProgress::Started, conn, config).await?;
+        }
+        Some(("finish", _matches)) => {
+            start_or_finish(_matches, PagesProgress::Finished, conn, config).await?;
+        }
+        Some(("calendar", _matches)) => {
+            let year = match _matches.get_one::<i32>("year") {
+                Some(year) => *year,
+                None => chrono::Datelike::year(&chrono::Local::now()),
+            };
+            let edition = match _matches.get_one::<String>("edition") {
+                Some(edition) => Some(Edition::get_by_isbn_or_id(conn, edition).await?.id),
+                None => None,
+            };
+            let calendar = stats::Calendar::new(conn, year, edition.as_ref()).await?;
+            println!("{}", calendar.render(config)?);
+        }
+        Some(("reading", _matches)) => {
+            let sort = *_matches
+                .get_one::<config::ReadingSort>("sort")
+                .unwrap_or(&config.reading_sort);
+            let limit = match *_matches
+                .get_one::<usize>("limit")
+                .unwrap_or(&config.reading_limit)
+            {
+                0 => None,
+                limit => Some(limit),
+            };
+            if _matches.get_flag("watch") {
+                let interval = *_matches.get_one::<u64>("interval").unwrap_or(&60);
+                watch_reading(conn, config, interval, sort, limit).await?;
+            } else {
+                let mut reading = stats::CurrentlyReading::new(conn, config).await?;
+                reading.arrange(sort, limit);
+                println!("{}", reading.render(conn, config).await?);
             }
-            Some(("review", _matches)) => {
-                Review::query_by_clap(conn, _matches, config).await?;
+        }
+        Some(("stats", _matches)) => {
+            let limit = match *_matches.get_one::<usize>("limit").unwrap_or(&0) {
+                0 => None,
+                limit => Some(limit),
+            };
+            let mut sections = stats::StatsSections {
+                totals:          _matches.get_flag("totals"),
+                genres:          _matches.get_flag("genres"),
+                months:          _matches.get_flag("months"),
+                ratings:         _matches.get_flag("ratings"),
+                edition_reviews: _matches.get_flag("edition-reviews"),
+                authors:         _matches.get_flag("authors"),
+                decades:         _matches.get_flag("decades"),
+            };
+            if !(sections.totals
+                || sections.genres
+                || sections.months
+                || sections.ratings
+                || sections.authors
+                || sections.decades)
+            {
+                sections = stats::StatsSections::all();
             }
-            Some(("edition", _matches)) => {
-                Edition::query_by_clap(conn, _matches, config).await?;
+            let report = stats::StatsReport::new(conn, sections, limit).await?;
+            match _matches.get_one::<String>("format").map(|x| x.as_str()) {
+                Some("json") => println!("{}", serde_json::to_string_pretty(&report)?),
+                _ => println!("{}", report.render(conn, config).await?),
             }
-            Some(("edition-review", _matches)) => {
-                EditionReview::query_by_clap(conn, _matches, config).await?;
+        }
+        Some(("merge", _matches)) => match _matches.subcommand() {
+            Some(("series", _matches)) => {
+                backup::confirm_recent_backup(database_path, _matches.get_flag("yes"))?;
+                Series::merge_by_prompt(conn).await?
             }
-            Some(("author", _matches)) => {
-                Author::query_by_clap(conn, _matches, config).await?;
+            _ => unreachable!("subcommand required"),
+        },
+        Some(("refresh", _matches)) => match _matches.subcommand() {
+            Some(("edition", _matches)) => {
+                let edition = _matches.get_one::<String>("edition").unwrap();
+                let edition = Edition::get_by_isbn_or_id(conn, edition).await?;
+                openlibrary::refresh_edition(edition, conn, network).await?;
             }
-            Some(("genre", _matches)) => {
-                Genre::query_by_clap(conn, _matches, config).await?;
+            _ => unreachable!("subcommand required"),
+        },
+        Some(("doctor", _matches)) => match _matches.subcommand() {
+            Some(("unused", _matches)) => {
+                let unused = doctor::Unused::all(conn, _matches.get_flag("prune")).await?;
+                let unused: Vec<String> = unused.iter().map(|x| x.render()).collect();
+                println!("{}", unused.join("\n\n"));
             }
-            Some(("mood", _matches)) => {
-                Mood::query_by_clap(conn, _matches, config).await?;
+            Some(("orphaned-editions", _matches)) => {
+                let orphaned = doctor::OrphanedEditions::new(conn).await?;
+                println!("{}", orphaned.render());
+                if _matches.get_flag("reassign") && !orphaned.editions.is_empty() {
+                    let moved = orphaned.reassign_by_prompt(conn).await?;
+                    println!("Moved {moved} edition(s)");
+                }
             }
-            Some(("pace", _matches)) => {
-                Pace::query_by_clap(conn, _matches, config).await?;
+            Some(("incomplete", _matches)) => {
+                let flags = [
+                    ("no-author", doctor::MissingField::Author),
+                    ("no-release-date", doctor::MissingField::ReleaseDate),
+                    ("no-pages", doctor::MissingField::Pages),
+                    ("no-isbn", doctor::MissingField::Isbn),
+                ];
+                let mut fields: Vec<doctor::MissingField> = flags
+                    .into_iter()
+                    .filter(|(flag, _)| _matches.get_flag(flag))
+                    .map(|(_, field)| field)
+                    .collect();
+                if fields.is_empty() {
+                    fields = doctor::MissingField::ALL.to_vec();
+                }
+                let incomplete = doctor::Incomplete::all(conn, &fields).await?;
+                let incomplete: Vec<String> = incomplete.iter().map(|x| x.render()).collect();
+                println!("{}", incomplete.join("\n\n"));
             }
-            Some(("language", _matches)) => {
-                Language::query_by_clap(conn, _matches, config).await?;
+            Some(("covers", _matches)) => {
+                println!("{}", doctor::BrokenCovers::new(conn).await?.render());
             }
-            Some(("publisher", _matches)) => {
-                Publisher::query_by_clap(conn, _matches, config).await?;
+            _ => unreachable!("subcommand required"),
+        },
+        Some(("seed", _matches)) => {
+            let added = [
+                (
+                    Genre::NAME_PLURAL,
+                    Genre::insert_missing_defaults(conn, config.seed.genres.clone()).await?,
+                ),
+                (
+                    Mood::NAME_PLURAL,
+                    Mood::insert_missing_defaults(conn, config.seed.moods.clone()).await?,
+                ),
+                (
+                    Language::NAME_PLURAL,
+                    Language::insert_missing_defaults(conn, config.seed.languages.clone()).await?,
+                ),
+                (
+                    Publisher::NAME_PLURAL,
+                    Publisher::insert_missing_defaults(conn, config.seed.publishers.clone())
+                        .await?,
+                ),
+                (
+                    Binding::NAME_PLURAL,
+                    Binding::insert_missing_defaults(conn, config.seed.bindings.clone()).await?,
+                ),
+                (
+                    EditionFormat::NAME_PLURAL,
+                    EditionFormat::insert_missing_defaults(conn, config.seed.formats.clone())
+                        .await?,
+                ),
+                (
+                    ContentWarning::NAME_PLURAL,
+                    ContentWarning::insert_missing_defaults(
+                        conn,
+                        config.seed.content_warnings.clone(),
+                    )
+                    .await?,
+                ),
+            ];
+            let mut any = false;
+            for (name, added) in added.iter().filter(|(_, x)| !x.is_empty()) {
+                println!("Added {} {name}: {}", added.len(), added.join(", "));
+                any = true;
             }
-            Some(("progress", _matches)) => {
-                Progress::query_by_clap(conn, _matches, config).await?;
+            if !any {
+                println!("No defaults were missing");
             }
-            Some((name, _matches)) => unimplemented!("{}", name),
-            None => unreachable!("subcommand required"),
-        },
+        }
         Some(("listen", _matches)) => {
-            crate::server::start(conn).await;
+            crate::server::start(conn, network).await;
         }
         Some(("exit", _matches)) => {
-            exit(0);
+            exit(*_matches.get_one::<i32>("code").unwrap_or(&error::EXIT_OK));
+        }
+        Some((name, _matches)) => unimplemented!("{}", name),
+        None => unreachable!("subcommand required"),
+    }
+    Ok(())
+}
+
+/// Redraw the reading dashboard every `interval` seconds until Ctrl-C
+async fn watch_reading(
+    conn: &SqlitePool,
+    config: &config::Config,
+    interval: u64,
+    sort: config::ReadingSort,
+    limit: Option<usize>,
+) -> Result<()> {
+    use crossterm::{cursor::MoveTo, execute, terminal};
+    let mut interval = tokio::time::interval(std::time::Duration::from_secs(interval));
+    loop {
+        tokio::select! {
+            _ = interval.tick() => {
+                let mut reading = stats::CurrentlyReading::new(conn, config).await?;
+                reading.arrange(sort, limit);
+                let rendered = reading.render(conn, config).await?;
+                execute!(
+                    std::io::stdout(),
+                    terminal::Clear(terminal::ClearType::All),
+                    MoveTo(0, 0)
+                )?;
+                println!("{rendered}");
+            }
+            _ = tokio::signal::ctrl_c() => return Ok(()),
+        }
+    }
+}
+
+/// Print records for the query command
+async fn run_query(
+    matches: &clap::ArgMatches,
+    conn: &SqlitePool,
+    config: &config::Config,
+) -> Result<()> {
+    match matches.subcommand() {
+        Some(("book", _matches)) => {
+            Book::query_by_clap(conn, _matches, config).await?;
+        }
+        Some(("series", _matches)) => {
+            Series::query_by_clap(conn, _matches, config).await?;
         }
+        Some(("review", _matches)) => {
+            Review::query_by_clap(conn, _matches, config).await?;
+        }
+        Some(("edition", _matches)) => {
+            let mut out = QueryOutput::by_clap(_matches);
+            Edition::query_by_clap_to(conn, _matches, config, &mut out).await?;
+            if _matches.get_flag("open-cover") {
+                let uuid = _matches
+                    .get_one::<String>("uuid")
+                    .expect("Required by open-cover");
+                let edition = Edition::get_by_isbn_or_id(conn, uuid).await?;
+                if !edition.open_cover()? {
+                    out.line("No cover")?;
+                }
+            }
+            out.finish()?;
+        }
+        Some(("edition-review", _matches)) => {
+            EditionReview::query_by_clap(conn, _matches, config).await?;
+        }
+        Some(("author", _matches)) => {
+            Author::query_by_clap(conn, _matches, config).await?;
+        }
+        Some(("genre", _matches)) => {
+            Genre::query_by_clap(conn, _matches, config).await?;
+        }
+        Some(("content-warning", _matches)) => {
+            ContentWarning::query_by_clap(conn, _matches, config).await?;
+        }
+        Some(("mood", _matches)) => {
+            Mood::query_by_clap(conn, _matches, config).await?;
+        }
+        Some(("pace", _matches)) => {
+            Pace::query_by_clap(conn, _matches, config).await?;
+        }
+        Some(("language", _matches)) => {
+            Language::query_by_clap(conn, _matches, config).await?;
+        }
+        Some(("publisher", _matches)) => {
+            Publisher::query_by_clap(conn, _matches, config).await?;
+        }
+        Some(("shelf", _matches)) => {
+            Shelf::query_by_clap(conn, _matches, config).await?;
+        }
+        Some(("progress", _matches)) => match _matches.get_one::<String>("edition") {
+            Some(edition) => {
+                let edition = Edition::get_by_isbn_or_id(conn, edition).await?;
+                let progress = Progress::get_all_for_edition(conn, &edition.id).await?;
+                let mut out = QueryOutput::by_clap(_matches);
+                if _matches.get_flag("json") {
+                    out.line(&to_json_by_clap(&progress, _matches)?)?;
+                } else {
+                    out.line(&edition.to_string())?;
+                    let pages = edition.pages_by_config(conn, config).await?;
+                    for x in progress {
+                        out.line(&format!(" {}", x.fmt_timeline(pages, config)))?;
+                    }
+                }
+                out.finish()?;
+            }
+            None => Progress::query_by_clap(conn, _matches, config).await?,
+        },
         Some((name, _matches)) => unimplemented!("{}", name),
         None => unreachable!("subcommand required"),
     }
     Ok(())
 }
 
-async fn connect_to_db(db_url: PathBuf) -> Result<SqlitePool> {
-    let db_url = shellexpand::full(
-        db_url
-            .to_str()
-            .ok_or(anyhow::anyhow!("Invalid unicode found in path to database"))?,
-    )?;
-    let db_url = PathBuf::from(db_url.into_owned());
+/// Add reference data from `--names`, or by prompt if none were given
+async fn add_reference<T>(matches: &clap::ArgMatches, conn: &SqlitePool) -> Result<()>
+where
+    T: ReferenceType + PromptType + Display,
+{
+    let Some(names) = matches.get_many::<String>("names") else {
+        T::insert_by_prompt(conn).await?;
+        return Ok(());
+    };
+    let names: Vec<String> = names.cloned().collect();
+    let (created, skipped) = T::insert_by_names(conn, &names).await?;
+    for x in created {
+        println!("Created {} {}", T::NAME_SINGULAR, x);
+    }
+    for name in skipped {
+        println!("Skipped {} {}, it already exists", T::NAME_SINGULAR, name);
+    }
+    Ok(())
+}
+
+async fn start_or_finish(
+    matches: &clap::ArgMatches,
+    pages_progress: PagesProgress,
+    conn: &SqlitePool,
+    config: &config::Config,
+) -> Result<()> {
+    let edition = matches
+        .get_one::<String>("edition")
+        .expect("Required argument");
+    let edition = Edition::get_by_isbn_or_id(conn, edition).await?;
+    let progress = Progress::now(edition.id, pages_progress);
+    progress.validate(conn, config).await?;
+    progress.insert(conn).await?;
+    println!(
+        "{}",
+        DisplayTerminal::fmt_to_string(&progress, conn, Some(" "), config).await?
+    );
+    Ok(())
+}
+
+/// Connect to the database from the config, falling back to the default
+/// location with a warning unless `no_fallback` is set
+async fn open_database(
+    config: &config::Config,
+    no_fallback: bool,
+    read_only: bool,
+) -> Result<(PathBuf, SqlitePool)> {
+    let result = match config.database_path() {
+        Ok(path) => match connect_to_db(path.clone(), read_only).await {
+            Ok(conn) => Ok((path, conn)),
+            Err(e) => Err(anyhow::anyhow!(
+                "Couldn't open database at {}: {e}. Check that the directory is writable or \
+                 change database_location in {}",
+                path.display(),
+                config::Config::path().display()
+            )),
+        },
+        Err(e) => Err(e),
+    };
+    match result {
+        Err(e) if !no_fallback => {
+            let path = config::Config::default_database_location();
+            eprintln!("Warning: {e}");
+            eprintln!("Falling back to {}", path.display());
+            Ok((path.clone(), connect_to_db(path, read_only).await?))
+        }
+        result => result,
+    }
+}
+
+/// Open the database in `$TOMEX_SQLITE_GUI`, or `sqlite3` if that isn't set,
+/// and wait for it to exit
+fn launch_sqlite_gui(path: &std::path::Path) -> Result<()> {
+    eprintln!(
+        "Warning: changes made outside of tomex skip its checks. Removed records are only \
+         marked as deleted and links between records are kept in separate tables"
+    );
+    let command = env::var("TOMEX_SQLITE_GUI").unwrap_or_else(|_| "sqlite3".into());
+    let mut args = shlex::split(&command)
+        .filter(|x| !x.is_empty())
+        .ok_or(anyhow::anyhow!("Couldn't parse TOMEX_SQLITE_GUI {command}"))?;
+    let program = args.remove(0);
+    let status = std::process::Command::new(&program)
+        .args(args)
+        .arg(path)
+        .status()
+        .map_err(|e| anyhow::anyhow!("Couldn't launch {program}: {e}"))?;
+    if !status.success() {
+        anyhow::bail!("{program} exited with {status}");
+    }
+    Ok(())
+}
+
+async fn connect_to_db(db_url: PathBuf, read_only: bool) -> Result<SqlitePool> {
+    if read_only {
+        // Nothing may be created or changed, including the journal mode
+        return Ok(Pool::connect_with(
+            SqliteConnectOptions::new().filename(db_url).read_only(true),
+        )
+        .await?);
+    }
     std::fs::create_dir_all(db_url.parent().ok_or(anyhow::anyhow!(
         "Couldn't extract parent directory from database location"
     ))?)?;
@@ -241,45 +738,179 @@ async fn connect_to_db(db_url: PathBuf) -> Result<SqlitePool> {
     .await?)
 }
 
-async fn create_tables(conn: &SqlitePool) -> Result<()> {
-    tokio::try_join!(
-        Author::init_table(conn),
-        Book::init_table(conn),
-        Series::init_table(conn),
-        Review::init_table(conn),
-        Edition::init_table(conn),
-        EditionReview::init_table(conn),
-        Publisher::init_table(conn),
-        Genre::init_table(conn),
-        Mood::init_table(conn),
-        Pace::init_table(conn),
-        Language::init_table(conn),
-        Progress::init_table(conn),
-        Binding::init_table(conn),
-        EditionFormat::init_table(conn),
-        BookAuthor::create_table(conn),
-        BookGenre::create_table(conn),
-        EditionLanguage::create_table(conn),
-        EditionPublisher::create_table(conn),
-        ReviewMood::create_table(conn),
-    )?;
-    Ok(())
+/// Define [create_tables] and [schema] from one list of tables, the tables
+/// before the `;` have data and the ones after are junction tables
+macro_rules! tables {
+    ($($table:ident),*; $($junction:ident),*) => {
+        async fn create_tables(conn: &SqlitePool, seed: Option<&config::SeedConfig>) -> Result<()> {
+            tokio::try_join!(
+                $($table::init_table(conn, seed),)*
+                $($junction::create_table(conn),)*
+            )?;
+            tomex::types::timestamp::migrate_none_sentinel(conn).await?;
+            Edition::canonicalize_isbns(conn).await?;
+            Ok(())
+        }
+
+        /// The statements [create_tables] runs for a new database, one per
+        /// table
+        fn schema() -> String {
+            let statements = [$($table::ddl(),)* $($junction::ddl(),)*];
+            schema_from(&statements)
+        }
+    };
+}
+
+tables!(
+    Author,
+    Book,
+    Series,
+    Review,
+    Edition,
+    EditionReview,
+    Publisher,
+    Shelf,
+    Genre,
+    ContentWarning,
+    Mood,
+    Pace,
+    Language,
+    Progress,
+    Binding,
+    EditionFormat;
+    BookAuthor,
+    BookGenre,
+    BookContentWarning,
+    BookShelf,
+    EditionLanguage,
+    EditionPublisher,
+    ReviewMood
+);
+
+/// Lay out `CREATE TABLE` statements for reading, separated by blank lines
+fn schema_from(statements: &[String]) -> String {
+    let statements: Vec<String> = statements
+        .iter()
+        .map(|statement| {
+            // The statements are laid out for the source code, not for reading
+            let lines: Vec<String> = statement
+                .lines()
+                .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+                .filter(|line| !line.is_empty())
+                .collect();
+            let last = lines.len().saturating_sub(1);
+            let lines: Vec<String> = lines
+                .into_iter()
+                .enumerate()
+                .map(|(i, line)| {
+                    if i == 0 || i == last {
+                        line
+                    } else {
+                        format!("    {line}")
+                    }
+                })
+                .collect();
+            lines.join("\n")
+        })
+        .collect();
+    statements.join("\n\n")
 }
 
 #[tokio::main]
-async fn main() -> Result<()> {
-    let args_parsed = command_parser::arg_parser_cli().get_matches_from(env::args_os().skip(1));
+async fn main() {
+    let args_parsed = command_parser::arg_parser_cli().get_matches_from(env::args_os());
+    if let Err(e) = run(&args_parsed).await {
+        if args_parsed.get_flag("json-errors") {
+            eprintln!("{}", serde_json::json!({ "error": format!("{e:#}") }));
+        } else if error::is_aborted(&e) {
+            eprintln!("{e}");
+        } else {
+            eprintln!("Error: {e:?}");
+        }
+        exit(error::exit_code(&e));
+    }
+}
+
+async fn run(args_parsed: &clap::ArgMatches) -> Result<()> {
+    if let Some(("completions", x)) = args_parsed.subcommand() {
+        let shell = x
+            .get_one::<clap_complete::Shell>("shell")
+            .ok_or(anyhow::anyhow!("No shell specified"))?;
+        command_parser::print_completions(*shell);
+        return Ok(());
+    }
+    if let Some(("man", _)) = args_parsed.subcommand() {
+        command_parser::print_man_page()?;
+        return Ok(());
+    }
+    if let Some(("schema", _)) = args_parsed.subcommand() {
+        println!("{}", schema());
+        return Ok(());
+    }
 
+    if let Some(path) = args_parsed.get_one::<PathBuf>("config") {
+        config::Config::set_path(path.clone())?;
+    }
+    let read_only = args_parsed.get_flag("read-only");
+    check_writable(args_parsed, read_only)?;
+    if let Some(("config", x)) = args_parsed.subcommand() {
+        let path = config::Config::path();
+        match x.subcommand() {
+            Some(("init", x)) => {
+                config::Config::init(&path, x.get_flag("force"))?;
+                println!("Wrote default config to {}", path.display());
+            }
+            Some(("path", _)) => println!("{}", path.display()),
+            Some(("show", _)) => print!("{}", toml::to_string(&config::Config::read_config()?)?),
+            Some(("check", _)) => {
+                let problems = config::Config::check(&path)?;
+                if !problems.is_empty() {
+                    for problem in &problems {
+                        eprintln!("{}: {problem}", path.display());
+                    }
+                    anyhow::bail!("Found {} problem(s) in config", problems.len());
+                }
+                println!("{} is valid", path.display());
+            }
+            Some((name, _)) => unimplemented!("{}", name),
+            None => unreachable!("subcommand required"),
+        }
+        return Ok(());
+    }
     let config = config::Config::read_config()?;
 
-    let conn = connect_to_db(config.database_location.clone()).await?;
+    if let Some(("open-db", x)) = args_parsed.subcommand() {
+        let database_path = config.database_path()?;
+        println!("{}", database_path.display());
+        if x.get_flag("launch") {
+            launch_sqlite_gui(&database_path)?;
+        }
+        return Ok(());
+    }
+
+    let network = !args_parsed.get_flag("no-network");
+    let no_fallback = args_parsed.get_flag("no-fallback");
+    let (database_path, mut conn) = open_database(&config, no_fallback, read_only).await?;
 
-    create_tables(&conn).await?;
-    // println!("{}", config::Config::default_as_string()?);
+    if !read_only {
+        let seed = !(args_parsed.get_flag("no-seed") || config.no_seed);
+        create_tables(&conn, seed.then_some(&config.seed)).await?;
+        // SQLite only notices that another connection changed the schema when
+        // a statement runs, not when it's prepared. A pooled connection that
+        // opened before a migration added columns prepares `SELECT *` with the
+        // old columns, sqlx keeps those, and the row it then gets has more
+        // columns than it expects, which panics in sqlx. Connections opened
+        // after the migrations read the new schema straight away
+        conn.close().await;
+        conn = connect_to_db(database_path.clone(), read_only).await?;
+    }
 
-    let subscriber = FmtSubscriber::builder()
-        .with_max_level(Level::INFO)
-        .finish();
+    let level = if args_parsed.get_flag("verbose") {
+        Level::DEBUG
+    } else {
+        Level::INFO
+    };
+    let subscriber = FmtSubscriber::builder().with_max_level(level).finish();
     tracing::subscriber::set_global_default(subscriber).expect("setting default subscriber failed");
 
     if let Some(("repl", _)) = args_parsed.subcommand() {
@@ -287,7 +918,17 @@ async fn main() -> Result<()> {
         loop {
             match repl.read_line() {
                 Ok(Signal::Success(buffer)) => {
-                    match handle_command(buffer.clone(), &conn, &config).await {
+                    let last_shown = &mut repl.last_shown;
+                    let result = handle_command(
+                        buffer.clone(),
+                        &conn,
+                        &config,
+                        read_only,
+                        network,
+                        &database_path,
+                        last_shown,
+                    );
+                    match result.await {
                         Ok(_) => (),
                         Err(e) => println!("Error: {e}"),
                     };
@@ -301,27 +942,94 @@ async fn main() -> Result<()> {
                 }
             }
         }
-    } else if let Some(("backup", _)) = args_parsed.subcommand() {
-        let mut state = backup::State::load(&conn).await?;
+    } else if let Some(("backup", x)) = args_parsed.subcommand() {
+        let mut state = if x.get_flag("hydrated") {
+            backup::State::load_hydrated(&conn).await?
+        } else {
+            backup::State::load(&conn).await?
+        };
         state.sort();
         println!("{}", state.serialize()?);
+        backup::record_backup(&database_path)?;
     } else if let Some(("restore", x)) = args_parsed.subcommand() {
         let content = fs::read_to_string(
             x.get_one::<String>("file")
                 .ok_or(anyhow::anyhow!("Couldn't read backup from specified file."))?,
         )?;
         let mut state = backup::State::deserialize(content)?;
-        backup::State::rebuild(&state, &conn).await?;
-    } else if let Some(("export", _)) = args_parsed.subcommand() {
-        let export = Export::new(&conn).await?;
-        Export::export(export)?;
+        let force = x.get_flag("force");
+        if force {
+            backup::confirm_recent_backup(&database_path, x.get_flag("yes"))?;
+        }
+        backup::State::rebuild(&state, &conn, force).await?;
+    } else if let Some(("export", x)) = args_parsed.subcommand() {
+        if x.get_flag("anonymize") {
+            let mut state = backup::State::load(&conn).await?;
+            state.sort();
+            state.anonymize();
+            println!("{}", state.serialize()?);
+        } else if x.get_one::<String>("format").map(String::as_str) == Some("json-lines") {
+            tomex::export::write_json_lines(&conn, std::io::stdout().lock()).await?;
+        } else {
+            let export = Export::new(&conn).await?;
+            let options = CsvOptions {
+                delimiter: *x.get_one::<u8>("delimiter").unwrap_or(&b','),
+                bom:       x.get_flag("bom"),
+                crlf:      x.get_flag("crlf"),
+            };
+            match x.get_one::<String>("format").map(String::as_str) {
+                Some("bookwyrm") => BookWyrm::export(export, &options)?,
+                _ => Export::export(export, &options)?,
+            }
+        }
+    } else if let Some(("progress", x)) = args_parsed
+        .subcommand_matches("import")
+        .and_then(|x| x.subcommand())
+    {
+        let file = x.get_one::<PathBuf>("file").expect("Required argument");
+        let rows = ReadingLog::read(file)?;
+        let result = ReadingLog::import(&rows, &conn).await?;
+        for (line, reason) in &result.skipped {
+            eprintln!("Skipped line {line}: {reason}");
+        }
+        println!(
+            "Imported {} progress updates from {} rows, {} already there, {} skipped",
+            result.new_progress,
+            rows.len(),
+            result.duplicates,
+            result.skipped.len()
+        );
+    } else if let Some(("import", x)) = args_parsed.subcommand() {
+        let books: Vec<ImportedBook> = match x.subcommand() {
+            Some(("storygraph", x)) => {
+                let file = x.get_one::<PathBuf>("file").expect("Required argument");
+                let strict = x.get_flag("strict");
+                StoryGraph::read(file)?
+                    .into_iter()
+                    .map(ImportedBook::from)
+                    .map(|x| match strict {
+                        true => x.strict_isbn(),
+                        false => x,
+                    })
+                    .collect()
+            }
+            _ => unreachable!("subcommand required"),
+        };
+        let (mut new_books, mut new_editions, mut new_reviews, mut new_progress) = (0, 0, 0, 0);
+        for book in &books {
+            let result = book.import(&conn).await?;
+            new_books += usize::from(result.new_book);
+            new_editions += usize::from(result.new_edition);
+            new_reviews += usize::from(result.new_review);
+            new_progress += result.new_progress;
+        }
+        println!(
+            "Imported {} rows: {new_books} new books, {new_editions} new editions, \
+             {new_reviews} new reviews, {new_progress} progress updates",
+            books.len()
+        );
     } else {
-        let args = env::args_os()
-            .skip(1)
-            .map(|x| x.into_string().expect("Invalid unicode in arguments"))
-            .collect::<Vec<String>>()
-            .join(" ");
-        handle_command(args, &conn, &config).await?;
+        run_command(args_parsed, &conn, &config, network, &database_path).await?;
     }
 
     conn.close().await;
@@ -2,6 +2,8 @@ use serde::{Deserialize, Serialize};
 
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Author {
+    /// Like `/authors/OL23919A`
+    pub key:        Option<String>,
     pub name:       String,
     pub birth_date: Option<String>,
     pub death_date: Option<String>,
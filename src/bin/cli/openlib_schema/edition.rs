@@ -2,6 +2,8 @@ use serde::{Deserialize, Serialize};
 
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Edition {
+    /// Like `/books/OL7353617M`
+    pub key:             Option<String>,
     pub publishers:      Option<Vec<String>>,
     pub subtitle:        Option<String>,
     pub title:           Option<String>,
@@ -1,14 +1,15 @@
 use anyhow::Result;
 use reqwest::{Client, ClientBuilder};
+use serde::Serialize;
 use tomex::{
-    traits::{Insertable, PromptType},
+    traits::{Insertable, PromptType, Queryable, Updateable, prompt_or_abort},
     types::{
         author::Author,
         text::Text,
         timestamp::{OptionalTimestamp, Timestamp},
     },
 };
-use tracing::info;
+use tracing::{debug, info};
 
 use crate::openlib_schema::{
     author::Author as OpenLibAuthor, book::Book as OpenLibBook, edition::Edition as OpenLibEdition,
@@ -19,7 +20,14 @@ fn opt_str_to_optional_timestamp(input: &Option<String>) -> OptionalTimestamp {
     match input {
         Some(x) => OptionalTimestamp(match dateparser::parse(x) {
             Ok(timestamp) => Some(Timestamp(timestamp)),
-            Err(_) => None,
+            Err(_) => {
+                let timestamp = Timestamp::parse_forgiving(x);
+                match &timestamp {
+                    Some(timestamp) => debug!("Approximated date {x:?} as {}", timestamp.0),
+                    None => debug!("Dropped date {x:?}, couldn't parse it"),
+                }
+                timestamp
+            }
         }),
         None => OptionalTimestamp(None),
     }
@@ -44,36 +52,53 @@ pub async fn isbn_to_edition(
     }
 }
 
+pub async fn key_to_edition(key: &str, client: &Client) -> Result<OpenLibEdition> {
+    let url = format!("https://openlibrary.org/books/{}.json", key);
+    info!("Making request to {}", url);
+    let resp = &client.get(url).send().await?.text().await?;
+    let resp = &mut serde_json::Deserializer::from_str(resp);
+    let resp: Result<OpenLibEdition, _> = serde_path_to_error::deserialize(resp);
+    match resp {
+        Ok(resp) => Ok(resp),
+        Err(err) => Err(anyhow::anyhow!(err.to_string())),
+    }
+}
+
 pub async fn build_edition(edition: OpenLibEdition, book: Book, isbn: &str) -> Edition {
     let release_date = opt_str_to_optional_timestamp(&edition.publish_date);
+    let openlibrary_key = edition.key.as_deref().map(edition_key).map(Text);
     Edition {
-        id:                  tomex::types::uuid::Uuid(uuid::Uuid::new_v4()),
-        book_id:             book.id,
-        edition_title:       if Some(book.title.0.clone()) == edition.title {
+        id: tomex::types::uuid::Uuid(uuid::Uuid::new_v4()),
+        book_id: book.id,
+        edition_title: if Some(book.title.0.clone()) == edition.title {
             None
         } else {
             edition.title.map(|x| Text(x))
         },
         edition_description: None,
-        isbn:                Some(Text(isbn.to_string())),
-        pages:               edition.number_of_pages,
-        languages:           None, // TODO
-        release_date:        release_date,
-        format_id:           None,
-        format:              None,
-        height:              None,
-        width:               None,
-        thickness:           None,
-        weight:              None,
-        binding_id:          None,
-        binding:             None,
-        publishers:          None, // TODO
-        cover:               None,
-        part_index:          None,
-        reviews:             None,
-        progress:            None,
-        deleted:             false,
-        book_title:          book.title,
+        isbn: Some(Text(isbn.to_string())),
+        pages: edition.number_of_pages,
+        languages: None, // TODO
+        release_date: release_date,
+        format_id: None,
+        format: None,
+        height: None,
+        width: None,
+        thickness: None,
+        weight: None,
+        binding_id: None,
+        binding: None,
+        publishers: None, // TODO
+        cover: None,
+        part_index: None,
+        acquired_from: None,
+        acquired_date: OptionalTimestamp(None),
+        openlibrary_key,
+        notes: None,
+        reviews: None,
+        progress: None,
+        deleted: false,
+        book_title: book.title,
     }
 }
 
@@ -108,11 +133,11 @@ pub async fn edition_to_book(
 
 pub async fn build_book(book: OpenLibBook, authors: Option<Vec<Author>>) -> Book {
     Book {
-        id:           tomex::types::uuid::Uuid(uuid::Uuid::new_v4()),
-        title:        Text(book.title),
-        authors:      authors,
-        release_date: OptionalTimestamp(None),
-        summary:      match book.description {
+        id:               tomex::types::uuid::Uuid(uuid::Uuid::new_v4()),
+        title:            Text(book.title),
+        authors:          authors,
+        release_date:     OptionalTimestamp(None),
+        summary:          match book.description {
             Some(description) => match description {
                 crate::openlib_schema::book::Description::Simple(x) => Some(Text(x)),
                 crate::openlib_schema::book::Description::Complex(x) => Some(Text(x.value)),
@@ -120,13 +145,16 @@ pub async fn build_book(book: OpenLibBook, authors: Option<Vec<Author>>) -> Book
             None => None,
         },
         //.map(|x| Text(x.value))},
-        series_id:    None,
-        series_index: None,
-        series:       None,
-        editions:     None,
-        reviews:      None,
-        genres:       None,
-        deleted:      false,
+        series_id:        None,
+        series_index:     None,
+        series:           None,
+        editions:         None,
+        reviews:          None,
+        genres:           None,
+        content_warnings: None,
+        shelves:          None,
+        tbr_priority:     None,
+        deleted:          false,
     }
 }
 
@@ -159,44 +187,156 @@ pub async fn book_to_authors(
 
 pub async fn build_author(author: OpenLibAuthor) -> Author {
     Author {
-        id:        tomex::types::uuid::Uuid(uuid::Uuid::new_v4()),
-        name:      Some(Text(author.name)),
-        date_born: opt_str_to_optional_timestamp(&author.birth_date),
-        date_died: opt_str_to_optional_timestamp(&author.death_date),
-        deleted:   false,
-        special:   false,
+        id:              tomex::types::uuid::Uuid(uuid::Uuid::new_v4()),
+        name:            Some(Text(author.name)),
+        date_born:       opt_str_to_optional_timestamp(&author.birth_date),
+        date_died:       opt_str_to_optional_timestamp(&author.death_date),
+        openlibrary_key: author.key.as_deref().map(author_key).map(Text),
+        website:         None,
+        deleted:         false,
+        special:         false,
     }
 }
 
-pub async fn create_by_isbn(
-    isbn: &str,
-    conn: &sqlx::SqlitePool,
-) -> Result<tomex::types::edition::Edition> {
-    let client = ClientBuilder::new()
+/// The bare id from an OpenLibrary author key, `/authors/OL23919A` becomes
+/// `OL23919A`
+fn author_key(key: &str) -> String {
+    key.trim_start_matches("/authors/").to_string()
+}
+
+/// The bare id from an OpenLibrary edition key, `/books/OL7353617M` becomes
+/// `OL7353617M`
+fn edition_key(key: &str) -> String {
+    key.trim_start_matches("/books/").to_string()
+}
+
+/// An edition already in the database with the same OpenLibrary key or isbn
+async fn existing_edition(fetched: &Fetched, conn: &sqlx::SqlitePool) -> Result<Option<Edition>> {
+    if let Some(key) = &fetched.edition.key {
+        if let Some(edition) = Edition::get_by_openlibrary_key(conn, &edition_key(key)).await? {
+            return Ok(Some(edition));
+        }
+    }
+    Edition::get_by_isbn(conn, &fetched.isbn).await
+}
+
+/// Everything OpenLibrary has on an isbn, before it's reviewed or saved
+pub struct Fetched {
+    pub isbn:    String,
+    pub edition: OpenLibEdition,
+    pub book:    OpenLibBook,
+    pub authors: Vec<OpenLibAuthor>,
+}
+
+/// The records [persist] would start from, see [preview]
+#[derive(Serialize)]
+pub struct Preview {
+    pub book:    Book,
+    pub edition: Edition,
+}
+
+/// A client for OpenLibrary, or an error if `network` is off (`--no-network`)
+fn client(network: bool) -> Result<Client> {
+    if !network {
+        return Err(tomex::error::Error::Usage(
+            "Can't reach OpenLibrary, network access is disabled by --no-network".into(),
+        )
+        .into());
+    }
+    Ok(ClientBuilder::new()
         .timeout(std::time::Duration::new(10, 0))
-        .build()?;
+        .build()?)
+}
+
+pub async fn fetch_by_isbn(isbn: &str, conn: &sqlx::SqlitePool, network: bool) -> Result<Fetched> {
+    let client = client(network)?;
     info!("Getting information from OpenLibrary");
     let edition = isbn_to_edition(isbn, conn, &client).await?;
+    let book = edition_to_book(&edition, conn, &client).await?;
+    let authors = book_to_authors(&book, conn, &client).await?;
+    Ok(Fetched {
+        isbn: isbn.to_string(),
+        edition,
+        book,
+        authors,
+    })
+}
 
-    // println!("Edition:\n{}", serde_json::to_string_pretty(&edition)?);
+/// Build the records without prompting or touching the database
+pub async fn preview(fetched: Fetched) -> Preview {
+    let mut authors = Vec::with_capacity(fetched.authors.len());
+    for author in fetched.authors {
+        authors.push(build_author(author).await);
+    }
+    let book = build_book(fetched.book, Some(authors)).await;
+    let edition = build_edition(fetched.edition, book.clone(), &fetched.isbn).await;
+    Preview { book, edition }
+}
 
-    let book_auto = edition_to_book(&edition, conn, &client).await?;
+pub async fn create_by_isbn(
+    isbn: &str,
+    conn: &sqlx::SqlitePool,
+    network: bool,
+) -> Result<tomex::types::edition::Edition> {
+    let fetched = fetch_by_isbn(isbn, conn, network).await?;
+    persist(fetched, conn).await
+}
 
-    let authors_auto = book_to_authors(&book_auto, conn, &client).await?;
+/// Let the user review the fetched records, then insert them
+pub async fn persist(
+    fetched: Fetched,
+    conn: &sqlx::SqlitePool,
+) -> Result<tomex::types::edition::Edition> {
+    if let Some(existing) = existing_edition(&fetched, conn).await? {
+        println!("Edition found in database: {existing}");
+        if !prompt_or_abort(|| {
+            inquire::Confirm::new("Add another copy of it?")
+                .with_default(false)
+                .prompt()
+        })? {
+            return Ok(existing);
+        }
+    }
+    let Fetched {
+        isbn,
+        edition,
+        book: book_auto,
+        authors: authors_auto,
+    } = fetched;
     let mut authors = Vec::with_capacity(authors_auto.len());
 
     info!("Review author information");
     for author in authors_auto {
-        let potential_author = Author::get_by_name(conn, author.name.clone()).await?;
+        let by_key = match &author.key {
+            Some(key) => Author::get_by_openlibrary_key(conn, &author_key(key)).await?,
+            None => None,
+        };
+        let potential_author = match by_key {
+            Some(author) => Some(author),
+            None => Author::get_by_name(conn, author.name.clone()).await?,
+        };
         match potential_author {
             Some(author_in_db) => {
                 println!("Author found in database: {author_in_db}");
 
-                if inquire::Confirm::new("Use this author?")
-                    .with_default(true)
-                    .prompt()?
-                {
-                    authors.push(author_in_db);
+                if prompt_or_abort(|| {
+                    inquire::Confirm::new("Use this author?")
+                        .with_default(true)
+                        .prompt()
+                })? {
+                    // Remember where the author came from for the next import
+                    let key = author.key.as_deref().map(author_key).map(Text);
+                    if author_in_db.openlibrary_key.is_none() && key.is_some() {
+                        let mut author_in_db = author_in_db;
+                        let new = Author {
+                            openlibrary_key: key,
+                            ..author_in_db.clone()
+                        };
+                        author_in_db.update(conn, new.clone()).await?;
+                        authors.push(new);
+                    } else {
+                        authors.push(author_in_db);
+                    }
                 } else {
                     // TODO: Extract this into a function, allow user to select an existing author
                     // if names don't match exactly
@@ -225,10 +365,11 @@ pub async fn create_by_isbn(
         Some(book_in_db) => {
             println!("Book found in database: {book_in_db}");
 
-            if inquire::Confirm::new("Use this book?")
-                .with_default(true)
-                .prompt()?
-            {
+            if prompt_or_abort(|| {
+                inquire::Confirm::new("Use this book?")
+                    .with_default(true)
+                    .prompt()
+            })? {
                 book_in_db
             } else {
                 let book_auto = build_book(book_auto, Some(authors)).await;
@@ -247,9 +388,93 @@ pub async fn create_by_isbn(
 
     // println!("Book:\n{}", serde_json::to_string_pretty(&book)?);
 
-    let edition_auto = build_edition(edition, book, isbn).await;
+    let edition_auto = build_edition(edition, book, &isbn).await;
     info!("Review edition information");
     let edition = PromptType::update_by_prompt(&edition_auto, "", conn).await?;
     edition.insert(conn).await?;
     Ok(edition)
 }
+
+/// Ask whether to replace `old` with `new` for the field `name`, fields
+/// OpenLibrary has nothing for are never cleared
+fn accept_change(name: &str, old: Option<String>, new: Option<String>) -> Result<bool> {
+    let Some(new) = new else {
+        return Ok(false);
+    };
+    if old.as_ref() == Some(&new) {
+        return Ok(false);
+    }
+    let message = format!(
+        "Change {name} from {} to {new}?",
+        old.unwrap_or_else(|| "nothing".into())
+    );
+    prompt_or_abort(|| inquire::Confirm::new(&message).with_default(true).prompt())
+}
+
+/// Fetch an edition from OpenLibrary again, by its key or otherwise its isbn,
+/// and take over the changed fields the user accepts
+pub async fn refresh_edition(
+    mut edition: Edition,
+    conn: &sqlx::SqlitePool,
+    network: bool,
+) -> Result<()> {
+    let client = client(network)?;
+    let fetched = match (&edition.openlibrary_key, &edition.isbn) {
+        (Some(key), _) => key_to_edition(&key.0, &client).await?,
+        (None, Some(isbn)) => isbn_to_edition(&isbn.0, conn, &client).await?,
+        (None, None) => {
+            return Err(tomex::error::Error::Usage(
+                "The edition has neither an OpenLibrary key nor an isbn".into(),
+            )
+            .into());
+        }
+    };
+    let book = Book::get_by_id(conn, &edition.book_id).await?;
+    let isbn = edition.isbn.clone().map(|x| x.0).unwrap_or_default();
+    let candidate = build_edition(fetched, book, &isbn).await;
+    // Relations are written by update too, so they have to be there
+    edition.hydrate(conn).await?;
+    let mut new = edition.clone();
+    let mut changed = 0;
+    let text = |x: &Option<Text>| x.as_ref().map(|x| x.0.clone());
+    let date = |x: &OptionalTimestamp| x.0.as_ref().map(|x| x.0.date_naive().to_string());
+    if accept_change(
+        "title",
+        text(&edition.edition_title),
+        text(&candidate.edition_title),
+    )? {
+        new.edition_title = candidate.edition_title.clone();
+        changed += 1;
+    }
+    if accept_change(
+        "pages",
+        edition.pages.map(|x| x.to_string()),
+        candidate.pages.map(|x| x.to_string()),
+    )? {
+        new.pages = candidate.pages;
+        changed += 1;
+    }
+    if accept_change(
+        "release date",
+        date(&edition.release_date),
+        date(&candidate.release_date),
+    )? {
+        new.release_date = candidate.release_date.clone();
+        changed += 1;
+    }
+    if accept_change(
+        "OpenLibrary key",
+        text(&edition.openlibrary_key),
+        text(&candidate.openlibrary_key),
+    )? {
+        new.openlibrary_key = candidate.openlibrary_key.clone();
+        changed += 1;
+    }
+    if changed == 0 {
+        println!("Nothing to update");
+        return Ok(());
+    }
+    edition.update(conn, new).await?;
+    println!("Updated {changed} field(s)");
+    Ok(())
+}
@@ -3,11 +3,63 @@ use reedline::{
     KeyModifiers, Reedline, ReedlineEvent, ReedlineMenu, Signal,
 };
 
+use tomex::{
+    traits::Queryable,
+    types::{
+        book::Book, edition::Edition, edition_review::EditionReview, review::Review, uuid::Uuid,
+    },
+};
+
 use crate::prompt::TomexPrompt;
 
 pub struct Repl {
-    reedline: Reedline,
-    prompt:   TomexPrompt,
+    reedline:       Reedline,
+    prompt:         TomexPrompt,
+    /// The record the last command showed, what `parent` starts from
+    pub last_shown: Option<Shown>,
+}
+
+/// A record a command showed by its uuid
+#[derive(Debug, Clone)]
+pub enum Shown {
+    /// Books have no parent, so which one doesn't matter
+    Book,
+    Edition(Uuid),
+    Review(Uuid),
+    EditionReview(Uuid),
+}
+
+impl Shown {
+    /// The record `query <type> --uuid` showed, if that's what ran
+    pub fn from_matches(matches: &clap::ArgMatches) -> Option<Self> {
+        let (name, matches) = matches.subcommand_matches("query")?.subcommand()?;
+        let uuid = matches.try_get_one::<String>("uuid").ok()??;
+        let uuid = Uuid(uuid::Uuid::parse_str(uuid).ok()?);
+        match name {
+            "book" => Some(Self::Book),
+            "edition" => Some(Self::Edition(uuid)),
+            "review" => Some(Self::Review(uuid)),
+            "edition-review" => Some(Self::EditionReview(uuid)),
+            _ => None,
+        }
+    }
+
+    /// The book an edition or (edition) review belongs to
+    pub async fn parent(&self, conn: &sqlx::SqlitePool) -> anyhow::Result<Book> {
+        let book_id = match self {
+            Self::Book => {
+                let message = "Books don't belong to anything, show an edition or review first";
+                return Err(tomex::error::Error::Usage(message.into()).into());
+            }
+            Self::Edition(id) => Edition::get_by_id(conn, id).await?.book_id,
+            Self::Review(id) => Review::get_by_id(conn, id).await?.book_id,
+            Self::EditionReview(id) => {
+                let edition_id = EditionReview::get_by_id(conn, id).await?.edition_id;
+                Edition::get_by_id(conn, &edition_id).await?.book_id
+            }
+        };
+        Book::get_by_id(conn, &book_id).await
+    }
 }
 
 impl Repl {
@@ -45,6 +97,7 @@ impl Repl {
         Repl {
             reedline: line_editor,
             prompt,
+            last_shown: None,
         }
     }
 
@@ -1,18 +1,46 @@
 use axum::extract::{Query, State};
-use axum::{extract::Path, http::StatusCode, routing::get, Router};
+use axum::response::{IntoResponse, Response};
+use axum::{Json, Router, extract::Path, http::StatusCode, routing::get};
 use local_ip_address::local_ip;
 use std::collections::HashMap;
 use std::net::SocketAddr;
 use std::sync::Arc;
 use tracing::{error, info};
 
+/// An error response, sent as `{"code": 500, "message": "..."}`
+pub struct ApiError {
+    code:    StatusCode,
+    message: String,
+}
+
+impl ApiError {
+    fn new(code: StatusCode, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let body = serde_json::json!({
+            "code": self.code.as_u16(),
+            "message": self.message,
+        });
+        (self.code, Json(body)).into_response()
+    }
+}
+
 pub struct TheStateOfAffairs {
-    conn: sqlx::SqlitePool,
+    conn:    sqlx::SqlitePool,
+    /// Whether isbns may be looked up on OpenLibrary, off with `--no-network`
+    network: bool,
 }
 
-pub async fn start(conn: &sqlx::SqlitePool) {
+pub async fn start(conn: &sqlx::SqlitePool, network: bool) {
     let conn = conn.clone();
-    let state = Arc::new(TheStateOfAffairs { conn });
+    let state = Arc::new(TheStateOfAffairs { conn, network });
 
     let app = Router::new()
         .route("/api/isbn", get(isbn_query))
@@ -23,6 +51,9 @@ pub async fn start(conn: &sqlx::SqlitePool) {
     let port = 3000;
     let addr = SocketAddr::from((ip, port));
     info!("Listening on {ip}:{port}.");
+    if !network {
+        info!("Network access is disabled, scanned isbns can't be looked up.");
+    }
     axum::Server::bind(&addr)
         .serve(app.into_make_service())
         .await
@@ -32,24 +63,32 @@ pub async fn start(conn: &sqlx::SqlitePool) {
 async fn isbn(
     Path(isbn): Path<String>,
     State(state): State<Arc<TheStateOfAffairs>>,
-) -> Result<String, StatusCode> {
+) -> Result<String, ApiError> {
     info!("Received {}.", isbn);
     match isbn.parse::<isbn2::Isbn>() {
         Ok(isbn) => {
-            match crate::openlibrary::create_by_isbn(&isbn.to_string(), &state.conn).await {
+            match crate::openlibrary::create_by_isbn(&isbn.to_string(), &state.conn, state.network)
+                .await
+            {
                 Ok(_) => {
                     info!("Handling of {} complete.", isbn);
                     Ok(format!("Handling of {} complete.", isbn))
                 }
                 Err(e) => {
                     error!("Handling of {} failed.\n{e}", isbn);
-                    Err(StatusCode::INTERNAL_SERVER_ERROR)
+                    Err(ApiError::new(
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                        format!("Handling of {} failed: {e}", isbn),
+                    ))
                 }
             }
         }
         Err(e) => {
             error!("{} is not an isbn.\n{e}", isbn);
-            Err(StatusCode::IM_A_TEAPOT)
+            Err(ApiError::new(
+                StatusCode::IM_A_TEAPOT,
+                format!("{} is not an isbn: {e}", isbn),
+            ))
         }
     }
 }
@@ -57,28 +96,41 @@ async fn isbn(
 async fn isbn_query(
     Query(params): Query<HashMap<String, String>>,
     State(state): State<Arc<TheStateOfAffairs>>,
-) -> Result<String, StatusCode> {
+) -> Result<String, ApiError> {
     let isbn = match params.get("content") {
         Some(isbn) => isbn,
-        None => return Err(StatusCode::IM_A_TEAPOT),
+        None => {
+            return Err(ApiError::new(
+                StatusCode::IM_A_TEAPOT,
+                "Missing `content` parameter",
+            ));
+        }
     };
     info!("Received {}.", isbn);
     match isbn.parse::<isbn2::Isbn>() {
         Ok(isbn) => {
-            match crate::openlibrary::create_by_isbn(&isbn.to_string(), &state.conn).await {
+            match crate::openlibrary::create_by_isbn(&isbn.to_string(), &state.conn, state.network)
+                .await
+            {
                 Ok(_) => {
                     info!("Handling of {} complete.", isbn);
                     Ok(format!("Handling of {} complete.", isbn))
                 }
                 Err(e) => {
                     error!("Handling of {} failed.\n{e}", isbn);
-                    Err(StatusCode::INTERNAL_SERVER_ERROR)
+                    Err(ApiError::new(
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                        format!("Handling of {} failed: {e}", isbn),
+                    ))
                 }
             }
         }
         Err(e) => {
             error!("{} is not an isbn.\n{e}", isbn);
-            Err(StatusCode::IM_A_TEAPOT)
+            Err(ApiError::new(
+                StatusCode::IM_A_TEAPOT,
+                format!("{} is not an isbn: {e}", isbn),
+            ))
         }
     }
 }
@@ -0,0 +1,50 @@
+use std::{any::Any, collections::HashMap, future::Future, sync::Mutex};
+
+use anyhow::Result;
+
+use crate::{traits::Queryable, types::uuid::Uuid};
+
+type Records = HashMap<(&'static str, uuid::Uuid), Box<dyn Any + Send>>;
+
+tokio::task_local! {
+    static CACHE: Mutex<Records>;
+}
+
+/// Run `f` with a fresh cache, so reference data like genres or authors that
+/// is looked up through [get_by_id] is only fetched from the database once.
+/// Records are cached for the whole of `f`, so it shouldn't change them.
+pub async fn scope<F: Future>(f: F) -> F::Output {
+    CACHE.scope(Mutex::new(HashMap::new()), f).await
+}
+
+/// Like [Queryable::get_by_id], but served from the cache when called inside
+/// [scope]
+pub async fn get_by_id<T>(conn: &sqlx::SqlitePool, id: &Uuid) -> Result<T>
+where
+    T: Queryable + Clone + Send + 'static,
+{
+    let key = (T::TABLE_NAME, id.0);
+    let cached = CACHE.try_with(|cache| {
+        cache
+            .lock()
+            .expect("Cache lock poisoned")
+            .get(&key)
+            .and_then(|x| x.downcast_ref::<T>())
+            .cloned()
+    });
+    match cached {
+        Ok(Some(x)) => Ok(x),
+        Ok(None) => {
+            let x = T::get_by_id(conn, id).await?;
+            CACHE.with(|cache| {
+                cache
+                    .lock()
+                    .expect("Cache lock poisoned")
+                    .insert(key, Box::new(x.clone()))
+            });
+            Ok(x)
+        }
+        // Not inside a scope
+        Err(_) => T::get_by_id(conn, id).await,
+    }
+}
@@ -1,7 +1,12 @@
-use std::path::PathBuf;
+use std::{
+    env,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::OnceLock,
+};
 
 use anyhow::Result;
-use crossterm::style::Stylize;
+use crossterm::style::{Color, Stylize};
 use figment::{
     providers::{Env, Format, Serialized, Toml},
     Figment,
@@ -10,16 +15,119 @@ use serde::{Deserialize, Serialize};
 
 use crate::{default_colors::*, traits::DisplayTerminal};
 
+/// Location of the config file if it was explicitly set, see [Config::set_path]
+static CONFIG_PATH: OnceLock<PathBuf> = OnceLock::new();
+
+/// The config records are formatted with, see [Config::cached]
+static CONFIG: OnceLock<Config> = OnceLock::new();
+
+/// Color as written in the config file
+///
+/// Accepts hex strings (`#rgb` or `#rrggbb`) as well as anything crossterm
+/// understands, like named colors (`dark_red`) or `rgb_(r,g,b)`.
+///
+/// ```
+/// use crossterm::style::Color;
+/// use tomex::config::ConfigColor;
+///
+/// let c: ConfigColor = "#cad3f5".parse().unwrap();
+/// assert_eq!(c.0, Color::Rgb { r: 202, g: 211, b: 245 });
+/// let c: ConfigColor = "#fa0".parse().unwrap();
+/// assert_eq!(c.0, Color::Rgb { r: 255, g: 170, b: 0 });
+/// let c: ConfigColor = "dark_red".parse().unwrap();
+/// assert_eq!(c.0, Color::DarkRed);
+/// let c: ConfigColor = "rgb_(1,2,3)".parse().unwrap();
+/// assert_eq!(c.0, Color::Rgb { r: 1, g: 2, b: 3 });
+/// assert!("#12345".parse::<ConfigColor>().is_err());
+/// assert!("not_a_color".parse::<ConfigColor>().is_err());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConfigColor(pub Color);
+
+impl ConfigColor {
+    fn parse_hex(hex: &str) -> Option<Color> {
+        if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            return None;
+        }
+        let channel = |s: &str| u8::from_str_radix(s, 16).ok();
+        let (r, g, b) = match hex.len() {
+            3 => (
+                channel(&hex[0..1].repeat(2))?,
+                channel(&hex[1..2].repeat(2))?,
+                channel(&hex[2..3].repeat(2))?,
+            ),
+            6 => (
+                channel(&hex[0..2])?,
+                channel(&hex[2..4])?,
+                channel(&hex[4..6])?,
+            ),
+            _ => return None,
+        };
+        Some(Color::Rgb { r, g, b })
+    }
+}
+
+impl FromStr for ConfigColor {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if let Some(hex) = s.strip_prefix('#') {
+            return Self::parse_hex(hex)
+                .map(ConfigColor)
+                .ok_or_else(|| anyhow::anyhow!("Invalid hex color `{}`", s));
+        }
+        let de = serde::de::value::StrDeserializer::<serde::de::value::Error>::new(s);
+        Color::deserialize(de)
+            .map(ConfigColor)
+            .map_err(|_| anyhow::anyhow!("Invalid color `{}`", s))
+    }
+}
+
+impl From<Color> for ConfigColor {
+    fn from(c: Color) -> Self {
+        ConfigColor(c)
+    }
+}
+
+impl Serialize for ConfigColor {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        match self.0 {
+            Color::Rgb { r, g, b } => {
+                serializer.serialize_str(&format!("#{:02x}{:02x}{:02x}", r, g, b))
+            }
+            c => c.serialize(serializer),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for ConfigColor {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct StyleConfig {
     bold:   bool,
     italic: bool,
-    color:  crossterm::style::Color,
+    color:  ConfigColor,
 }
 
 impl StyleConfig {
+    pub fn color(&self) -> Color {
+        self.color.0
+    }
+
     fn style(&self, s: impl ToString) -> String {
-        let mut s = s.to_string().with(self.color);
+        let mut s = s.to_string().with(self.color.0);
         if self.bold {
             s = s.bold();
         }
@@ -46,7 +154,7 @@ where
 impl Default for StyleConfig {
     fn default() -> Self {
         Self {
-            color:  COLOR_WHITE,
+            color:  COLOR_WHITE.into(),
             bold:   false,
             italic: false,
         }
@@ -138,9 +246,131 @@ impl Default for OutputConfig {
     }
 }
 
+/// How precisely timestamps are described, like "3 months ago" or "3 months,
+/// 2 days and 4 hours ago"
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TimestampAccuracy {
+    #[default]
+    Rough,
+    Precise,
+}
+
+/// Where the page count of a read edition comes from in stats and estimates
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PagesSource {
+    /// The edition's own page count, else the book's most common one
+    #[default]
+    Edition,
+    /// The book's most common page count, else the edition's own one
+    Book,
+}
+
+/// Order of the editions on the `reading` dashboard
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum ReadingSort {
+    /// Most recently updated first
+    #[default]
+    Recent,
+    /// Furthest along first, editions without a page count last
+    Percent,
+    /// By title, ignoring case
+    Title,
+}
+
+/// How text entered in prompts is cleaned up, it's always trimmed
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct TextConfig {
+    /// Replace runs of whitespace with a single space
+    pub collapse_whitespace: bool,
+    /// Capitalize every word in names of genres, moods, etc.
+    pub title_case_names:    bool,
+}
+
+/// Reference data inserted when a table is first created, unset lists use
+/// the built-in defaults
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct SeedConfig {
+    pub moods:            Option<Vec<String>>,
+    pub paces:            Option<Vec<String>>,
+    pub genres:           Option<Vec<String>>,
+    pub languages:        Option<Vec<String>>,
+    pub publishers:       Option<Vec<String>>,
+    pub bindings:         Option<Vec<String>>,
+    pub formats:          Option<Vec<String>>,
+    pub shelves:          Option<Vec<String>>,
+    pub content_warnings: Option<Vec<String>>,
+}
+
+impl SeedConfig {
+    /// Rows to seed a table with, `configured` names if set, otherwise
+    /// `defaults`. Configured names matching a default keep its uuid so
+    /// databases stay compatible.
+    ///
+    /// ```
+    /// use tomex::config::SeedConfig;
+    ///
+    /// let slow = uuid::Uuid::new_v4();
+    /// let defaults = vec![("Slow", slow), ("Fast", uuid::Uuid::new_v4())];
+    /// let seed = SeedConfig::resolve(Some(vec!["Slow".into(), "Glacial".into()]), defaults);
+    /// assert_eq!(seed.len(), 2);
+    /// assert_eq!(seed[0], ("Slow".to_string(), slow));
+    /// assert_eq!(seed[1].0, "Glacial");
+    /// ```
+    pub fn resolve(
+        configured: Option<Vec<String>>,
+        defaults: Vec<(&str, uuid::Uuid)>,
+    ) -> Vec<(String, uuid::Uuid)> {
+        match configured {
+            Some(names) => names
+                .into_iter()
+                .map(|name| {
+                    let id = defaults
+                        .iter()
+                        .find(|(default, _)| *default == name)
+                        .map(|(_, id)| *id)
+                        .unwrap_or_else(uuid::Uuid::new_v4);
+                    (name, id)
+                })
+                .collect(),
+            None => defaults
+                .into_iter()
+                .map(|(name, id)| (name.to_string(), id))
+                .collect(),
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Config {
     pub database_location:        std::path::PathBuf,
+    /// Where cover images attached with `--cover` are copied to
+    pub cover_dir:                std::path::PathBuf,
+    /// Reject progress updates that are out of order instead of warning
+    pub strict_progress:          bool,
+    /// Warn when adding a book whose title is at most this many edits away
+    /// from an existing one
+    pub duplicate_title_distance: usize,
+    /// Refuse adding an edition whose isbn another edition already has,
+    /// instead of only warning
+    pub enforce_unique_isbn:      bool,
+    /// Show isbns hyphenated, they're always stored as plain ISBN-13
+    pub hyphenate_isbn:           bool,
+    /// Create new tables without default genres, moods, etc., like `--no-seed`
+    pub no_seed:                  bool,
+    /// How precisely timestamps are described, `rough` or `precise`
+    pub timestamp_accuracy:       TimestampAccuracy,
+    /// Which page count stats and estimates use, `edition` or `book`
+    pub pages_source:             PagesSource,
+    /// Show the cover, typesetting, material and price ratings of edition
+    /// reviews, like `C:90 T:80 M:70 P:85`
+    pub edition_review_aspects:   bool,
+    /// Order of the `reading` dashboard, `recent`, `percent` or `title`
+    pub reading_sort:             ReadingSort,
+    /// Show at most this many editions on the `reading` dashboard, 0 for all
+    pub reading_limit:            usize,
     pub output_uuid:              OutputConfig,
     pub output_timestamp:         OutputConfig,
     pub output_author:            OutputConfig,
@@ -148,12 +378,15 @@ pub struct Config {
     pub output_pace:              OutputConfig,
     pub output_book:              OutputConfig,
     pub output_genre:             OutputConfig,
+    pub output_shelf:             OutputConfig,
+    pub output_content_warning:   OutputConfig,
     pub output_edition:           OutputConfig,
     pub output_progress:          OutputConfig,
     pub output_language:          OutputConfig,
     pub output_publisher:         OutputConfig,
     pub output_edition_review:    OutputConfig,
     pub output_rating:            OutputConfig,
+    pub output_aspect_ratings:    OutputConfig,
     pub output_recommended_true:  OutputConfig,
     pub output_recommended_false: OutputConfig,
     pub output_last_updated:      OutputConfig,
@@ -166,8 +399,14 @@ pub struct Config {
     pub output_binding:           OutputConfig,
     pub output_dimensions:        OutputConfig,
     pub output_price:             OutputConfig,
+    pub output_acquired:          OutputConfig,
+    pub output_notes:             OutputConfig,
     pub output_part_index:        OutputConfig,
     pub output_error:             OutputConfig,
+    /// Headers printed by `--group-by`
+    pub output_group:             OutputConfig,
+    pub seed:                     SeedConfig,
+    pub text:                     TextConfig,
 }
 
 impl Config {
@@ -175,12 +414,181 @@ impl Config {
         Ok(toml::to_string(&Self::default())?)
     }
 
+    /// Default location of the config file,
+    /// `$XDG_CONFIG_HOME/tomex/config.toml` falling back to
+    /// `~/.config/tomex/config.toml`
+    pub fn default_path() -> PathBuf {
+        let config_home = match env::var("XDG_CONFIG_HOME") {
+            Ok(dir) if !dir.is_empty() => PathBuf::from(dir),
+            _ => PathBuf::from(shellexpand::tilde("~/.config").into_owned()),
+        };
+        config_home.join("tomex").join("config.toml")
+    }
+
+    /// Override the location of the config file for the rest of the process
+    pub fn set_path(path: PathBuf) -> Result<()> {
+        CONFIG_PATH
+            .set(path)
+            .map_err(|_| anyhow::anyhow!("Location of config file was already set"))
+    }
+
+    /// Location of the config file, in order of precedence this is the path
+    /// set by [Config::set_path], the `TOMEX_CONFIG` environment variable or
+    /// [Config::default_path]
+    pub fn path() -> PathBuf {
+        if let Some(path) = CONFIG_PATH.get() {
+            return path.clone();
+        }
+        match env::var("TOMEX_CONFIG") {
+            Ok(path) if !path.is_empty() => PathBuf::from(shellexpand::tilde(&path).into_owned()),
+            _ => Self::default_path(),
+        }
+    }
+
+    /// Where the database goes if `database_location` can't be used,
+    /// `$XDG_DATA_HOME/tomex/database`
+    pub fn default_database_location() -> PathBuf {
+        let data_home = match env::var("XDG_DATA_HOME") {
+            Ok(dir) if !dir.is_empty() => PathBuf::from(dir),
+            _ => PathBuf::from(shellexpand::tilde("~/.local/share").into_owned()),
+        };
+        data_home.join("tomex").join("database")
+    }
+
+    /// `database_location` with `~` and environment variables expanded
+    ///
+    /// ```
+    /// use tomex::config::Config;
+    ///
+    /// let config = Config {
+    ///     database_location: "$TOMEX_SURELY_UNSET/database".into(),
+    ///     ..Config::default()
+    /// };
+    /// let err = config.database_path().unwrap_err().to_string();
+    /// assert!(err.contains("$TOMEX_SURELY_UNSET/database"));
+    /// assert!(err.contains("TOMEX_SURELY_UNSET isn't set"));
+    /// ```
+    pub fn database_path(&self) -> Result<PathBuf> {
+        Self::expand_path(&self.database_location, "database_location")
+    }
+
+    /// `cover_dir` with `~` and environment variables expanded
+    pub fn cover_path(&self) -> Result<PathBuf> {
+        Self::expand_path(&self.cover_dir, "cover_dir")
+    }
+
+    /// Expand `~` and environment variables in the path set as `key`
+    fn expand_path(path: &Path, key: &str) -> Result<PathBuf> {
+        let location = path.to_str().ok_or(anyhow::anyhow!(
+            "{key} {} isn't valid unicode",
+            path.display()
+        ))?;
+        match shellexpand::full(location) {
+            Ok(path) => Ok(PathBuf::from(path.into_owned())),
+            Err(e) => anyhow::bail!(
+                "Couldn't expand {key} `{location}`: {} isn't set. Set it or change {key} in {}",
+                e.var_name,
+                Self::path().display()
+            ),
+        }
+    }
+
+    /// Write the default config to `path`, refusing to replace an existing
+    /// file unless `force` is set
+    pub fn init(path: &Path, force: bool) -> Result<()> {
+        if path.exists() && !force {
+            anyhow::bail!(
+                "Config file already exists at {}, use --force to overwrite it",
+                path.display()
+            );
+        }
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, Self::default_as_string()?)?;
+        Ok(())
+    }
+
+    /// The config, read once and kept for every later call. Used where errors
+    /// can't be reported, like [Display](std::fmt::Display) impls, so if it
+    /// can't be read this warns once and falls back to the defaults instead
+    ///
+    /// ```
+    /// use tomex::config::Config;
+    /// use tomex::types::{genre::Genre, text::Text};
+    ///
+    /// let path = std::env::temp_dir().join(format!("tomex-{}.toml", std::process::id()));
+    /// std::fs::write(&path, "hyphenate_isbn = \"not a bool\"\nthis isn't toml").unwrap();
+    /// Config::set_path(path.clone()).unwrap();
+    /// assert_eq!(Config::cached().hyphenate_isbn, Config::default().hyphenate_isbn);
+    /// let genre = Genre {
+    ///     name: Text("Fantasy".into()),
+    ///     ..Genre::default()
+    /// };
+    /// assert!(genre.to_string().contains("Fantasy"));
+    /// std::fs::remove_file(path).unwrap();
+    /// ```
+    pub fn cached() -> &'static Self {
+        CONFIG.get_or_init(|| {
+            Self::read_config().unwrap_or_else(|e| {
+                eprintln!("Couldn't read the config, using the defaults instead: {e:#}");
+                Self::default()
+            })
+        })
+    }
+
     pub fn read_config() -> Result<Self> {
-        Ok(Figment::new()
+        Self::read_config_from(&Self::path())
+    }
+
+    pub fn read_config_from(path: &Path) -> Result<Self> {
+        Ok(Self::figment(path).extract()?)
+    }
+
+    fn figment(path: &Path) -> Figment {
+        Figment::new()
             .merge(Serialized::defaults(Config::default()))
-            .merge(Toml::file("config.toml"))
-            .merge(Env::prefixed("TOMEX_"))
-            .extract()?)
+            .merge(Toml::file(path))
+            .merge(Env::prefixed("TOMEX_").ignore(&["config"]))
+    }
+
+    /// Check the config file at `path` for unknown keys and invalid values
+    /// (like colors crossterm doesn't understand), returns a description of
+    /// every problem found
+    pub fn check(path: &Path) -> Result<Vec<String>> {
+        if !path.exists() {
+            return Ok(vec![]);
+        }
+        let parsed = match std::fs::read_to_string(path)?.parse::<toml::Table>() {
+            Ok(parsed) => parsed,
+            Err(e) => return Ok(vec![e.to_string()]),
+        };
+        let known = toml::Table::try_from(Self::default())?;
+        let mut problems = vec![];
+        unknown_keys(&parsed, &known, "", &mut problems);
+        if let Err(errors) = Self::figment(path).extract::<Self>() {
+            problems.extend(errors.into_iter().map(|e| e.to_string()));
+        }
+        Ok(problems)
+    }
+}
+
+/// Collect keys which are present in `parsed` but not in `known`, recursing
+/// into tables which exist in both
+fn unknown_keys(parsed: &toml::Table, known: &toml::Table, prefix: &str, found: &mut Vec<String>) {
+    for (key, value) in parsed {
+        let path = if prefix.is_empty() {
+            key.to_string()
+        } else {
+            format!("{prefix}.{key}")
+        };
+        match (value, known.get(key)) {
+            (_, None) => found.push(format!("Unknown key `{path}`")),
+            (toml::Value::Table(parsed), Some(toml::Value::Table(known))) => {
+                unknown_keys(parsed, known, &path, found)
+            }
+            _ => (),
+        }
     }
 }
 
@@ -188,18 +596,29 @@ impl Default for Config {
     fn default() -> Self {
         Self {
             database_location:        PathBuf::from("~/.local/share/tomex/database"),
+            cover_dir:                PathBuf::from("~/.local/share/tomex/covers"),
+            strict_progress:          false,
+            duplicate_title_distance: 3,
+            enforce_unique_isbn:      false,
+            hyphenate_isbn:           true,
+            no_seed:                  false,
+            timestamp_accuracy:       TimestampAccuracy::Rough,
+            pages_source:             PagesSource::Edition,
+            edition_review_aspects:   true,
+            reading_sort:             ReadingSort::Recent,
+            reading_limit:            0,
             output_uuid:              OutputConfig {
                 prefix: "(".into(),
                 suffix: ")".into(),
                 style_content: StyleConfig {
-                    color: COLOR_DIMMED,
+                    color: COLOR_DIMMED.into(),
                     ..StyleConfig::default()
                 },
                 ..OutputConfig::default()
             },
             output_timestamp:         OutputConfig {
                 style_content: StyleConfig {
-                    color: COLOR_TIMESTAMP,
+                    color: COLOR_TIMESTAMP.into(),
                     ..StyleConfig::default()
                 },
                 ..OutputConfig::default()
@@ -208,7 +627,7 @@ impl Default for Config {
                 description: "Written by:".into(),
                 separator: " and ".into(),
                 style_content: StyleConfig {
-                    color: COLOR_AUTHOR,
+                    color: COLOR_AUTHOR.into(),
                     ..StyleConfig::default()
                 },
                 ..OutputConfig::default()
@@ -216,7 +635,7 @@ impl Default for Config {
             output_mood:              OutputConfig {
                 description: "Moods:".into(),
                 style_content: StyleConfig {
-                    color: COLOR_MOOD,
+                    color: COLOR_MOOD.into(),
                     ..StyleConfig::default()
                 },
                 ..OutputConfig::default()
@@ -224,14 +643,14 @@ impl Default for Config {
             output_pace:              OutputConfig {
                 description: "Pace:".into(),
                 style_content: StyleConfig {
-                    color: COLOR_PACE,
+                    color: COLOR_PACE.into(),
                     ..StyleConfig::default()
                 },
                 ..OutputConfig::default()
             },
             output_book:              OutputConfig {
                 style_content: StyleConfig {
-                    color: COLOR_BOOK,
+                    color: COLOR_BOOK.into(),
                     ..StyleConfig::default()
                 },
                 ..OutputConfig::default()
@@ -239,7 +658,23 @@ impl Default for Config {
             output_genre:             OutputConfig {
                 description: "Genres:".into(),
                 style_content: StyleConfig {
-                    color: COLOR_GENRE,
+                    color: COLOR_GENRE.into(),
+                    ..StyleConfig::default()
+                },
+                ..OutputConfig::default()
+            },
+            output_shelf:             OutputConfig {
+                description: "Shelves:".into(),
+                style_content: StyleConfig {
+                    color: COLOR_SHELF.into(),
+                    ..StyleConfig::default()
+                },
+                ..OutputConfig::default()
+            },
+            output_content_warning:   OutputConfig {
+                description: "Content warnings:".into(),
+                style_content: StyleConfig {
+                    color: COLOR_CONTENT_WARNING.into(),
                     ..StyleConfig::default()
                 },
                 ..OutputConfig::default()
@@ -247,7 +682,7 @@ impl Default for Config {
             output_edition:           OutputConfig {
                 display_uuid: true,
                 style_content: StyleConfig {
-                    color: COLOR_EDITION,
+                    color: COLOR_EDITION.into(),
                     bold: true,
                     ..StyleConfig::default()
                 },
@@ -255,7 +690,7 @@ impl Default for Config {
             },
             output_progress:          OutputConfig {
                 style_content: StyleConfig {
-                    color: COLOR_EDITION,
+                    color: COLOR_EDITION.into(),
                     bold: true,
                     ..StyleConfig::default()
                 },
@@ -265,7 +700,7 @@ impl Default for Config {
                 description: "Written in:".into(),
                 separator: " and ".into(),
                 style_content: StyleConfig {
-                    color: COLOR_LANGUAGE,
+                    color: COLOR_LANGUAGE.into(),
                     ..StyleConfig::default()
                 },
                 ..OutputConfig::default()
@@ -273,14 +708,14 @@ impl Default for Config {
             output_publisher:         OutputConfig {
                 description: "Publisher:".into(),
                 style_content: StyleConfig {
-                    color: COLOR_PUBLISHER,
+                    color: COLOR_PUBLISHER.into(),
                     ..StyleConfig::default()
                 },
                 ..OutputConfig::default()
             },
             output_edition_review:    OutputConfig {
                 style_content: StyleConfig {
-                    color: COLOR_EDITION_REVIEW,
+                    color: COLOR_EDITION_REVIEW.into(),
                     ..StyleConfig::default()
                 },
                 ..OutputConfig::default()
@@ -288,7 +723,15 @@ impl Default for Config {
             output_rating:            OutputConfig {
                 description: "Rating:".into(),
                 style_content: StyleConfig {
-                    color: COLOR_RATING,
+                    color: COLOR_RATING.into(),
+                    ..StyleConfig::default()
+                },
+                ..OutputConfig::default()
+            },
+            output_aspect_ratings:    OutputConfig {
+                description: "Aspects:".into(),
+                style_content: StyleConfig {
+                    color: COLOR_RATING.into(),
                     ..StyleConfig::default()
                 },
                 ..OutputConfig::default()
@@ -297,7 +740,7 @@ impl Default for Config {
                 description: "Recommended:".into(),
                 style_content: StyleConfig {
                     bold: true,
-                    color: COLOR_RECOMMENDED_TRUE,
+                    color: COLOR_RECOMMENDED_TRUE.into(),
                     ..StyleConfig::default()
                 },
                 ..OutputConfig::default()
@@ -306,7 +749,7 @@ impl Default for Config {
                 description: "Recommended:".into(),
                 style_content: StyleConfig {
                     bold: true,
-                    color: COLOR_RECOMMENDED_FALSE,
+                    color: COLOR_RECOMMENDED_FALSE.into(),
                     ..StyleConfig::default()
                 },
                 ..OutputConfig::default()
@@ -329,7 +772,7 @@ impl Default for Config {
                     ..StyleConfig::default()
                 },
                 style_content: StyleConfig {
-                    color: COLOR_PAGE_COUNT,
+                    color: COLOR_PAGE_COUNT.into(),
                     ..StyleConfig::default()
                 },
                 ..OutputConfig::default()
@@ -345,7 +788,7 @@ impl Default for Config {
             },
             output_series:            OutputConfig {
                 style_content: StyleConfig {
-                    color: COLOR_SERIES,
+                    color: COLOR_SERIES.into(),
                     ..StyleConfig::default()
                 },
                 ..OutputConfig::default()
@@ -365,7 +808,7 @@ impl Default for Config {
             output_format:            OutputConfig {
                 description: "Format:".into(),
                 style_content: StyleConfig {
-                    color: COLOR_FORMAT,
+                    color: COLOR_FORMAT.into(),
                     ..StyleConfig::default()
                 },
                 ..OutputConfig::default()
@@ -373,7 +816,7 @@ impl Default for Config {
             output_binding:           OutputConfig {
                 description: "Binding:".into(),
                 style_content: StyleConfig {
-                    color: COLOR_BINDING,
+                    color: COLOR_BINDING.into(),
                     ..StyleConfig::default()
                 },
                 ..OutputConfig::default()
@@ -386,15 +829,27 @@ impl Default for Config {
             },
             output_price:             OutputConfig {
                 style_content: StyleConfig {
-                    color: COLOR_PRICE,
+                    color: COLOR_PRICE.into(),
                     ..StyleConfig::default()
                 },
                 ..OutputConfig::default()
             },
+            output_acquired:          OutputConfig {
+                description: "Acquired".into(),
+                style_content: StyleConfig {
+                    color: COLOR_PRICE.into(),
+                    ..StyleConfig::default()
+                },
+                ..OutputConfig::default()
+            },
+            output_notes:             OutputConfig {
+                description: "Notes:".into(),
+                ..OutputConfig::default()
+            },
             output_part_index:        OutputConfig {
                 description: "Volume".into(),
                 style_content: StyleConfig {
-                    color: COLOR_PART_INDEX,
+                    color: COLOR_PART_INDEX.into(),
                     ..StyleConfig::default()
                 },
                 ..OutputConfig::default()
@@ -402,11 +857,20 @@ impl Default for Config {
             output_error:             OutputConfig {
                 description: "Error".into(),
                 style_content: StyleConfig {
-                    color: COLOR_ERROR,
+                    color: COLOR_ERROR.into(),
+                    ..StyleConfig::default()
+                },
+                ..OutputConfig::default()
+            },
+            output_group:             OutputConfig {
+                style_content: StyleConfig {
+                    bold: true,
                     ..StyleConfig::default()
                 },
                 ..OutputConfig::default()
             },
+            seed:                     SeedConfig::default(),
+            text:                     TextConfig::default(),
         }
     }
 }
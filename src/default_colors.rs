@@ -111,6 +111,16 @@ pub const COLOR_PART_INDEX: Color = Color::Rgb {
     g: 160,
     b: 246,
 };
+pub const COLOR_SHELF: Color = Color::Rgb {
+    r: 183,
+    g: 189,
+    b: 248,
+};
+pub const COLOR_CONTENT_WARNING: Color = Color::Rgb {
+    r: 238,
+    g: 212,
+    b: 159,
+};
 pub const COLOR_ERROR: Color = Color::Rgb {
     r: 237,
     g: 135,
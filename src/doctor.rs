@@ -0,0 +1,281 @@
+use std::fmt::Display;
+
+use anyhow::Result;
+use crossterm::style::Stylize;
+
+use crate::{
+    default_colors::COLOR_DIMMED,
+    traits::*,
+    types::{
+        author::Author, binding::Binding, book::Book, edition::Edition, format::EditionFormat,
+        genre::Genre, junction_tables::book_author::BookAuthor, language::Language, mood::Mood,
+        publisher::Publisher, timestamp::NONE_SENTINEL,
+    },
+};
+
+/// Reference records of one type which nothing links to
+pub struct Unused {
+    /// Plural name of the type, like "genres"
+    pub name:    &'static str,
+    /// The unused records and whether each is one of the built-in defaults
+    pub records: Vec<(String, bool)>,
+    /// How many of them were removed
+    pub pruned:  usize,
+}
+
+impl Unused {
+    /// Find unused records of `T`, removing the ones that aren't defaults if
+    /// `prune` is set
+    pub async fn new<T>(conn: &sqlx::SqlitePool, prune: bool) -> Result<Self>
+    where
+        T: Referenced + Display,
+    {
+        let mut records = vec![];
+        let mut pruned = 0;
+        for x in T::get_all_unused(conn).await? {
+            let default = x.is_default().await;
+            if prune && !default {
+                x.remove(conn).await?;
+                pruned += 1;
+            }
+            records.push((x.to_string(), default));
+        }
+        Ok(Self {
+            name: T::NAME_PLURAL,
+            records,
+            pruned,
+        })
+    }
+
+    /// Unused genres, moods, languages, publishers, bindings and formats
+    pub async fn all(conn: &sqlx::SqlitePool, prune: bool) -> Result<Vec<Self>> {
+        Ok(vec![
+            Self::new::<Genre>(conn, prune).await?,
+            Self::new::<Mood>(conn, prune).await?,
+            Self::new::<Language>(conn, prune).await?,
+            Self::new::<Publisher>(conn, prune).await?,
+            Self::new::<Binding>(conn, prune).await?,
+            Self::new::<EditionFormat>(conn, prune).await?,
+        ])
+    }
+
+    /// Render as a header followed by one line per record, defaults marked
+    pub fn render(&self) -> String {
+        let mut s = format!("Unused {} ({}):", self.name, self.records.len());
+        for (name, default) in &self.records {
+            s.push_str(&format!("\n • {name}"));
+            if *default {
+                s.push_str(&format!(" {}", "(default)".with(COLOR_DIMMED)));
+            }
+        }
+        if self.pruned > 0 {
+            s.push_str(&format!("\nRemoved {} of them", self.pruned));
+        }
+        s
+    }
+}
+
+/// Editions whose book was removed or doesn't exist, which can't be reached
+/// from the book anymore
+pub struct OrphanedEditions {
+    pub editions: Vec<Edition>,
+}
+
+impl OrphanedEditions {
+    pub async fn new(conn: &sqlx::SqlitePool) -> Result<Self> {
+        let editions = sqlx::query_as::<_, Edition>(&format!(
+            "SELECT * FROM {} e WHERE e.deleted = 0 AND NOT EXISTS \
+             (SELECT 1 FROM {} b WHERE b.id = e.book_id AND b.deleted = 0);",
+            Edition::TABLE_NAME,
+            Book::TABLE_NAME,
+        ))
+        .fetch_all(conn)
+        .await?;
+        Ok(Self { editions })
+    }
+
+    /// Render as a header followed by one line per edition
+    pub fn render(&self) -> String {
+        let mut s = format!("Orphaned editions ({}):", self.editions.len());
+        for edition in &self.editions {
+            s.push_str(&format!(
+                "\n • {edition} {}",
+                format!("(book {})", edition.book_id).with(COLOR_DIMMED)
+            ));
+        }
+        s
+    }
+
+    /// Ask for a new book for every orphaned edition, skipping one leaves it
+    /// where it is. Returns how many were moved
+    pub async fn reassign_by_prompt(&self, conn: &sqlx::SqlitePool) -> Result<usize> {
+        let mut moved = 0;
+        for edition in &self.editions {
+            println!("Edition without a book: {edition}");
+            let Some(book) = Book::query_or_create_by_prompt_skippable(conn).await? else {
+                continue;
+            };
+            let mut edition = edition.clone();
+            // Update writes the relations too, so they have to be there
+            edition.hydrate(conn).await?;
+            let new = Edition {
+                book_id: book.id.clone(),
+                book_title: book.title.clone(),
+                ..edition.clone()
+            };
+            edition.update(conn, new).await?;
+            moved += 1;
+        }
+        Ok(moved)
+    }
+}
+
+/// Editions whose cover file is gone or empty
+pub struct BrokenCovers {
+    /// The editions and what's wrong with their cover
+    pub editions: Vec<(Edition, &'static str)>,
+}
+
+impl BrokenCovers {
+    pub async fn new(conn: &sqlx::SqlitePool) -> Result<Self> {
+        let mut editions = vec![];
+        for edition in Edition::get_all(conn).await? {
+            let Some(cover) = &edition.cover else {
+                continue;
+            };
+            let problem = match std::fs::metadata(cover) {
+                Ok(metadata) if !metadata.is_file() => "not a file",
+                Ok(metadata) if metadata.len() == 0 => "empty",
+                Ok(_) => continue,
+                Err(_) => "missing",
+            };
+            editions.push((edition, problem));
+        }
+        Ok(Self { editions })
+    }
+
+    /// Render as a header followed by one line per edition
+    pub fn render(&self) -> String {
+        let mut s = format!("Editions with broken covers ({}):", self.editions.len());
+        for (edition, problem) in &self.editions {
+            let cover = edition.cover.as_deref().unwrap_or_default();
+            s.push_str(&format!(
+                "\n • {edition} {}",
+                format!("({problem}: {cover})").with(COLOR_DIMMED)
+            ));
+        }
+        s
+    }
+}
+
+/// Something books or editions are often imported without
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MissingField {
+    /// Books without an author
+    Author,
+    /// Books without a release date
+    ReleaseDate,
+    /// Editions without a page count
+    Pages,
+    /// Books none of whose editions has an isbn
+    Isbn,
+}
+
+impl MissingField {
+    pub const ALL: [Self; 4] = [Self::Author, Self::ReleaseDate, Self::Pages, Self::Isbn];
+
+    /// Header of the list of records missing it
+    ///
+    /// ```
+    /// use tomex::doctor::MissingField;
+    ///
+    /// assert_eq!(MissingField::Pages.description(), "Editions without a page count");
+    /// ```
+    pub fn description(&self) -> &'static str {
+        match self {
+            Self::Author => "Books without an author",
+            Self::ReleaseDate => "Books without a release date",
+            Self::Pages => "Editions without a page count",
+            Self::Isbn => "Books without an isbn on any edition",
+        }
+    }
+}
+
+/// Records which are missing one field, ignoring removed ones
+pub struct Incomplete {
+    pub field:   MissingField,
+    pub records: Vec<String>,
+}
+
+impl Incomplete {
+    pub async fn new(conn: &sqlx::SqlitePool, field: MissingField) -> Result<Self> {
+        let books = |condition: String| {
+            format!(
+                "SELECT * FROM {} b WHERE b.deleted = 0 AND {condition};",
+                Book::TABLE_NAME
+            )
+        };
+        let records: Vec<String> = match field {
+            MissingField::Author => sqlx::query_as::<_, Book>(&books(format!(
+                "NOT EXISTS (SELECT 1 FROM {} j JOIN {} a ON a.id = j.author_id \
+                 WHERE j.book_id = b.id AND a.deleted = 0)",
+                <BookAuthor as JunctionTable<Book, Author>>::TABLE_NAME,
+                Author::TABLE_NAME,
+            )))
+            .fetch_all(conn)
+            .await?
+            .iter()
+            .map(Book::to_string)
+            .collect(),
+            MissingField::ReleaseDate => sqlx::query_as::<_, Book>(&books(
+                "(b.release_date IS NULL OR b.release_date = ?1)".to_string(),
+            ))
+            .bind(NONE_SENTINEL)
+            .fetch_all(conn)
+            .await?
+            .iter()
+            .map(Book::to_string)
+            .collect(),
+            MissingField::Pages => sqlx::query_as::<_, Edition>(&format!(
+                "SELECT * FROM {} e WHERE e.deleted = 0 AND e.pages IS NULL AND EXISTS \
+                 (SELECT 1 FROM {} b WHERE b.id = e.book_id AND b.deleted = 0);",
+                Edition::TABLE_NAME,
+                Book::TABLE_NAME,
+            ))
+            .fetch_all(conn)
+            .await?
+            .iter()
+            .map(Edition::to_string)
+            .collect(),
+            MissingField::Isbn => sqlx::query_as::<_, Book>(&books(format!(
+                "NOT EXISTS (SELECT 1 FROM {} e WHERE e.book_id = b.id AND e.deleted = 0 \
+                 AND e.isbn IS NOT NULL AND e.isbn != '')",
+                Edition::TABLE_NAME,
+            )))
+            .fetch_all(conn)
+            .await?
+            .iter()
+            .map(Book::to_string)
+            .collect(),
+        };
+        Ok(Self { field, records })
+    }
+
+    /// Records missing any of `fields`, one list per field
+    pub async fn all(conn: &sqlx::SqlitePool, fields: &[MissingField]) -> Result<Vec<Self>> {
+        let mut incomplete = vec![];
+        for field in fields {
+            incomplete.push(Self::new(conn, *field).await?);
+        }
+        Ok(incomplete)
+    }
+
+    /// Render as a header followed by one line per record
+    pub fn render(&self) -> String {
+        let mut s = format!("{} ({}):", self.field.description(), self.records.len());
+        for record in &self.records {
+            s.push_str(&format!("\n • {record}"));
+        }
+        s
+    }
+}
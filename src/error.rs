@@ -0,0 +1,62 @@
+use std::fmt::Display;
+
+/// Exit code when everything went fine
+pub const EXIT_OK: i32 = 0;
+/// Exit code for errors without a more specific code
+pub const EXIT_ERROR: i32 = 1;
+/// Exit code when the command line couldn't be understood
+pub const EXIT_USAGE: i32 = 2;
+/// Exit code when a requested record doesn't exist
+pub const EXIT_NOT_FOUND: i32 = 3;
+
+/// Errors which should be reported with a specific exit code, everything else
+/// can stay a plain [anyhow::Error]
+#[derive(Debug)]
+pub enum Error {
+    NotFound(String),
+    Usage(String),
+    /// The user cancelled the command, not an error as such
+    Aborted(String),
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::NotFound(s) | Error::Usage(s) | Error::Aborted(s) => write!(f, "{s}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+}
+
+/// Exit code for an error, looking through the chain for an [Error] or a
+/// missing row
+///
+/// ```
+/// use tomex::error::*;
+///
+/// let e = anyhow::Error::from(Error::NotFound("No book with id 1".into()));
+/// assert_eq!(exit_code(&e), EXIT_NOT_FOUND);
+/// assert_eq!(exit_code(&e.context("Couldn't edit book")), EXIT_NOT_FOUND);
+/// assert_eq!(exit_code(&anyhow::anyhow!("Something else")), EXIT_ERROR);
+/// ```
+pub fn exit_code(e: &anyhow::Error) -> i32 {
+    for cause in e.chain() {
+        match cause.downcast_ref::<Error>() {
+            Some(Error::NotFound(_)) => return EXIT_NOT_FOUND,
+            Some(Error::Usage(_)) => return EXIT_USAGE,
+            Some(Error::Aborted(_)) | None => (),
+        }
+        if let Some(sqlx::Error::RowNotFound) = cause.downcast_ref::<sqlx::Error>() {
+            return EXIT_NOT_FOUND;
+        }
+    }
+    EXIT_ERROR
+}
+
+/// Whether the command was cancelled by the user, see [Error::Aborted]
+pub fn is_aborted(e: &anyhow::Error) -> bool {
+    e.chain()
+        .any(|x| matches!(x.downcast_ref::<Error>(), Some(Error::Aborted(_))))
+}
@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, io::Write};
 
 use anyhow::Result;
 use serde::Serialize;
@@ -6,8 +6,11 @@ use serde::Serialize;
 use crate::{
     traits::Queryable,
     types::{
+        book::Book,
+        book_shelf::BookShelf,
         edition::Edition,
-        progress::{PagesProgress, Progress}, book::Book,
+        progress::{PagesProgress, Progress},
+        review::Review,
     },
 };
 
@@ -61,6 +64,9 @@ pub struct Export {
     read_count: Option<String>,
     #[serde(rename = "Owned Copies")]
     owned_copies: String,
+    /// Rating out of 100, Goodreads' `My Rating` is left empty
+    #[serde(skip)]
+    rating: Option<u32>,
 }
 
 impl Export {
@@ -96,9 +102,45 @@ impl Export {
         }
         let mut result = Vec::new();
         for (edition_id, (timestamp_started, timestamp_finished)) in editions_read.into_iter() {
-            let edition = Edition::get_by_id(conn, &crate::types::uuid::Uuid(edition_id)).await?;
-            let book = Book::get_by_id(conn, &edition.book_id).await?;
-            let authors = book.get_authors(conn).await?;
+            let mut edition =
+                Edition::get_by_id(conn, &crate::types::uuid::Uuid(edition_id)).await?;
+            edition.hydrate(conn).await?;
+            let mut book = Book::get_by_id(conn, &edition.book_id).await?;
+            book.hydrate(conn).await?;
+            let authors = book.authors.clone();
+            let review = Review::get_all_for_book(conn, &book.id)
+                .await?
+                .into_iter()
+                .max_by(|a, b| {
+                    a.timestamp_updated
+                        .partial_cmp(&b.timestamp_updated)
+                        .unwrap()
+                });
+            let acquired = match (&edition.acquired_from, &edition.acquired_date.0) {
+                (Some(from), Some(date)) => Some(format!(
+                    "Acquired: {} ({})",
+                    from.0,
+                    date.0.format("%Y/%m/%d")
+                )),
+                (Some(from), None) => Some(format!("Acquired: {}", from.0)),
+                (None, Some(date)) => Some(format!("Acquired: {}", date.0.format("%Y/%m/%d"))),
+                (None, None) => None,
+            };
+            let shelves = book.shelves.clone().unwrap_or_default();
+            let mut positions = Vec::with_capacity(shelves.len());
+            for shelf in &shelves {
+                let position = BookShelf::position(conn, &book, shelf).await?;
+                positions.push(format!("{} (#{position})", shelf.name.0));
+            }
+            let exclusive_shelf = match shelves.iter().find(|x| x.exclusive) {
+                Some(shelf) => shelf.name.0.clone(),
+                // Everything exported has been read
+                None => "read".into(),
+            };
+            let shelves = shelves
+                .into_iter()
+                .map(|x| x.name.0)
+                .collect::<Vec<String>>();
             result.push(Self {
                 isbn: Some(format!("=\"{}\"", "")),
                 isbn13: Some(format!(
@@ -110,38 +152,227 @@ impl Export {
                 )),
                 title: Some(match edition.edition_title {
                     Some(s) => s.0,
-                    None => {
-                        book.title.0
-                    },
+                    None => book.title.0,
                 }),
                 author: Some(match authors {
-                    Some(authors) => {
-                        match authors.first() {
-                            Some(author) => match author.name.clone() {
-                                Some(author_name) => author_name.0,
-                                None => "".to_string(),
-                            },
+                    Some(authors) => match authors.first() {
+                        Some(author) => match author.name.clone() {
+                            Some(author_name) => author_name.0,
                             None => "".to_string(),
-                        }
+                        },
+                        None => "".to_string(),
                     },
                     None => "".to_string(),
                 }),
+                private_notes: [
+                    review
+                        .as_ref()
+                        .and_then(|x| x.private_notes.clone())
+                        .map(|x| x.0),
+                    acquired,
+                    edition.notes.clone().map(|x| x.0),
+                ]
+                .into_iter()
+                .flatten()
+                .reduce(|a, b| format!("{a}\n{b}")),
+                my_review: review.as_ref().and_then(|x| x.content.clone()).map(|x| x.0),
+                spoiler: review
+                    .as_ref()
+                    .filter(|x| x.spoiler)
+                    .map(|_| "true".to_string()),
                 date_read: Some(timestamp_finished.0.format("%Y/%m/%d").to_string()),
                 date_added: Some(timestamp_started.0.format("%Y/%m/%d").to_string()),
-                exclusive_shelf: Some("read".into()),
+                bookshelves: Some(shelves.join(", ")).filter(|x| !x.is_empty()),
+                bookshelves_with_positions: Some(positions.join(", ")).filter(|x| !x.is_empty()),
+                exclusive_shelf: Some(exclusive_shelf),
                 read_count: Some(1.to_string()), // TODO this should be actually calculated
+                rating: review.as_ref().and_then(|x| x.rating),
                 ..Self::default()
             });
         }
         Ok(result)
     }
 
-    pub fn export(data: Vec<Self>) -> Result<()> {
-        let mut wtr = csv::Writer::from_writer(std::io::stdout());
-        for record in data {
-            wtr.serialize(record)?;
+    pub fn export(data: Vec<Self>, options: &CsvOptions) -> Result<()> {
+        write_csv(data, options)
+    }
+}
+
+/// A row of the Goodreads style CSV BookWyrm imports. Differences from the
+/// Goodreads export:
+///
+/// - ISBN13 is plain digits instead of Excel's `="..."` quoting
+/// - `My Rating` is set, in stars out of 5
+/// - `Exclusive Shelf` is always one of BookWyrm's `read`, `currently-reading`
+///   and `to-read`
+/// - `My Review` is HTML, with paragraphs and line breaks kept
+/// - Columns BookWyrm ignores, like private notes, are left out
+#[derive(Debug, Default, Serialize)]
+pub struct BookWyrm {
+    #[serde(rename = "Title")]
+    title:           Option<String>,
+    #[serde(rename = "Author")]
+    author:          Option<String>,
+    #[serde(rename = "ISBN13")]
+    isbn13:          Option<String>,
+    #[serde(rename = "My Rating")]
+    my_rating:       Option<String>,
+    #[serde(rename = "Date Read")]
+    date_read:       Option<String>,
+    #[serde(rename = "Date Added")]
+    date_added:      Option<String>,
+    #[serde(rename = "Bookshelves")]
+    bookshelves:     Option<String>,
+    #[serde(rename = "Exclusive Shelf")]
+    exclusive_shelf: String,
+    #[serde(rename = "My Review")]
+    my_review:       Option<String>,
+}
+
+impl From<Export> for BookWyrm {
+    fn from(x: Export) -> Self {
+        let exclusive_shelf = match x.exclusive_shelf.as_deref() {
+            Some(shelf @ ("read" | "currently-reading" | "to-read")) => shelf.to_string(),
+            _ => "read".to_string(),
+        };
+        Self {
+            title: x.title,
+            author: x.author,
+            isbn13: x
+                .isbn13
+                .map(|x| {
+                    x.chars()
+                        .filter(|c| c.is_ascii_alphanumeric())
+                        .collect::<String>()
+                })
+                .filter(|x| !x.is_empty()),
+            my_rating: x.rating.map(|x| ((x + 10) / 20).min(5).to_string()),
+            date_read: x.date_read,
+            date_added: x.date_added,
+            bookshelves: x.bookshelves,
+            exclusive_shelf,
+            my_review: x.my_review.map(|x| review_to_html(&x)),
+        }
+    }
+}
+
+impl BookWyrm {
+    pub fn export(data: Vec<Export>, options: &CsvOptions) -> Result<()> {
+        write_csv(data.into_iter().map(Self::from), options)
+    }
+}
+
+/// Turn a plain text review into HTML, blank lines separate paragraphs
+///
+/// ```
+/// use tomex::export::review_to_html;
+///
+/// assert_eq!(
+///     review_to_html("Great <3\nloved it\n\nWould read again"),
+///     "<p>Great &lt;3<br>loved it</p><p>Would read again</p>"
+/// );
+/// ```
+pub fn review_to_html(review: &str) -> String {
+    let escaped = review
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace("\r\n", "\n");
+    escaped
+        .split("\n\n")
+        .map(|x| x.trim())
+        .filter(|x| !x.is_empty())
+        .map(|x| format!("<p>{}</p>", x.replace('\n', "<br>")))
+        .collect()
+}
+
+/// How many books [write_json_lines] writes between flushes
+const JSON_LINES_FLUSH_EVERY: usize = 100;
+
+/// Write every book as one line of JSON, hydrated and with its editions and
+/// reviews nested. Books are loaded and written one at a time instead of
+/// building the whole export first. Returns how many books were written
+pub async fn write_json_lines(conn: &sqlx::SqlitePool, out: impl std::io::Write) -> Result<usize> {
+    let mut out = std::io::BufWriter::new(out);
+    let books = Book::get_all(conn).await?;
+    let count = books.len();
+    for (i, mut book) in books.into_iter().enumerate() {
+        book.hydrate(conn).await?;
+        let mut editions = Edition::get_all_for_book(conn, &book.id).await?;
+        for edition in &mut editions {
+            edition.hydrate(conn).await?;
+        }
+        let mut reviews = Review::get_all_for_book(conn, &book.id).await?;
+        for review in &mut reviews {
+            review.hydrate(conn).await?;
+        }
+        book.editions = Some(editions).filter(|x| !x.is_empty());
+        book.reviews = Some(reviews).filter(|x| !x.is_empty());
+        serde_json::to_writer(&mut out, &book)?;
+        out.write_all(b"\n")?;
+        if (i + 1) % JSON_LINES_FLUSH_EVERY == 0 {
+            out.flush()?;
         }
-        wtr.flush()?;
-        Ok(())
     }
+    out.flush()?;
+    Ok(count)
+}
+
+/// How exported CSV is written, the defaults match what Goodreads exports
+pub struct CsvOptions {
+    pub delimiter: u8,
+    /// Start with a UTF-8 byte order mark, which Excel needs to detect UTF-8
+    pub bom:       bool,
+    /// End lines with CRLF instead of LF
+    pub crlf:      bool,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        Self {
+            delimiter: b',',
+            bom:       false,
+            crlf:      false,
+        }
+    }
+}
+
+impl CsvOptions {
+    /// Parse a delimiter given as a single ASCII character, or `tab`
+    ///
+    /// ```
+    /// use tomex::export::CsvOptions;
+    ///
+    /// assert_eq!(CsvOptions::parse_delimiter(";").unwrap(), b';');
+    /// assert_eq!(CsvOptions::parse_delimiter("tab").unwrap(), b'\t');
+    /// assert!(CsvOptions::parse_delimiter("ab").is_err());
+    /// assert!(CsvOptions::parse_delimiter("§").is_err());
+    /// ```
+    pub fn parse_delimiter(s: &str) -> Result<u8> {
+        match s.as_bytes() {
+            _ if s == "tab" || s == "\\t" => Ok(b'\t'),
+            [c] if c.is_ascii() => Ok(*c),
+            _ => anyhow::bail!("Delimiter has to be a single ASCII character or tab"),
+        }
+    }
+}
+
+fn write_csv(data: impl IntoIterator<Item = impl Serialize>, options: &CsvOptions) -> Result<()> {
+    let mut out = std::io::stdout();
+    if options.bom {
+        std::io::Write::write_all(&mut out, "\u{feff}".as_bytes())?;
+    }
+    let terminator = match options.crlf {
+        true => csv::Terminator::CRLF,
+        false => csv::Terminator::Any(b'\n'),
+    };
+    let mut wtr = csv::WriterBuilder::new()
+        .delimiter(options.delimiter)
+        .terminator(terminator)
+        .from_writer(out);
+    for record in data {
+        wtr.serialize(record)?;
+    }
+    wtr.flush()?;
+    Ok(())
 }
@@ -0,0 +1,513 @@
+use std::path::Path;
+
+use anyhow::Result;
+use chrono::{NaiveDate, TimeZone};
+use serde::Deserialize;
+
+use crate::{
+    traits::*,
+    types::{
+        author::Author,
+        book::Book,
+        content_warning::ContentWarning,
+        edition::Edition,
+        mood::Mood,
+        pace::Pace,
+        progress::{PagesProgress, Progress},
+        review::Review,
+        text::Text,
+        timestamp::{OptionalTimestamp, Timestamp},
+        uuid::Uuid,
+    },
+};
+
+/// A book read from another service's export, before it's matched against
+/// the database
+#[derive(Debug, Default)]
+pub struct ImportedBook {
+    pub title:            String,
+    pub authors:          Vec<String>,
+    pub isbn:             Option<String>,
+    /// Rating from 0 to 100
+    pub rating:           Option<u32>,
+    pub review:           Option<String>,
+    pub pace:             Option<String>,
+    pub moods:            Vec<String>,
+    /// Names of content warnings, ones not in the database are dropped
+    pub content_warnings: Vec<String>,
+    pub progress:         Vec<(Timestamp, PagesProgress)>,
+}
+
+/// What happened when importing a single [ImportedBook]
+#[derive(Debug, Default)]
+pub struct ImportResult {
+    pub new_book:     bool,
+    pub new_edition:  bool,
+    pub new_review:   bool,
+    pub new_progress: usize,
+}
+
+impl ImportedBook {
+    /// Drop the isbn if it isn't valid, otherwise it's kept as it came, some
+    /// services export their own ids or malformed isbns in that column
+    ///
+    /// ```
+    /// use tomex::import::ImportedBook;
+    ///
+    /// let book = |isbn: &str| ImportedBook {
+    ///     isbn: Some(isbn.to_string()),
+    ///     ..ImportedBook::default()
+    /// };
+    /// assert_eq!(book("0441172717").strict_isbn().isbn, Some("0441172717".to_string()));
+    /// assert_eq!(book("sg-1234").strict_isbn().isbn, None);
+    /// ```
+    pub fn strict_isbn(self) -> Self {
+        Self {
+            isbn: self.isbn.filter(|x| x.parse::<isbn2::Isbn>().is_ok()),
+            ..self
+        }
+    }
+
+    /// Insert into the database, reusing editions with the same isbn, books
+    /// with the same title and authors with the same name
+    pub async fn import(&self, conn: &sqlx::SqlitePool) -> Result<ImportResult> {
+        let mut result = ImportResult::default();
+        let existing_edition = match &self.isbn {
+            Some(isbn) => Edition::get_by_isbn(conn, isbn).await?,
+            None => None,
+        };
+        let (book, edition) = match existing_edition {
+            Some(edition) => (Book::get_by_id(conn, &edition.book_id).await?, edition),
+            None => {
+                let book = match Book::get_by_title(conn, self.title.clone()).await? {
+                    Some(book) => book,
+                    None => {
+                        let book = self.build_book(conn).await?;
+                        book.insert(conn).await?;
+                        result.new_book = true;
+                        book
+                    }
+                };
+                // Without an isbn there's nothing to tell editions apart by
+                let existing_edition = match &self.isbn {
+                    Some(_) => None,
+                    None => Edition::get_all_for_book(conn, &book.id)
+                        .await?
+                        .into_iter()
+                        .next(),
+                };
+                let edition = match existing_edition {
+                    Some(edition) => edition,
+                    None => {
+                        let edition = Edition {
+                            id: Uuid(uuid::Uuid::new_v4()),
+                            book_id: book.id.clone(),
+                            isbn: self.isbn.clone().map(Text),
+                            book_title: book.title.clone(),
+                            ..Edition::default()
+                        };
+                        edition.insert(conn).await?;
+                        result.new_edition = true;
+                        edition
+                    }
+                };
+                (book, edition)
+            }
+        };
+        if self.rating.is_some() || self.review.is_some() {
+            let reviewed = Review::get_all(conn)
+                .await?
+                .iter()
+                .any(|x| x.book_id == book.id);
+            if !reviewed {
+                self.build_review(&book, conn).await?.insert(conn).await?;
+                result.new_review = true;
+            }
+        }
+        result.new_progress = insert_new_progress(conn, &edition.id, &self.progress).await?;
+        Ok(result)
+    }
+
+    async fn build_book(&self, conn: &sqlx::SqlitePool) -> Result<Book> {
+        let mut authors = Vec::with_capacity(self.authors.len());
+        for name in &self.authors {
+            let author = match Author::get_by_name(conn, name.clone()).await? {
+                Some(author) => author,
+                None => {
+                    let author = Author {
+                        id: Uuid(uuid::Uuid::new_v4()),
+                        name: Some(Text(name.clone())),
+                        ..Author::default()
+                    };
+                    author.insert(conn).await?;
+                    author
+                }
+            };
+            authors.push(author);
+        }
+        let content_warnings: Vec<ContentWarning> = ContentWarning::get_all(conn)
+            .await?
+            .into_iter()
+            .filter(|x| {
+                self.content_warnings
+                    .iter()
+                    .any(|c| x.name.0.eq_ignore_ascii_case(c))
+            })
+            .collect();
+        Ok(Book {
+            id: Uuid(uuid::Uuid::new_v4()),
+            title: Text(self.title.clone()),
+            authors: if authors.is_empty() {
+                None
+            } else {
+                Some(authors)
+            },
+            release_date: OptionalTimestamp(None),
+            content_warnings: if content_warnings.is_empty() {
+                None
+            } else {
+                Some(content_warnings)
+            },
+            ..Book::default()
+        })
+    }
+
+    async fn build_review(&self, book: &Book, conn: &sqlx::SqlitePool) -> Result<Review> {
+        let pace = match &self.pace {
+            Some(name) => Pace::get_all(conn)
+                .await?
+                .into_iter()
+                .find(|x| x.name.0.eq_ignore_ascii_case(name)),
+            None => None,
+        };
+        let moods: Vec<Mood> = Mood::get_all(conn)
+            .await?
+            .into_iter()
+            .filter(|x| self.moods.iter().any(|m| x.name.0.eq_ignore_ascii_case(m)))
+            .collect();
+        let now = Timestamp(chrono::Utc::now());
+        Ok(Review {
+            id: Uuid(uuid::Uuid::new_v4()),
+            book_id: book.id.clone(),
+            rating: self.rating,
+            content: self.review.clone().map(Text),
+            timestamp_created: now.clone(),
+            timestamp_updated: now,
+            pace_id: pace.as_ref().map(|x| x.id.clone()),
+            pace,
+            book_title: book.title.clone(),
+            moods: if moods.is_empty() { None } else { Some(moods) },
+            ..Review::default()
+        })
+    }
+}
+
+/// Insert the progress updates the edition doesn't have yet, returns how many
+/// were inserted
+async fn insert_new_progress(
+    conn: &sqlx::SqlitePool,
+    edition_id: &Uuid,
+    progress: &[(Timestamp, PagesProgress)],
+) -> Result<usize> {
+    let existing = Progress::get_all_for_edition(conn, edition_id).await?;
+    let mut inserted = 0;
+    for (timestamp, pages_progress) in progress {
+        if existing
+            .iter()
+            .any(|x| &x.timestamp == timestamp && &x.pages_progress == pages_progress)
+        {
+            continue;
+        }
+        Progress {
+            timestamp: timestamp.clone(),
+            ..Progress::now(edition_id.clone(), pages_progress.clone())
+        }
+        .insert(conn)
+        .await?;
+        inserted += 1;
+    }
+    Ok(inserted)
+}
+
+/// A row of the CSV export from StoryGraph, other columns are ignored
+#[derive(Debug, Deserialize)]
+pub struct StoryGraph {
+    #[serde(rename = "Title")]
+    title:            String,
+    #[serde(rename = "Authors")]
+    authors:          Option<String>,
+    #[serde(rename = "ISBN/UID")]
+    isbn:             Option<String>,
+    #[serde(rename = "Read Status")]
+    read_status:      Option<String>,
+    #[serde(rename = "Date Added")]
+    date_added:       Option<String>,
+    #[serde(rename = "Last Date Read")]
+    last_date_read:   Option<String>,
+    #[serde(rename = "Dates Read")]
+    dates_read:       Option<String>,
+    #[serde(rename = "Moods")]
+    moods:            Option<String>,
+    #[serde(rename = "Content Warnings")]
+    content_warnings: Option<String>,
+    #[serde(rename = "Pace")]
+    pace:             Option<String>,
+    #[serde(rename = "Star Rating")]
+    star_rating:      Option<String>,
+    #[serde(rename = "Review")]
+    review:           Option<String>,
+}
+
+/// Parse a date the way StoryGraph writes them (2023/01/31)
+fn parse_date(s: &str) -> Option<Timestamp> {
+    NaiveDate::parse_from_str(s.trim(), "%Y/%m/%d")
+        .ok()
+        .and_then(|x| x.and_hms_opt(0, 0, 0))
+        .map(|x| Timestamp(chrono::Utc.from_utc_datetime(&x)))
+}
+
+/// Split a comma separated list, dropping empty entries
+fn split_list(s: &Option<String>) -> Vec<String> {
+    s.iter()
+        .flat_map(|x| x.split(','))
+        .map(|x| x.trim().to_string())
+        .filter(|x| !x.is_empty())
+        .collect()
+}
+
+impl StoryGraph {
+    /// Read all rows from a StoryGraph CSV export
+    pub fn read(path: &Path) -> Result<Vec<Self>> {
+        let mut rdr = csv::Reader::from_path(path)?;
+        Ok(rdr.deserialize().collect::<Result<Vec<Self>, _>>()?)
+    }
+
+    /// Reading sessions as (start, end) pairs, from `Dates Read` which looks
+    /// like `2023/01/05-2023/02/01, 2024/03/01-2024/03/10`
+    fn sessions(&self) -> Vec<(Option<Timestamp>, Option<Timestamp>)> {
+        split_list(&self.dates_read)
+            .iter()
+            .map(|x| match x.split_once('-') {
+                Some((start, end)) => (parse_date(start), parse_date(end)),
+                None => (None, parse_date(x)),
+            })
+            .collect()
+    }
+
+    /// Map the read status to progress updates, `read` becomes a start and a
+    /// finish per session, `did-not-finish` ends in [PagesProgress::Abandoned]
+    fn progress(&self) -> Vec<(Timestamp, PagesProgress)> {
+        let added = self.date_added.as_deref().and_then(parse_date);
+        let last_read = self.last_date_read.as_deref().and_then(parse_date);
+        let end = match self.read_status.as_deref() {
+            Some("read") => PagesProgress::Finished,
+            Some("did-not-finish") => PagesProgress::Abandoned,
+            Some("currently-reading") => {
+                let start = self
+                    .sessions()
+                    .into_iter()
+                    .find_map(|(start, _)| start)
+                    .or(added);
+                return start
+                    .map(|x| vec![(x, PagesProgress::Started)])
+                    .unwrap_or_default();
+            }
+            _ => return vec![],
+        };
+        let mut sessions = self.sessions();
+        if sessions.is_empty() {
+            // Best guess for the start is when it was added, if that's not
+            // after it was finished
+            let start = added
+                .clone()
+                .filter(|x| last_read.as_ref().is_none_or(|y| x <= y));
+            sessions.push((start, last_read.clone().or(added)));
+        }
+        let mut result = vec![];
+        for (start, finish) in sessions {
+            let Some(finish) = finish.or(last_read.clone()) else {
+                continue;
+            };
+            result.push((start.unwrap_or(finish.clone()), PagesProgress::Started));
+            result.push((finish, end.clone()));
+        }
+        result
+    }
+}
+
+impl From<StoryGraph> for ImportedBook {
+    fn from(x: StoryGraph) -> Self {
+        let progress = x.progress();
+        Self {
+            title: x.title.trim().to_string(),
+            authors: split_list(&x.authors),
+            isbn: x
+                .isbn
+                .map(|x| x.trim().to_string())
+                .filter(|x| !x.is_empty()),
+            // Stars go from 0 to 5 in quarter steps
+            rating: x
+                .star_rating
+                .and_then(|x| x.trim().parse::<f64>().ok())
+                .map(|x| (x * 20.0).round().clamp(0.0, 100.0) as u32),
+            review: x.review.filter(|x| !x.trim().is_empty()),
+            pace: x.pace.filter(|x| !x.trim().is_empty()),
+            moods: split_list(&x.moods),
+            content_warnings: split_list(&x.content_warnings),
+            progress,
+        }
+    }
+}
+
+/// A row of a reading log kept by hand, the book is given by an `isbn` or a
+/// `title` column, or a `book` column holding either. `progress` is a page
+/// number or `started`, `finished` or `abandoned`
+#[derive(Debug, Deserialize)]
+pub struct ReadingLog {
+    book:     Option<String>,
+    isbn:     Option<String>,
+    title:    Option<String>,
+    date:     String,
+    #[serde(alias = "page", alias = "status")]
+    progress: String,
+}
+
+/// What happened when importing a reading log
+#[derive(Debug, Default)]
+pub struct LogImportResult {
+    pub new_progress: usize,
+    /// Rows the edition already had a matching progress update for
+    pub duplicates:   usize,
+    /// Line numbers of rows that weren't imported, with the reason
+    pub skipped:      Vec<(usize, String)>,
+}
+
+/// Parse a date like 2023-01-31, 2023/01/31 or anything else [dateparser]
+/// understands
+fn parse_log_date(s: &str) -> Option<Timestamp> {
+    NaiveDate::parse_from_str(s.trim(), "%Y-%m-%d")
+        .ok()
+        .and_then(|x| x.and_hms_opt(0, 0, 0))
+        .map(|x| Timestamp(chrono::Utc.from_utc_datetime(&x)))
+        .or_else(|| parse_date(s))
+        .or_else(|| dateparser::parse(s.trim()).ok().map(Timestamp))
+}
+
+/// Parse a page number or a status, case doesn't matter
+///
+/// ```
+/// use tomex::{import::parse_log_progress, types::progress::PagesProgress};
+///
+/// assert_eq!(parse_log_progress(" 42 "), Some(PagesProgress::Pages(42)));
+/// assert_eq!(parse_log_progress("DNF"), Some(PagesProgress::Abandoned));
+/// assert_eq!(parse_log_progress("halfway"), None);
+/// ```
+pub fn parse_log_progress(s: &str) -> Option<PagesProgress> {
+    let s = s.trim().to_lowercase();
+    match s.as_str() {
+        "started" | "start" => Some(PagesProgress::Started),
+        "finished" | "finish" | "read" => Some(PagesProgress::Finished),
+        "abandoned" | "dnf" | "did-not-finish" => Some(PagesProgress::Abandoned),
+        _ => s.parse().ok().map(PagesProgress::Pages),
+    }
+}
+
+impl ReadingLog {
+    /// Read all rows from a reading log CSV with a header row
+    pub fn read(path: &Path) -> Result<Vec<Self>> {
+        let mut rdr = csv::ReaderBuilder::new()
+            .trim(csv::Trim::All)
+            .from_path(path)?;
+        Ok(rdr.deserialize().collect::<Result<Vec<Self>, _>>()?)
+    }
+
+    /// The edition with this row's isbn, or the only edition of the book with
+    /// its title. Otherwise why it couldn't be resolved
+    async fn edition(&self, conn: &sqlx::SqlitePool) -> Result<Result<Edition, String>> {
+        let non_empty = |x: &Option<String>| x.clone().filter(|x| !x.trim().is_empty());
+        let (isbn, title) = match (non_empty(&self.isbn), non_empty(&self.title)) {
+            (None, None) => match non_empty(&self.book) {
+                Some(book) => (Some(book.clone()), Some(book)),
+                None => return Ok(Err("no isbn or title".into())),
+            },
+            x => x,
+        };
+        if let Some(isbn) = &isbn {
+            if let Some(edition) = Edition::get_by_isbn(conn, isbn).await? {
+                return Ok(Ok(edition));
+            }
+        }
+        let Some(title) = title else {
+            return Ok(Err(format!(
+                "no edition found for {}",
+                isbn.unwrap_or_default()
+            )));
+        };
+        let Some(book) = Book::get_by_title(conn, title.clone()).await? else {
+            return Ok(Err(format!("no edition or book found for {title}")));
+        };
+        let mut editions = Edition::get_all_for_book(conn, &book.id).await?;
+        Ok(match editions.len() {
+            1 => Ok(editions.remove(0)),
+            0 => Err(format!("{} has no editions", book.title)),
+            n => Err(format!("{} has {n} editions, use the isbn", book.title)),
+        })
+    }
+
+    /// Insert the rows as progress updates, skipping rows that can't be
+    /// resolved to an edition, have an invalid date or progress, or go past
+    /// the edition's page count. Nothing is inserted if any insert fails
+    pub async fn import(rows: &[Self], conn: &sqlx::SqlitePool) -> Result<LogImportResult> {
+        let mut result = LogImportResult::default();
+        let mut new: Vec<Progress> = vec![];
+        for (i, row) in rows.iter().enumerate() {
+            // The header is the first line
+            let line = i + 2;
+            let edition = match row.edition(conn).await? {
+                Ok(edition) => edition,
+                Err(reason) => {
+                    result.skipped.push((line, reason));
+                    continue;
+                }
+            };
+            let Some(date) = parse_log_date(&row.date) else {
+                result
+                    .skipped
+                    .push((line, format!("invalid date {}", row.date)));
+                continue;
+            };
+            let Some(progress) = parse_log_progress(&row.progress) else {
+                result
+                    .skipped
+                    .push((line, format!("invalid progress {}", row.progress)));
+                continue;
+            };
+            if let (PagesProgress::Pages(page), Some(pages)) = (&progress, edition.pages) {
+                if *page > pages {
+                    result
+                        .skipped
+                        .push((line, format!("page {page} of {pages}")));
+                    continue;
+                }
+            }
+            let existing = Progress::get_all_for_edition(conn, &edition.id).await?;
+            if existing.iter().chain(&new).any(|x| {
+                x.edition_id == edition.id && x.timestamp == date && x.pages_progress == progress
+            }) {
+                result.duplicates += 1;
+                continue;
+            }
+            new.push(Progress {
+                timestamp: date,
+                ..Progress::now(edition.id, progress)
+            });
+        }
+        let mut tx = conn.begin().await?;
+        for x in &new {
+            x.insert_with(&mut tx).await?;
+        }
+        tx.commit().await?;
+        result.new_progress = new.len();
+        Ok(result)
+    }
+}
@@ -2,8 +2,14 @@
 #![allow(incomplete_features)]
 
 pub mod backup;
+pub mod cache;
 pub mod config;
 pub mod default_colors;
+pub mod doctor;
+pub mod error;
 pub mod export;
+pub mod import;
+pub mod stats;
+pub mod table;
 pub mod traits;
 pub mod types;
@@ -0,0 +1,820 @@
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use chrono::{Datelike, Duration, NaiveDate};
+use crossterm::style::{Color, Stylize};
+use serde::{Serialize, ser::SerializeStruct};
+
+use crate::{
+    config::{Config, ReadingSort},
+    default_colors::COLOR_DIMMED,
+    traits::*,
+    types::{
+        author::Author,
+        book::Book,
+        edition::Edition,
+        edition_review::EditionReview,
+        genre::Genre,
+        junction_tables::{book_author::BookAuthor, book_genre::BookGenre},
+        progress::{PagesProgress, Progress},
+        review::Review,
+        timestamp::Timestamp,
+        uuid::Uuid,
+    },
+};
+
+/// Reading activity of a single year, bucketed by day
+pub struct Calendar {
+    pub year: i32,
+    /// Pages read per day, days with progress updates but no pages count as 0
+    pub days: BTreeMap<NaiveDate, u32>,
+}
+
+impl Calendar {
+    /// Collect progress updates of `year`, optionally only for one edition
+    pub async fn new(
+        conn: &sqlx::SqlitePool,
+        year: i32,
+        edition_id: Option<&Uuid>,
+    ) -> Result<Self> {
+        let progress = Progress::get_all(conn)
+            .await?
+            .into_iter()
+            .filter(|x| edition_id.is_none_or(|id| &x.edition_id == id))
+            .collect::<Vec<Progress>>();
+        let mut days = BTreeMap::new();
+        for (date, pages) in pages_read(progress) {
+            if date.year() == year {
+                *days.entry(date).or_insert(0) += pages;
+            }
+        }
+        Ok(Self { year, days })
+    }
+
+    /// Render as a heatmap with one row per weekday and one column per week,
+    /// fails if the year is out of the range [NaiveDate] supports
+    pub fn render(&self, config: &Config) -> Result<String> {
+        let color = config.output_progress.style_content.color();
+        let max = self.days.values().copied().max().unwrap_or(0);
+        let invalid = || crate::error::Error::Usage(format!("Invalid year {}", self.year));
+        let first = NaiveDate::from_ymd_opt(self.year, 1, 1).ok_or_else(invalid)?;
+        let last = NaiveDate::from_ymd_opt(self.year, 12, 31).ok_or_else(invalid)?;
+        // Start on the monday of the first week so columns line up
+        let start = first
+            .checked_sub_signed(Duration::days(
+                first.weekday().num_days_from_monday().into(),
+            ))
+            .ok_or_else(invalid)?;
+        let mut s = String::new();
+        for (weekday, label) in ["Mon", "", "Wed", "", "Fri", "", "Sun"].iter().enumerate() {
+            s.push_str(&format!("{label:<4}"));
+            let mut date = start + Duration::days(weekday as i64);
+            while date <= last {
+                if date < first {
+                    s.push(' ');
+                } else {
+                    s.push_str(&match self.days.get(&date) {
+                        Some(pages) => "■".with(shade(color, *pages, max)).to_string(),
+                        None => "·".with(COLOR_DIMMED).to_string(),
+                    });
+                }
+                match date.checked_add_signed(Duration::days(7)) {
+                    Some(next) => date = next,
+                    None => break,
+                }
+            }
+            s.push('\n');
+        }
+        let pages: u32 = self.days.values().sum();
+        s.push_str(&format!(
+            "{} days with progress, {} pages read in {}",
+            self.days.len(),
+            pages,
+            self.year
+        ));
+        Ok(s)
+    }
+}
+
+/// The local date of every progress update with how many pages were read
+/// since the previous one of the same edition, in order
+fn pages_read(mut progress: Vec<Progress>) -> Vec<(NaiveDate, u32)> {
+    progress.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
+    let mut last_page: BTreeMap<Uuid, u32> = BTreeMap::new();
+    let mut result = Vec::with_capacity(progress.len());
+    for x in progress {
+        let date = x.timestamp.0.with_timezone(&chrono::Local).date_naive();
+        let pages = match x.pages_progress {
+            PagesProgress::Pages(n) => {
+                let last = last_page.insert(x.edition_id.clone(), n).unwrap_or(0);
+                n.saturating_sub(last)
+            }
+            PagesProgress::Started => {
+                last_page.remove(&x.edition_id);
+                0
+            }
+            PagesProgress::Finished | PagesProgress::Abandoned => 0,
+        };
+        result.push((date, pages));
+    }
+    result
+}
+
+/// Blend from [COLOR_DIMMED] towards `color` depending on how many pages were
+/// read compared to the busiest day, in four steps
+fn shade(color: Color, pages: u32, max: u32) -> Color {
+    let step = if max == 0 {
+        1
+    } else {
+        1 + (pages * 3).div_ceil(max).min(3)
+    };
+    match (COLOR_DIMMED, color) {
+        (
+            Color::Rgb {
+                r: r0,
+                g: g0,
+                b: b0,
+            },
+            Color::Rgb { r, g, b },
+        ) => {
+            let mix = |from: u8, to: u8| {
+                (i32::from(from) + (i32::from(to) - i32::from(from)) * step as i32 / 4) as u8
+            };
+            Color::Rgb {
+                r: mix(r0, r),
+                g: mix(g0, g),
+                b: mix(b0, b),
+            }
+        }
+        _ => color,
+    }
+}
+
+/// Authors ranked by how many (non-deleted) books they have
+pub struct AuthorLeaderboard {
+    /// Authors with their number of books, most books first, ties by name
+    pub authors: Vec<(Author, u32)>,
+    /// Number of books only attributed to the unknown author
+    pub unknown: u32,
+}
+
+impl AuthorLeaderboard {
+    /// Count books per author, keeping only the top `limit` authors
+    pub async fn new(conn: &sqlx::SqlitePool, limit: Option<usize>) -> Result<Self> {
+        let counts: Vec<(Uuid, u32)> = sqlx::query_as(&format!(
+            r#"
+            SELECT ba.author_id, COUNT(DISTINCT ba.book_id) FROM {book_author} ba
+            JOIN {books} b ON b.id = ba.book_id
+            WHERE b.deleted = 0
+            GROUP BY ba.author_id;
+            "#,
+            book_author = <BookAuthor as JunctionTable<Book, Author>>::TABLE_NAME,
+            books = Book::TABLE_NAME,
+        ))
+        .fetch_all(conn)
+        .await?;
+        let names: BTreeMap<Uuid, Author> = Author::get_all(conn)
+            .await?
+            .into_iter()
+            .map(|x| (x.id.clone(), x))
+            .collect();
+        let mut unknown = 0;
+        let mut authors = Vec::new();
+        for (id, count) in counts {
+            match names.get(&id) {
+                Some(author) if author.special => unknown += count,
+                Some(author) => authors.push((author.clone(), count)),
+                None => (),
+            }
+        }
+        let name = |x: &Author| {
+            x.name
+                .as_ref()
+                .map(|x| x.0.to_lowercase())
+                .unwrap_or_default()
+        };
+        authors.sort_by(|(a, a_count), (b, b_count)| {
+            b_count.cmp(a_count).then_with(|| name(a).cmp(&name(b)))
+        });
+        if let Some(limit) = limit {
+            authors.truncate(limit);
+        }
+        Ok(Self { authors, unknown })
+    }
+
+    /// Render one line per author, the unknown author last
+    pub async fn render(&self, conn: &sqlx::SqlitePool, config: &Config) -> Result<String> {
+        let width = self.authors.len().to_string().len();
+        let mut s = String::new();
+        for (i, (author, count)) in self.authors.iter().enumerate() {
+            let mut line = String::new();
+            DisplayTerminal::fmt(author, &mut line, conn, config).await?;
+            s.push_str(&format!("{:>width$}. {line}: {count}\n", i + 1));
+        }
+        if self.unknown > 0 {
+            s.push_str(&format!(
+                "{} {}\n",
+                "Unknown author:".with(COLOR_DIMMED),
+                self.unknown
+            ));
+        }
+        if s.is_empty() {
+            s.push_str("No books with authors\n");
+        }
+        s.pop();
+        Ok(s)
+    }
+}
+
+impl Serialize for AuthorLeaderboard {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        #[derive(Serialize)]
+        struct Entry<'a> {
+            id:    &'a Uuid,
+            name:  Option<&'a str>,
+            books: u32,
+        }
+        let mut s = serializer.serialize_struct("AuthorLeaderboard", 2)?;
+        let authors: Vec<Entry> = self
+            .authors
+            .iter()
+            .map(|(author, books)| Entry {
+                id:    &author.id,
+                name:  author.name.as_ref().map(|x| x.0.as_str()),
+                books: *books,
+            })
+            .collect();
+        s.serialize_field("authors", &authors)?;
+        s.serialize_field("unknown", &self.unknown)?;
+        s.end()
+    }
+}
+
+/// How review ratings are distributed over buckets of ten points
+pub struct RatingHistogram {
+    /// How often each rating was given
+    pub ratings: BTreeMap<u32, u32>,
+}
+
+impl RatingHistogram {
+    /// Count the ratings of reviews, and of edition reviews if `editions` is
+    /// set
+    pub async fn new(conn: &sqlx::SqlitePool, editions: bool) -> Result<Self> {
+        let mut tables = vec![Review::TABLE_NAME];
+        if editions {
+            tables.push(EditionReview::TABLE_NAME);
+        }
+        let mut ratings = BTreeMap::new();
+        for table in tables {
+            let counts: Vec<(u32, u32)> = sqlx::query_as(&format!(
+                r#"
+                SELECT rating, COUNT(*) FROM {table}
+                WHERE deleted = 0 AND rating IS NOT NULL
+                GROUP BY rating;
+                "#
+            ))
+            .fetch_all(conn)
+            .await?;
+            for (rating, count) in counts {
+                *ratings.entry(rating).or_default() += count;
+            }
+        }
+        Ok(Self { ratings })
+    }
+
+    /// Number of ratings in each bucket, 0-9 up to 90-100
+    pub fn buckets(&self) -> [u32; 10] {
+        let mut buckets = [0; 10];
+        for (rating, count) in &self.ratings {
+            buckets[(*rating as usize / 10).min(9)] += count;
+        }
+        buckets
+    }
+
+    /// The mean rating, None without ratings
+    pub fn average(&self) -> Option<f64> {
+        let count: u32 = self.ratings.values().sum();
+        let sum: u64 = self.ratings.iter().map(|(x, n)| u64::from(x * n)).sum();
+        (count > 0).then(|| sum as f64 / f64::from(count))
+    }
+
+    /// The middle rating, the mean of the two middle ones for an even count
+    ///
+    /// ```
+    /// use tomex::stats::RatingHistogram;
+    ///
+    /// let histogram = RatingHistogram { ratings: [(40, 1), (70, 2), (90, 1)].into() };
+    /// assert_eq!(histogram.median(), Some(70.0));
+    /// let histogram = RatingHistogram { ratings: [(40, 1), (70, 1)].into() };
+    /// assert_eq!(histogram.median(), Some(55.0));
+    /// assert_eq!(RatingHistogram { ratings: [].into() }.median(), None);
+    /// ```
+    pub fn median(&self) -> Option<f64> {
+        let count: u32 = self.ratings.values().sum();
+        let nth = |n: u32| {
+            let mut seen = 0;
+            self.ratings.iter().find_map(|(x, c)| {
+                seen += c;
+                (seen > n).then_some(*x)
+            })
+        };
+        match count {
+            0 => None,
+            _ if count % 2 == 1 => nth(count / 2).map(f64::from),
+            _ => Some((f64::from(nth(count / 2 - 1)?) + f64::from(nth(count / 2)?)) / 2.0),
+        }
+    }
+
+    /// Render one bar per bucket followed by the average and median
+    pub fn render(&self, config: &Config) -> String {
+        let color = config.output_rating.style_content.color();
+        let buckets = self.buckets();
+        let max = buckets.iter().copied().max().unwrap_or(0);
+        let mut s = String::new();
+        for (i, count) in buckets.iter().enumerate() {
+            let label = match i {
+                9 => "90-100".to_string(),
+                i => format!("{}-{}", i * 10, i * 10 + 9),
+            };
+            s.push_str(&format!(
+                "{label:>6} {} {count}\n",
+                format!("{:<30}", bar(*count, max, 30)).with(color)
+            ));
+        }
+        match (self.average(), self.median()) {
+            (Some(average), Some(median)) => s.push_str(&format!(
+                "Average {average:.1}, median {median} of {} ratings",
+                self.ratings.values().sum::<u32>()
+            )),
+            _ => s.push_str("No ratings"),
+        }
+        s
+    }
+}
+
+impl Serialize for RatingHistogram {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut s = serializer.serialize_struct("RatingHistogram", 4)?;
+        s.serialize_field("count", &self.ratings.values().sum::<u32>())?;
+        s.serialize_field("average", &self.average())?;
+        s.serialize_field("median", &self.median())?;
+        s.serialize_field("buckets", &self.buckets())?;
+        s.end()
+    }
+}
+
+/// Books counted by the decade they were released in
+pub struct DecadeChart {
+    /// Number of books per decade, by the decade's first year
+    pub decades: BTreeMap<i32, u32>,
+    /// Number of books without a release date
+    pub undated: u32,
+}
+
+impl DecadeChart {
+    /// Bucket all (non-deleted) books by [Book::release_decade]
+    pub async fn new(conn: &sqlx::SqlitePool) -> Result<Self> {
+        let mut decades = BTreeMap::new();
+        let mut undated = 0;
+        for book in Book::get_all(conn).await? {
+            match book.release_decade() {
+                Some(decade) => *decades.entry(decade).or_default() += 1,
+                None => undated += 1,
+            }
+        }
+        Ok(Self { decades, undated })
+    }
+
+    /// Render one bar per decade from the oldest to the newest, including
+    /// empty decades in between
+    pub fn render(&self, config: &Config) -> String {
+        let color = config.output_release_date.style_content.color();
+        let max = self.decades.values().copied().max().unwrap_or(0);
+        let mut s = String::new();
+        if let (Some(first), Some(last)) = (self.decades.keys().next(), self.decades.keys().last())
+        {
+            for decade in (*first..=*last).step_by(10) {
+                let count = self.decades.get(&decade).copied().unwrap_or(0);
+                s.push_str(&format!(
+                    "{:>6} {} {count}\n",
+                    format!("{decade}s"),
+                    format!("{:<30}", bar(count, max, 30)).with(color)
+                ));
+            }
+        }
+        if self.undated > 0 {
+            s.push_str(&format!(
+                "{} {}\n",
+                "Without release date:".with(COLOR_DIMMED),
+                self.undated
+            ));
+        }
+        if s.is_empty() {
+            s.push_str("No books\n");
+        }
+        s.pop();
+        s
+    }
+}
+
+impl Serialize for DecadeChart {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        #[derive(Serialize)]
+        struct Entry {
+            decade: i32,
+            books:  u32,
+        }
+        let mut s = serializer.serialize_struct("DecadeChart", 2)?;
+        let decades: Vec<Entry> = self
+            .decades
+            .iter()
+            .map(|(decade, books)| Entry {
+                decade: *decade,
+                books:  *books,
+            })
+            .collect();
+        s.serialize_field("decades", &decades)?;
+        s.serialize_field("undated", &self.undated)?;
+        s.end()
+    }
+}
+
+/// Number of records in the library, removed ones don't count
+#[derive(Debug, Serialize)]
+pub struct Totals {
+    pub books:      u32,
+    pub editions:   u32,
+    /// Not counting the unknown author
+    pub authors:    u32,
+    pub reviews:    u32,
+    /// Pages read according to all progress updates
+    pub pages_read: u64,
+}
+
+impl Totals {
+    pub async fn new(conn: &sqlx::SqlitePool) -> Result<Self> {
+        let count = |table: &'static str| async move {
+            let (count,): (u32,) =
+                sqlx::query_as(&format!("SELECT COUNT(*) FROM {table} WHERE deleted = 0;"))
+                    .fetch_one(conn)
+                    .await?;
+            anyhow::Ok(count)
+        };
+        let authors = Author::get_all(conn)
+            .await?
+            .iter()
+            .filter(|x| !x.special)
+            .count();
+        let pages_read = pages_read(Progress::get_all(conn).await?)
+            .into_iter()
+            .map(|(_, pages)| u64::from(pages))
+            .sum();
+        Ok(Self {
+            books: count(Book::TABLE_NAME).await?,
+            editions: count(Edition::TABLE_NAME).await?,
+            authors: authors as u32,
+            reviews: count(Review::TABLE_NAME).await?,
+            pages_read,
+        })
+    }
+
+    /// Render one line per total
+    pub fn render(&self) -> String {
+        format!(
+            "Books: {}\nEditions: {}\nAuthors: {}\nReviews: {}\nPages read: {}",
+            self.books, self.editions, self.authors, self.reviews, self.pages_read
+        )
+    }
+}
+
+/// How many books a genre has
+#[derive(Debug, Serialize)]
+pub struct GenreCount {
+    pub name:  String,
+    pub books: u32,
+}
+
+/// Genres by number of (non-deleted) books, most first, unused ones left out
+pub async fn genre_counts(conn: &sqlx::SqlitePool) -> Result<Vec<GenreCount>> {
+    let counts: Vec<(String, u32)> = sqlx::query_as(&format!(
+        r#"
+        SELECT g.name, COUNT(DISTINCT j.book_id) AS books FROM {genres} g
+        JOIN {book_genre} j ON j.genre_id = g.id
+        JOIN {books} b ON b.id = j.book_id
+        WHERE b.deleted = 0 AND g.deleted = 0
+        GROUP BY g.id
+        ORDER BY books DESC, g.name COLLATE NOCASE;
+        "#,
+        genres = Genre::TABLE_NAME,
+        book_genre = <BookGenre as JunctionTable<Book, Genre>>::TABLE_NAME,
+        books = Book::TABLE_NAME,
+    ))
+    .fetch_all(conn)
+    .await?;
+    Ok(counts
+        .into_iter()
+        .map(|(name, books)| GenreCount { name, books })
+        .collect())
+}
+
+/// Pages read in one month
+#[derive(Debug, Serialize)]
+pub struct MonthPages {
+    /// Like `2024-03`
+    pub month: String,
+    pub pages: u32,
+}
+
+/// Pages read per month, oldest first, only months with progress updates
+pub async fn pages_per_month(conn: &sqlx::SqlitePool) -> Result<Vec<MonthPages>> {
+    let mut months: BTreeMap<String, u32> = BTreeMap::new();
+    for (date, pages) in pages_read(Progress::get_all(conn).await?) {
+        *months.entry(date.format("%Y-%m").to_string()).or_default() += pages;
+    }
+    Ok(months
+        .into_iter()
+        .map(|(month, pages)| MonthPages { month, pages })
+        .collect())
+}
+
+/// Which parts of a [StatsReport] to compute
+#[derive(Debug, Default, Clone, Copy)]
+pub struct StatsSections {
+    pub totals:          bool,
+    pub genres:          bool,
+    pub months:          bool,
+    pub ratings:         bool,
+    /// Include edition reviews in the ratings
+    pub edition_reviews: bool,
+    pub authors:         bool,
+    pub decades:         bool,
+}
+
+impl StatsSections {
+    /// Every section, edition reviews not included in the ratings
+    pub fn all() -> Self {
+        Self {
+            totals:          true,
+            genres:          true,
+            months:          true,
+            ratings:         true,
+            edition_reviews: false,
+            authors:         true,
+            decades:         true,
+        }
+    }
+}
+
+/// The statistics `stats` shows, as text or as JSON. Parts that weren't
+/// asked for are left out
+#[derive(Serialize)]
+pub struct StatsReport {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub totals:          Option<Totals>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub genres:          Option<Vec<GenreCount>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pages_per_month: Option<Vec<MonthPages>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ratings:         Option<RatingHistogram>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub authors:         Option<AuthorLeaderboard>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub decades:         Option<DecadeChart>,
+}
+
+impl StatsReport {
+    /// Compute the `sections` asked for, keeping only the top `limit` authors
+    pub async fn new(
+        conn: &sqlx::SqlitePool,
+        sections: StatsSections,
+        limit: Option<usize>,
+    ) -> Result<Self> {
+        Ok(Self {
+            totals:          match sections.totals {
+                true => Some(Totals::new(conn).await?),
+                false => None,
+            },
+            genres:          match sections.genres {
+                true => Some(genre_counts(conn).await?),
+                false => None,
+            },
+            pages_per_month: match sections.months {
+                true => Some(pages_per_month(conn).await?),
+                false => None,
+            },
+            ratings:         match sections.ratings {
+                true => Some(RatingHistogram::new(conn, sections.edition_reviews).await?),
+                false => None,
+            },
+            authors:         match sections.authors {
+                true => Some(AuthorLeaderboard::new(conn, limit).await?),
+                false => None,
+            },
+            decades:         match sections.decades {
+                true => Some(DecadeChart::new(conn).await?),
+                false => None,
+            },
+        })
+    }
+
+    /// Render the computed parts one after another
+    pub async fn render(&self, conn: &sqlx::SqlitePool, config: &Config) -> Result<String> {
+        let mut parts = vec![];
+        if let Some(totals) = &self.totals {
+            parts.push(totals.render());
+        }
+        if let Some(genres) = &self.genres {
+            let color = config.output_genre.style_content.color();
+            let max = genres.iter().map(|x| x.books).max().unwrap_or(0);
+            let width = genres
+                .iter()
+                .map(|x| x.name.chars().count())
+                .max()
+                .unwrap_or(0);
+            let lines: Vec<String> = genres
+                .iter()
+                .map(|x| {
+                    format!(
+                        "{:>width$} {} {}",
+                        x.name,
+                        format!("{:<30}", bar(x.books, max, 30)).with(color),
+                        x.books
+                    )
+                })
+                .collect();
+            parts.push(match lines.is_empty() {
+                true => "No books with genres".to_string(),
+                false => lines.join("\n"),
+            });
+        }
+        if let Some(months) = &self.pages_per_month {
+            let color = config.output_progress.style_content.color();
+            let max = months.iter().map(|x| x.pages).max().unwrap_or(0);
+            let lines: Vec<String> = months
+                .iter()
+                .map(|x| {
+                    format!(
+                        "{} {} {}",
+                        x.month,
+                        format!("{:<30}", bar(x.pages, max, 30)).with(color),
+                        x.pages
+                    )
+                })
+                .collect();
+            parts.push(match lines.is_empty() {
+                true => "No pages read".to_string(),
+                false => lines.join("\n"),
+            });
+        }
+        if let Some(ratings) = &self.ratings {
+            parts.push(ratings.render(config));
+        }
+        if let Some(authors) = &self.authors {
+            parts.push(authors.render(conn, config).await?);
+        }
+        if let Some(decades) = &self.decades {
+            parts.push(decades.render(config));
+        }
+        Ok(parts.join("\n"))
+    }
+}
+
+/// A horizontal bar of block characters, `width` long for `max`
+fn bar(value: u32, max: u32, width: usize) -> String {
+    const EIGHTHS: [&str; 8] = ["", "▏", "▎", "▍", "▌", "▋", "▊", "▉"];
+    if max == 0 {
+        return String::new();
+    }
+    let eighths = (u64::from(value) * width as u64 * 8).div_ceil(u64::from(max)) as usize;
+    format!("{}{}", "█".repeat(eighths / 8), EIGHTHS[eighths % 8])
+}
+
+/// An edition that is currently being read
+pub struct Reading {
+    pub edition:  Edition,
+    /// The edition's title, else its book's
+    pub title:    String,
+    /// When progress was last recorded
+    pub updated:  Timestamp,
+    /// The last page reached, None if no pages were recorded since starting
+    pub page:     Option<u32>,
+    /// Page count according to the configured `pages_source`
+    pub pages:    Option<u32>,
+    /// When it will likely be finished, see [Edition::estimated_finish]
+    pub estimate: Option<Timestamp>,
+}
+
+impl Reading {
+    /// How far along it is, None without a page count
+    pub fn percent(&self) -> Option<u64> {
+        match self.pages {
+            Some(total) if total > 0 => {
+                Some(u64::from(self.page.unwrap_or(0)) * 100 / u64::from(total))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Editions whose latest progress update is a start or a page count
+pub struct CurrentlyReading {
+    /// Most recently updated first, unless [CurrentlyReading::arrange]d
+    pub editions: Vec<Reading>,
+}
+
+impl CurrentlyReading {
+    /// Collect the editions being read, with their estimated finish
+    pub async fn new(conn: &sqlx::SqlitePool, config: &Config) -> Result<Self> {
+        let mut latest: BTreeMap<Uuid, Progress> = BTreeMap::new();
+        for progress in Progress::get_all(conn).await? {
+            match latest.get(&progress.edition_id) {
+                Some(x) if x.timestamp >= progress.timestamp => (),
+                _ => {
+                    latest.insert(progress.edition_id.clone(), progress);
+                }
+            }
+        }
+        let mut latest: Vec<Progress> = latest
+            .into_values()
+            .filter(|x| {
+                matches!(
+                    x.pages_progress,
+                    PagesProgress::Started | PagesProgress::Pages(_)
+                )
+            })
+            .collect();
+        latest.sort_by(|a, b| b.timestamp.partial_cmp(&a.timestamp).unwrap());
+        let mut editions = Vec::new();
+        for progress in latest {
+            let edition = Edition::get_by_id(conn, &progress.edition_id).await?;
+            if edition.deleted {
+                continue;
+            }
+            let page = match progress.pages_progress {
+                PagesProgress::Pages(n) => Some(n),
+                _ => None,
+            };
+            let title = match &edition.edition_title {
+                Some(title) => title.to_string(),
+                None => Book::get_by_id(conn, &edition.book_id)
+                    .await?
+                    .title
+                    .to_string(),
+            };
+            let pages = edition.pages_by_config(conn, config).await?;
+            let estimate = edition.estimated_finish(conn, config).await?;
+            editions.push(Reading {
+                edition,
+                title,
+                updated: progress.timestamp,
+                page,
+                pages,
+                estimate,
+            });
+        }
+        Ok(Self { editions })
+    }
+
+    /// Sort the editions and keep the first `limit` of them
+    pub fn arrange(&mut self, sort: ReadingSort, limit: Option<usize>) {
+        match sort {
+            ReadingSort::Recent => self.editions.sort_by(|a, b| b.updated.cmp(&a.updated)),
+            ReadingSort::Percent => self
+                .editions
+                .sort_by_key(|x| (x.percent().is_none(), std::cmp::Reverse(x.percent()))),
+            ReadingSort::Title => self.editions.sort_by_key(|x| x.title.to_lowercase()),
+        }
+        if let Some(limit) = limit {
+            self.editions.truncate(limit);
+        }
+    }
+
+    /// Render one line per edition with how far along it is
+    pub async fn render(&self, conn: &sqlx::SqlitePool, config: &Config) -> Result<String> {
+        let mut s = String::new();
+        for reading in &self.editions {
+            let mut line = String::new();
+            DisplayTerminal::fmt(&reading.edition, &mut line, conn, config).await?;
+            match (reading.page, reading.pages, reading.percent()) {
+                (Some(page), Some(total), Some(percent)) => {
+                    line.push_str(&format!(" page {page} of {total}, {percent}%"))
+                }
+                (Some(page), ..) => line.push_str(&format!(" page {page}")),
+                (None, ..) => line.push_str(&format!(" {}", "just started".with(COLOR_DIMMED))),
+            }
+            if let Some(estimate) = &reading.estimate {
+                line.push_str(&format!(
+                    ", finishing {}",
+                    estimate.humanize(config.timestamp_accuracy)
+                ));
+            }
+            s.push_str(&format!("{line}\n"));
+        }
+        if s.is_empty() {
+            s.push_str("Not reading anything\n");
+        }
+        s.pop();
+        Ok(s)
+    }
+}
@@ -0,0 +1,184 @@
+use crossterm::style::Stylize;
+use unicode_width::UnicodeWidthChar;
+
+/// Cells wider than this many columns are cut off with an ellipsis
+pub const MAX_CELL_WIDTH: usize = 40;
+
+/// Rows of (possibly styled) cells, rendered as aligned columns under a header
+///
+/// ```
+/// use tomex::table::Table;
+///
+/// let mut table = Table::new(&["Title", "Rating"]);
+/// table.push(vec!["The Hobbit".into(), "90".into()]);
+/// table.push(vec!["Dune".into(), "".into()]);
+/// let rendered = table.render();
+/// let lines: Vec<&str> = rendered.lines().skip(1).collect();
+/// assert_eq!(lines, ["The Hobbit  90", "Dune"]);
+/// ```
+pub struct Table {
+    pub headers: Vec<String>,
+    pub rows:    Vec<Vec<String>>,
+}
+
+impl Table {
+    pub fn new(headers: &[&str]) -> Self {
+        Self {
+            headers: headers.iter().map(|x| x.to_string()).collect(),
+            rows:    Vec::new(),
+        }
+    }
+
+    /// Add a row, cutting off cells wider than [MAX_CELL_WIDTH]
+    pub fn push(&mut self, row: Vec<String>) {
+        self.rows.push(
+            row.into_iter()
+                .map(|x| truncate(&x, MAX_CELL_WIDTH))
+                .collect(),
+        );
+    }
+
+    /// Render the bold header and rows, each column padded to its widest cell
+    pub fn render(&self) -> String {
+        let mut widths: Vec<usize> = self.headers.iter().map(|x| display_width(x)).collect();
+        for row in &self.rows {
+            for (i, cell) in row.iter().enumerate() {
+                if i < widths.len() {
+                    widths[i] = widths[i].max(display_width(cell));
+                }
+            }
+        }
+        let headers: Vec<String> = self
+            .headers
+            .iter()
+            .map(|x| x.clone().bold().to_string())
+            .collect();
+        let mut lines = vec![render_row(&headers, &widths)];
+        lines.extend(self.rows.iter().map(|row| render_row(row, &widths)));
+        lines.join("\n")
+    }
+}
+
+/// Pad every cell but the last to its column's width
+fn render_row(row: &[String], widths: &[usize]) -> String {
+    let mut line = String::new();
+    for (i, (cell, width)) in row.iter().zip(widths).enumerate() {
+        if i > 0 {
+            line.push_str("  ");
+        }
+        line.push_str(cell);
+        if i + 1 < row.len() {
+            line.push_str(&" ".repeat(width - display_width(cell)));
+        }
+    }
+    line.trim_end().to_string()
+}
+
+/// Width of `s` in terminal columns, not counting escape sequences
+///
+/// ```
+/// use crossterm::style::Stylize;
+/// use tomex::table::display_width;
+///
+/// assert_eq!(display_width(&"Dune".red().to_string()), 4);
+/// assert_eq!(display_width("三体"), 4);
+/// ```
+pub fn display_width(s: &str) -> usize {
+    let mut width = 0;
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            skip_escape(&mut chars);
+        } else {
+            width += c.width().unwrap_or(0);
+        }
+    }
+    width
+}
+
+/// Cut `s` to at most `width` columns ending in an ellipsis, keeping its
+/// escape sequences and resetting the style after the cut
+///
+/// ```
+/// use tomex::table::{display_width, truncate};
+///
+/// assert_eq!(truncate("The Hobbit", 20), "The Hobbit");
+/// assert_eq!(truncate("The Hobbit", 6), "The H…");
+/// assert_eq!(display_width(&truncate("\x1b[1mThe Hobbit\x1b[0m", 6)), 6);
+/// ```
+pub fn truncate(s: &str, width: usize) -> String {
+    if display_width(s) <= width {
+        return s.to_string();
+    }
+    let mut result = String::new();
+    let mut used = 0;
+    let mut styled = false;
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            styled = true;
+            result.push(c);
+            result.push_str(&skip_escape(&mut chars));
+            continue;
+        }
+        let w = c.width().unwrap_or(0);
+        if used + w + 1 > width {
+            break;
+        }
+        used += w;
+        result.push(c);
+    }
+    result.push('…');
+    if styled {
+        result.push_str("\x1b[0m");
+    }
+    result
+}
+
+/// Remove the escape sequences from `s`, leaving only its text
+///
+/// ```
+/// use crossterm::style::Stylize;
+/// use tomex::table::strip_escapes;
+///
+/// assert_eq!(strip_escapes(&format!("{} Herbert", "Frank".red())), "Frank Herbert");
+/// ```
+pub fn strip_escapes(s: &str) -> String {
+    let mut result = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            skip_escape(&mut chars);
+        } else {
+            result.push(c);
+        }
+    }
+    result
+}
+
+/// Cross out `s`, keeping it crossed out past any style resets inside it
+///
+/// ```
+/// use tomex::table::cross_out;
+///
+/// assert_eq!(cross_out("Dune"), "\x1b[9mDune\x1b[29m");
+/// assert_eq!(
+///     cross_out("\x1b[1mDune\x1b[0m!"),
+///     "\x1b[9m\x1b[1mDune\x1b[0m\x1b[9m!\x1b[29m"
+/// );
+/// ```
+pub fn cross_out(s: &str) -> String {
+    format!("\x1b[9m{}\x1b[29m", s.replace("\x1b[0m", "\x1b[0m\x1b[9m"))
+}
+
+/// Consume the rest of an escape sequence after its `\x1b`, returning it
+fn skip_escape(chars: &mut std::str::Chars) -> String {
+    let mut sequence = String::new();
+    for c in chars.by_ref() {
+        sequence.push(c);
+        if c.is_ascii_alphabetic() {
+            break;
+        }
+    }
+    sequence
+}
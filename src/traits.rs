@@ -1,4 +1,5 @@
 use std::fmt::Display;
+use std::io::Write;
 
 use anyhow::Result;
 use sqlx::{
@@ -6,15 +7,15 @@ use sqlx::{
     FromRow,
 };
 
-use crate::config;
-use crate::types::{option_to_create::OptionToCreate, uuid::Uuid};
+use crate::config::{self, Styleable};
+use crate::types::{option_to_create::OptionToCreate, text::Text, uuid::Uuid};
 
 /// A trait which corresponds to a junction table between two other types in the
 /// database
 pub trait JunctionTable<A, B>
 where
     A: CRUD + Eq,
-    B: CRUD + Eq,
+    B: CRUD + Eq + Clone + 'static,
     Self: Sized + Send + Unpin,
     Self: for<'r> FromRow<'r, SqliteRow>,
 {
@@ -39,9 +40,9 @@ where
         Ok(results)
     }
 
-    /// Create the junction table
-    async fn create_table(conn: &sqlx::SqlitePool) -> Result<()> {
-        sqlx::query(&format!(
+    /// The `CREATE TABLE` statement of the junction table
+    fn ddl() -> String {
+        format!(
             r#"
             CREATE TABLE IF NOT EXISTS {table_name_self} (
             	{singular_name_a}_id	INT NOT NULL,
@@ -56,14 +57,17 @@ where
             table_name_b = B::TABLE_NAME,
             singular_name_a = A::NAME_SINGULAR,
             singular_name_b = B::NAME_SINGULAR,
-        ))
-        .execute(conn)
-        .await?;
+        )
+    }
+
+    /// Create the junction table
+    async fn create_table(conn: &sqlx::SqlitePool) -> Result<()> {
+        sqlx::query(&Self::ddl()).execute(conn).await?;
         Ok(())
     }
 
-    /// Insert a new link between `a` and `b`
-    async fn insert(conn: &sqlx::SqlitePool, a: &A, b: &B) -> Result<()> {
+    /// Insert a new link between `a` and `b`, `conn` can be a transaction
+    async fn insert(conn: &mut sqlx::SqliteConnection, a: &A, b: &B) -> Result<()> {
         sqlx::query(&format!(
             r#"
             INSERT INTO {table_name_self} 
@@ -117,7 +121,7 @@ where
         let mut b_s = vec![];
         for result in results {
             let id = result.get_id_b().await;
-            b_s.push(B::get_by_id(conn, id).await?);
+            b_s.push(crate::cache::get_by_id::<B>(conn, id).await?);
         }
 
         Ok(b_s)
@@ -187,7 +191,7 @@ where
         else if old.is_none() {
             if let Some(b_s) = new {
                 for b in b_s {
-                    Self::insert(conn, a, b).await?;
+                    Self::insert(&mut *conn.acquire().await?, a, b).await?;
                 }
             }
         }
@@ -198,7 +202,7 @@ where
             for b in new {
                 // If the B didn't exist before, add it
                 if !old.contains(b) {
-                    Self::insert(conn, a, b).await?;
+                    Self::insert(&mut *conn.acquire().await?, a, b).await?;
                 }
             }
             for b in old {
@@ -348,16 +352,46 @@ where
         .is_empty())
     }
     /// Initialise table, i.e. create and potentially insert data if the table
-    /// doesn't already exist
-    async fn init_table(conn: &sqlx::SqlitePool) -> Result<()> {
+    /// doesn't already exist. Without `seed` no default data is inserted,
+    /// otherwise the lists it configures or the built-in defaults
+    async fn init_table(conn: &sqlx::SqlitePool, seed: Option<&config::SeedConfig>) -> Result<()> {
         if !Self::table_exists(conn).await? {
-            return Self::create_table(conn).await;
+            return Self::create_table(conn, seed).await;
         }
+        Self::migrate_table(conn).await
+    }
+    /// Bring a table created by an older version up to date
+    async fn migrate_table(_conn: &sqlx::SqlitePool) -> Result<()> {
         Ok(())
     }
+    /// Add a column to the table unless it already exists
+    async fn add_column(conn: &sqlx::SqlitePool, name: &str, definition: &str) -> Result<()> {
+        let exists = !sqlx::query(&format!(
+            "SELECT name FROM pragma_table_info('{}') WHERE name = ?1;",
+            Self::TABLE_NAME
+        ))
+        .bind(name)
+        .fetch_all(conn)
+        .await?
+        .is_empty();
+        if !exists {
+            sqlx::query(&format!(
+                "ALTER TABLE {} ADD COLUMN {name} {definition};",
+                Self::TABLE_NAME
+            ))
+            .execute(conn)
+            .await?;
+        }
+        Ok(())
+    }
+    /// The `CREATE TABLE` statement of the table, which
+    /// [CreateTable::create_table] executes
+    fn ddl() -> String;
     /// Create the table and potentially insert data (like default genre names)
-    /// (will insert duplicate data if the table already exists)
-    async fn create_table(conn: &sqlx::SqlitePool) -> Result<()>;
+    /// if `seed` is set (will insert duplicate data if the table already
+    /// exists)
+    async fn create_table(conn: &sqlx::SqlitePool, seed: Option<&config::SeedConfig>)
+    -> Result<()>;
 }
 
 /// Singular and plural names for type & name of table in database, for example:
@@ -398,7 +432,11 @@ where
     Self: Sized,
 {
     /// Insert self into database
-    async fn insert(&self, conn: &sqlx::SqlitePool) -> Result<SqliteQueryResult>;
+    async fn insert(&self, conn: &sqlx::SqlitePool) -> Result<SqliteQueryResult> {
+        self.insert_with(&mut *conn.acquire().await?).await
+    }
+    /// Insert self using a single connection, which can be a transaction
+    async fn insert_with(&self, conn: &mut sqlx::SqliteConnection) -> Result<SqliteQueryResult>;
     /// Create self by prompts
     // async fn create_by_prompt(conn: &sqlx::SqlitePool) -> Result<Self>;
     /// Create self by prompts and insert
@@ -407,11 +445,12 @@ where
         Self: Insertable + PromptType,
     {
         let x = Self::create_by_prompt("", None::<&Self>, conn).await?;
-        if !inquire::Confirm::new("Add to database?")
-            .with_default(true)
-            .prompt()?
-        {
-            anyhow::bail!("Aborted");
+        if !prompt_or_abort(|| {
+            inquire::Confirm::new("Add to database?")
+                .with_default(true)
+                .prompt()
+        })? {
+            anyhow::bail!(crate::error::Error::Aborted("Aborted".into()));
         };
         x.insert(conn).await?;
         Ok(x)
@@ -429,6 +468,7 @@ where
     Self: Send,
     Self: Unpin,
     Self: PromptType,
+    Self: 'static,
 {
     /// Return record with id from database
     async fn get_by_id(conn: &sqlx::SqlitePool, id: &Uuid) -> Result<Self> {
@@ -440,6 +480,36 @@ where
         .fetch_one(conn)
         .await?)
     }
+    /// Check if a record with id exists, without fetching it
+    ///
+    /// ```
+    /// use tomex::{traits::*, types::{pace::Pace, uuid::Uuid}};
+    ///
+    /// # tokio_test();
+    /// # #[tokio::main]
+    /// # async fn tokio_test() {
+    /// let conn = sqlx::sqlite::SqlitePoolOptions::new()
+    ///     .max_connections(1)
+    ///     .connect("sqlite::memory:")
+    ///     .await
+    ///     .unwrap();
+    /// Pace::init_table(&conn, Some(&Default::default())).await.unwrap();
+    /// let slow = Uuid(uuid::uuid!("7b0f2901-e058-4901-a527-307d4be12baf"));
+    /// assert!(Pace::exists(&conn, &slow).await.unwrap());
+    /// let unknown = Uuid(uuid::Uuid::nil());
+    /// assert!(!Pace::exists(&conn, &unknown).await.unwrap());
+    /// # }
+    /// ```
+    async fn exists(conn: &sqlx::SqlitePool, id: &Uuid) -> Result<bool> {
+        Ok(sqlx::query(&format!(
+            "SELECT 1 FROM {} WHERE id = ?1 AND deleted = 0 LIMIT 1;",
+            Self::TABLE_NAME
+        ))
+        .bind(id)
+        .fetch_optional(conn)
+        .await?
+        .is_some())
+    }
     /// Get all records from this database
     async fn get_all(conn: &sqlx::SqlitePool) -> Result<Vec<Self>> {
         Ok(sqlx::query_as::<_, Self>(&format!(
@@ -449,13 +519,106 @@ where
         .fetch_all(conn)
         .await?)
     }
+    /// Get all records from this database, including removed ones
+    async fn get_all_including_deleted(conn: &sqlx::SqlitePool) -> Result<Vec<Self>> {
+        Ok(
+            sqlx::query_as::<_, Self>(&format!("SELECT * FROM {};", Self::TABLE_NAME))
+                .fetch_all(conn)
+                .await?,
+        )
+    }
+    /// Ids of all removed records
+    async fn deleted_ids(conn: &sqlx::SqlitePool) -> Result<std::collections::HashSet<Uuid>> {
+        let ids: Vec<(Uuid,)> = sqlx::query_as(&format!(
+            "SELECT id FROM {} WHERE deleted = 1;",
+            Self::TABLE_NAME
+        ))
+        .fetch_all(conn)
+        .await?;
+        Ok(ids.into_iter().map(|(id,)| id).collect())
+    }
+    /// SQL condition matching a record against a search term bound as `?1`,
+    /// lets tables with many records be searched instead of listed in prompts
+    const SEARCH_CONDITION: Option<&'static str> = None;
+    /// Number of records in this database
+    async fn count(conn: &sqlx::SqlitePool) -> Result<usize> {
+        let (count,): (i64,) = sqlx::query_as(&format!(
+            "SELECT COUNT(*) FROM {} WHERE deleted = 0;",
+            Self::TABLE_NAME
+        ))
+        .fetch_one(conn)
+        .await?;
+        Ok(usize::try_from(count)?)
+    }
+    /// Number of records in this database, including removed ones
+    async fn count_including_deleted(conn: &sqlx::SqlitePool) -> Result<usize> {
+        let (count,): (i64,) =
+            sqlx::query_as(&format!("SELECT COUNT(*) FROM {};", Self::TABLE_NAME))
+                .fetch_one(conn)
+                .await?;
+        Ok(usize::try_from(count)?)
+    }
+    /// Get at most `limit` records containing `term` according to
+    /// [Queryable::SEARCH_CONDITION]
+    async fn search(conn: &sqlx::SqlitePool, term: &str, limit: usize) -> Result<Vec<Self>> {
+        let Some(condition) = Self::SEARCH_CONDITION else {
+            return Ok(vec![]);
+        };
+        let found = sqlx::query_as::<_, Self>(&format!(
+            "SELECT * FROM {} WHERE deleted = 0 AND ({condition}) LIMIT {limit};",
+            Self::TABLE_NAME
+        ))
+        .bind(format!("%{}%", term.trim()))
+        .fetch_all(conn)
+        .await?;
+        Ok(Self::sort_for_display(found).await)
+    }
+    /// Whether there are too many records to list them all in a prompt
+    async fn prompt_by_search(conn: &sqlx::SqlitePool) -> Result<bool> {
+        Ok(Self::SEARCH_CONDITION.is_some() && Self::count(conn).await? > PROMPT_MAX_OPTIONS)
+    }
+    /// Select a record by typing to search, suggesting the first matches as
+    /// you type
+    async fn search_by_prompt(conn: &sqlx::SqlitePool, skippable: bool) -> Result<Option<Self>> {
+        let completer = SearchCompleter::<Self>::new(conn);
+        let found = completer.found.clone();
+        loop {
+            // The completer blocks on its queries, which only a thread outside
+            // the runtime may do
+            let completer = completer.clone();
+            let answer = tokio::task::spawn_blocking(move || {
+                let message = format!("Search {}:", Self::NAME_SINGULAR);
+                let prompt = inquire::Text::new(&message)
+                    .with_help_message("Type to search, ↑↓ to move, enter to select")
+                    .with_autocomplete(completer);
+                match skippable {
+                    true => Ok(prompt.prompt_skippable()?),
+                    false => prompt_or_abort(|| prompt.clone().prompt()).map(Some),
+                }
+            })
+            .await??;
+            let Some(answer) = answer else {
+                return Ok(None);
+            };
+            let found = std::mem::take(&mut *found.lock().unwrap());
+            let exact = found.iter().position(|x| x.to_string() == answer);
+            match (exact, found.len()) {
+                (Some(i), _) => return Ok(found.into_iter().nth(i)),
+                (None, 1) => return Ok(found.into_iter().next()),
+                (None, 0) => println!("No {} matches {answer}", Self::NAME_SINGULAR),
+                (None, _) => println!("Select one of the suggestions"),
+            }
+        }
+    }
     /// Select a record by a prompt from a list of all records
     async fn query_by_prompt(conn: &sqlx::SqlitePool) -> Result<Self> {
-        Ok(inquire::Select::new(
-            &format!("Select {}:", Self::NAME_SINGULAR),
-            Self::get_all(conn).await?,
-        )
-        .prompt()?)
+        if Self::prompt_by_search(conn).await? {
+            let found = Self::search_by_prompt(conn, false).await?;
+            return found.ok_or_else(|| crate::error::Error::Aborted("Cancelled".into()).into());
+        }
+        let message = format!("Select {}:", Self::NAME_SINGULAR);
+        let prompt = inquire::Select::new(&message, Self::get_all(conn).await?);
+        prompt_or_abort(|| prompt.clone().prompt())
     }
     /// Like `query_by_prompt` or create and insert a new record
     async fn query_or_create_by_prompt(conn: &sqlx::SqlitePool) -> Result<Self>
@@ -463,8 +626,8 @@ where
         Self: Insertable,
     {
         let options = OptionToCreate::create_option_to_create(Self::get_all(conn).await?);
-        let result =
-            inquire::Select::new(&format!("Select {}:", Self::NAME_SINGULAR), options).prompt()?;
+        let message = format!("Select {}:", Self::NAME_SINGULAR);
+        let result = prompt_or_abort(|| inquire::Select::new(&message, options.clone()).prompt())?;
         match result {
             OptionToCreate::Value(value) => Ok(value),
             OptionToCreate::Create => {
@@ -475,6 +638,9 @@ where
     }
     /// Like `query_by_prompt` but can be skipped
     async fn query_by_prompt_skippable(conn: &sqlx::SqlitePool) -> Result<Option<Self>> {
+        if Self::prompt_by_search(conn).await? {
+            return Self::search_by_prompt(conn, true).await;
+        }
         Ok(inquire::Select::new(
             &format!("Select {}:", Self::NAME_SINGULAR),
             Self::get_all(conn).await?,
@@ -500,21 +666,66 @@ where
             None => Ok(None),
         }
     }
-    /// Select a single record from the database by parsing [clap] matches
+
+    /// Print records selected by the arguments of the query command
+    ///
+    /// With `--json` the records are printed as JSON instead, fields are
+    /// always emitted in the order they're declared in on the struct so the
+    /// output is stable across runs
     async fn query_by_clap(
         conn: &sqlx::SqlitePool,
         matches: &clap::ArgMatches,
         config: &config::Config,
-    ) -> Result<()> {
+    ) -> Result<()>
+    where
+        Self: serde::Serialize,
+        Self: serde::de::DeserializeOwned,
+        Self: Id,
+    {
+        let mut out = QueryOutput::by_clap(matches);
+        Self::query_by_clap_to(conn, matches, config, &mut out).await?;
+        out.finish()
+    }
+
+    /// Like [Queryable::query_by_clap], but writing to `out` so callers can
+    /// add their own lines before it's finished
+    async fn query_by_clap_to(
+        conn: &sqlx::SqlitePool,
+        matches: &clap::ArgMatches,
+        config: &config::Config,
+        out: &mut QueryOutput,
+    ) -> Result<()>
+    where
+        Self: serde::Serialize,
+        Self: serde::de::DeserializeOwned,
+        Self: Id,
+    {
+        if matches.get_flag("count-only") {
+            out.line(&Self::count_where(conn, matches).await?.to_string())?;
+            return Ok(());
+        }
+        let json = matches.get_flag("json");
+        let table =
+            matches.try_get_one::<String>("format").ok().flatten() == Some(&"table".to_string());
+        if table && Self::TABLE_HEADERS.is_empty() {
+            return Err(crate::error::Error::Usage(format!(
+                "Can't show {} as a table",
+                Self::NAME_PLURAL
+            ))
+            .into());
+        }
+        let pretty = pretty_by_clap(matches);
+        let fields = fields_by_clap::<Self>(matches)?;
         if let Some(clap::parser::ValueSource::CommandLine) = matches.value_source("interactive") {
             match Self::query_by_prompt_skippable(conn).await? {
+                Some(mut x) if json => {
+                    x.hydrate_for_export(conn).await?;
+                    out.line(&to_json_selected(&x, &fields, pretty)?)?
+                }
                 Some(x) => {
-                    println!(
-                        "{}",
-                        DisplayTerminal::fmt_to_string(&x, conn, Some(" "), config).await?
-                    )
+                    out.line(&DisplayTerminal::fmt_to_string(&x, conn, Some(" "), config).await?)?
                 }
-                None => println!("No {} selected.", Self::NAME_SINGULAR),
+                None => out.line(&format!("No {} selected.", Self::NAME_SINGULAR))?,
             }
         }
         if let Some(clap::parser::ValueSource::CommandLine) = matches.value_source("uuid") {
@@ -522,25 +733,56 @@ where
                 Some(uuid_str) => match uuid::Uuid::parse_str(uuid_str) {
                     Ok(uuid) => {
                         let uuid = Uuid(uuid);
-                        println!(
-                            "{}",
-                            DisplayTerminal::fmt_to_string(
-                                &Self::get_by_id(conn, &uuid).await?,
-                                conn,
-                                Some(" "),
-                                config
-                            )
-                            .await?
+                        if !Self::exists(conn, &uuid).await? {
+                            return Err(crate::error::Error::NotFound(format!(
+                                "No {} with id {}",
+                                Self::NAME_SINGULAR,
+                                uuid.0
+                            ))
+                            .into());
+                        }
+                        let mut x = Self::get_by_id_by_clap(conn, &uuid, matches).await?;
+                        if json {
+                            x.hydrate_for_export(conn).await?;
+                            out.line(&to_json_selected(&x, &fields, pretty)?)?;
+                        } else {
+                            let mut buf = String::from(" ");
+                            x.info_card(&mut buf, conn, config).await?;
+                            out.line(&buf)?;
+                        }
+                    }
+                    Err(_) => {
+                        return Err(
+                            crate::error::Error::Usage(format!("Invalid uuid {uuid_str}")).into(),
                         );
                     }
-                    Err(_) => println!("Invalid uuid"),
                 },
-                None => println!("No uuid supplied"),
+                None => return Err(crate::error::Error::Usage("No uuid supplied".into()).into()),
             }
         }
         //else if let Some(ValueSource::CommandLine) = _matches.value_source("all")
-        else {
-            println!(
+        else if json {
+            let group_by = Self::group_by_clap(matches)?;
+            let (mut xs, _) = Self::get_all_by_clap_with_deleted(conn, matches).await?;
+            for x in &mut xs {
+                x.hydrate_for_export(conn).await?;
+            }
+            let xs = Self::sort_by_clap(xs, matches).await;
+            match group_by {
+                Some(key) => {
+                    let groups = Self::group(conn, &xs, key)
+                        .await?
+                        .into_iter()
+                        .map(|(group, xs)| Ok((group, select_fields(&xs, &fields)?)))
+                        .collect::<Result<std::collections::BTreeMap<String, _>>>()?;
+                    out.line(&to_json(&groups, pretty)?)?;
+                }
+                None => out.line(&to_json_selected(&xs, &fields, pretty)?)?,
+            }
+        } else {
+            let group_by = Self::group_by_clap(matches)?;
+            let (xs, deleted) = Self::get_all_by_clap_with_deleted(conn, matches).await?;
+            out.line(&format!(
                 "\n{}{}:",
                 Self::NAME_PLURAL
                     .chars()
@@ -549,21 +791,275 @@ where
                     .to_uppercase()
                     .collect::<String>(),
                 Self::NAME_PLURAL.chars().skip(1).collect::<String>()
-            );
-            let xs = Self::get_all(conn).await?;
-            for x in Self::sort_for_display(xs).await {
-                println!(
-                    "{}",
-                    DisplayTerminal::fmt_to_string(&x, conn, Some(" • "), config).await?
-                );
+            ))?;
+            let xs = Self::sort_by_clap(xs, matches).await;
+            let groups = match group_by {
+                Some(key) => Self::group(conn, &xs, key).await?,
+                None => vec![(String::new(), xs.iter().collect())],
+            };
+            let annotations = Self::annotations_by_clap(conn, matches, &xs).await?;
+            for (group, xs) in groups {
+                if !group.is_empty() {
+                    out.line(&format!(
+                        "\n{}:",
+                        group.style(&config.output_group.style_content)
+                    ))?;
+                }
+                if table {
+                    let mut table = crate::table::Table::new(Self::TABLE_HEADERS);
+                    for x in xs {
+                        let mut row = x.table_row(conn, config).await?;
+                        if deleted.contains(&x.id().await) {
+                            row = row.iter().map(|x| crate::table::cross_out(x)).collect();
+                            let removed = "(removed)".style(&config.output_error.style_content);
+                            row[0] = format!("{} {removed}", row[0]);
+                        }
+                        table.push(row);
+                    }
+                    out.line(&table.render())?;
+                    continue;
+                }
+                for x in xs {
+                    let mut line =
+                        DisplayTerminal::fmt_to_string(x, conn, Some(" • "), config).await?;
+                    if let Some(annotation) = annotations.get(&x.id().await) {
+                        line.truncate(line.trim_end().len());
+                        line.push_str(annotation);
+                    }
+                    if deleted.contains(&x.id().await) {
+                        line = format!(
+                            "{} {}",
+                            crate::table::cross_out(&line),
+                            "(removed)".style(&config.output_error.style_content)
+                        );
+                    }
+                    out.line(&line)?;
+                }
             }
         }
         Ok(())
     }
 
+    /// Extra text to show after the listed records `xs`, by id, for flags
+    /// that are cheaper to answer for all records at once. It's appended
+    /// without trailing whitespace in between, so it should start with a space
+    async fn annotations_by_clap(
+        _conn: &sqlx::SqlitePool,
+        _matches: &clap::ArgMatches,
+        _xs: &[Self],
+    ) -> Result<std::collections::HashMap<Uuid, String>> {
+        Ok(std::collections::HashMap::new())
+    }
+
+    /// Column headers for `--format table`, empty if this type has no table
+    const TABLE_HEADERS: &'static [&'static str] = &[];
+
+    /// The cells of this record's row for `--format table`, one per
+    /// [Queryable::TABLE_HEADERS]
+    async fn table_row(
+        &self,
+        _conn: &sqlx::SqlitePool,
+        _config: &config::Config,
+    ) -> Result<Vec<String>> {
+        Ok(vec![])
+    }
+
+    /// Keys this type can be grouped by with `--group-by`
+    const GROUP_KEYS: &'static [&'static str] = &[];
+
+    /// Names of the groups a record is in when grouping by `key`, one of
+    /// [Queryable::GROUP_KEYS]
+    async fn group_names(&self, _conn: &sqlx::SqlitePool, _key: &str) -> Result<Vec<String>> {
+        Ok(vec![])
+    }
+
+    /// The `--group-by` key, if it's one this type can be grouped by
+    fn group_by_clap(matches: &clap::ArgMatches) -> Result<Option<&str>> {
+        match matches.get_one::<String>("group-by") {
+            Some(key) if Self::GROUP_KEYS.contains(&key.as_str()) => Ok(Some(key)),
+            Some(key) if Self::GROUP_KEYS.is_empty() => Err(crate::error::Error::Usage(format!(
+                "Can't group {} by {key}, they can't be grouped",
+                Self::NAME_PLURAL
+            ))
+            .into()),
+            Some(key) => Err(crate::error::Error::Usage(format!(
+                "Can't group {} by {key}, use one of {}",
+                Self::NAME_PLURAL,
+                Self::GROUP_KEYS.join(", ")
+            ))
+            .into()),
+            None => Ok(None),
+        }
+    }
+
+    /// Bucket records by their groups, sorted by name, a record can be in
+    /// several groups and records in none are put under [UNGROUPED]
+    async fn group<'a>(
+        conn: &sqlx::SqlitePool,
+        xs: &'a [Self],
+        key: &str,
+    ) -> Result<Vec<(String, Vec<&'a Self>)>> {
+        let mut groups: std::collections::BTreeMap<String, Vec<&Self>> = Default::default();
+        let mut ungrouped = vec![];
+        for x in xs {
+            let names = x.group_names(conn, key).await?;
+            if names.is_empty() {
+                ungrouped.push(x);
+            }
+            for name in names {
+                groups.entry(name).or_default().push(x);
+            }
+        }
+        let mut groups: Vec<(String, Vec<&Self>)> = groups.into_iter().collect();
+        if !ungrouped.is_empty() {
+            groups.push((UNGROUPED.to_string(), ungrouped));
+        }
+        Ok(groups)
+    }
+
     async fn sort_for_display(x: Vec<Self>) -> Vec<Self> {
         return x;
     }
+
+    /// Sort for display, types whose filter flags change the order override
+    /// this
+    async fn sort_by_clap(x: Vec<Self>, _matches: &clap::ArgMatches) -> Vec<Self> {
+        Self::sort_for_display(x).await
+    }
+
+    /// Get all records matching the filters of the query command
+    async fn get_all_by_clap(
+        conn: &sqlx::SqlitePool,
+        matches: &clap::ArgMatches,
+    ) -> Result<Vec<Self>> {
+        Self::get_all_by_clap_filtered(conn, matches, false).await
+    }
+
+    /// Like [Queryable::get_all_by_clap], with `include_deleted` also the
+    /// removed records matching the filters. Types with their own filter flags
+    /// override this
+    async fn get_all_by_clap_filtered(
+        conn: &sqlx::SqlitePool,
+        _matches: &clap::ArgMatches,
+        include_deleted: bool,
+    ) -> Result<Vec<Self>> {
+        match include_deleted {
+            true => Self::get_all_including_deleted(conn).await,
+            false => Self::get_all(conn).await,
+        }
+    }
+
+    /// Like [Queryable::get_all_by_clap], with `--include-deleted` also
+    /// returning the removed records and their ids
+    async fn get_all_by_clap_with_deleted(
+        conn: &sqlx::SqlitePool,
+        matches: &clap::ArgMatches,
+    ) -> Result<(Vec<Self>, std::collections::HashSet<Uuid>)>
+    where
+        Self: Id,
+    {
+        let include_deleted = matches.get_flag("include-deleted");
+        let xs = Self::get_all_by_clap_filtered(conn, matches, include_deleted).await?;
+        let deleted = match include_deleted {
+            true => Self::deleted_ids(conn).await?,
+            false => Default::default(),
+        };
+        Ok((xs, deleted))
+    }
+
+    /// Whether the arguments of the query command filter the records, types
+    /// overriding [Queryable::get_all_by_clap_filtered] override this too
+    fn filtered_by_clap(_matches: &clap::ArgMatches) -> bool {
+        false
+    }
+
+    /// Count the records matching the filters of the query command, in SQL
+    /// unless [Queryable::filtered_by_clap]. Removed records count too with
+    /// `--include-deleted`
+    async fn count_where(conn: &sqlx::SqlitePool, matches: &clap::ArgMatches) -> Result<usize> {
+        let include_deleted = matches.get_flag("include-deleted");
+        if Self::filtered_by_clap(matches) {
+            return Ok(
+                Self::get_all_by_clap_filtered(conn, matches, include_deleted)
+                    .await?
+                    .len(),
+            );
+        }
+        match include_deleted {
+            true => Self::count_including_deleted(conn).await,
+            false => Self::count(conn).await,
+        }
+    }
+
+    /// Get a record by id for the query command, for types whose flags change
+    /// how a record is shown
+    async fn get_by_id_by_clap(
+        conn: &sqlx::SqlitePool,
+        id: &Uuid,
+        _matches: &clap::ArgMatches,
+    ) -> Result<Self> {
+        Self::get_by_id(conn, id).await
+    }
+
+    /// Fill in the relations of a record before it's serialized, so exports
+    /// don't contain `null` for things that are only stored in other tables.
+    /// Types with relations override this
+    ///
+    /// ```
+    /// use tomex::traits::*;
+    /// use tomex::types::{
+    ///     author::Author, book::Book, content_warning::ContentWarning, genre::Genre,
+    ///     junction_tables::{
+    ///         book_author::BookAuthor, book_content_warning::BookContentWarning,
+    ///         book_genre::BookGenre, book_shelf::BookShelf,
+    ///     },
+    ///     shelf::Shelf, text::Text,
+    /// };
+    ///
+    /// # tokio_test();
+    /// # #[tokio::main]
+    /// # async fn tokio_test() {
+    /// let conn = sqlx::sqlite::SqlitePoolOptions::new()
+    ///     .max_connections(1)
+    ///     .connect("sqlite::memory:")
+    ///     .await
+    ///     .unwrap();
+    /// # Book::init_table(&conn, None).await.unwrap();
+    /// # Author::init_table(&conn, None).await.unwrap();
+    /// # Genre::init_table(&conn, None).await.unwrap();
+    /// # ContentWarning::init_table(&conn, None).await.unwrap();
+    /// # Shelf::init_table(&conn, None).await.unwrap();
+    /// # BookAuthor::create_table(&conn).await.unwrap();
+    /// # BookGenre::create_table(&conn).await.unwrap();
+    /// # BookContentWarning::create_table(&conn).await.unwrap();
+    /// # BookShelf::create_table(&conn).await.unwrap();
+    /// let author = Author {
+    ///     id: tomex::types::uuid::Uuid(uuid::Uuid::new_v4()),
+    ///     name: Some(Text("Susanna Clarke".into())),
+    ///     ..Author::default()
+    /// };
+    /// let book = Book {
+    ///     title: Text("Piranesi".into()),
+    ///     summary: Some(Text("A house of endless halls".into())),
+    ///     ..Book::default()
+    /// };
+    /// author.insert(&conn).await.unwrap();
+    /// book.insert(&conn).await.unwrap();
+    /// let mut tx = conn.begin().await.unwrap();
+    /// BookAuthor::insert(&mut tx, &book, &author).await.unwrap();
+    /// tx.commit().await.unwrap();
+    ///
+    /// // What `query book --json` and exports serialize
+    /// let mut books = Book::get_all(&conn).await.unwrap();
+    /// books[0].hydrate_for_export(&conn).await.unwrap();
+    /// let json = serde_json::to_value(&books).unwrap();
+    /// assert_eq!(json[0]["summary"], "A house of endless halls");
+    /// assert_eq!(json[0]["authors"][0]["name"], "Susanna Clarke");
+    /// # }
+    /// ```
+    async fn hydrate_for_export(&mut self, _conn: &sqlx::SqlitePool) -> Result<()> {
+        Ok(())
+    }
 }
 
 /// A type which corresponds to a database table entry and can be updated
@@ -589,6 +1085,412 @@ where
     // -> Result<()>;
 }
 
+/// Header for records which aren't in any group with `--group-by`
+pub const UNGROUPED: &str = "Ungrouped";
+
+/// Reference data like genres or moods, which is nothing more than a name
+pub trait ReferenceType
+where
+    Self: Queryable,
+    Self: Insertable,
+{
+    fn name(&self) -> &Text;
+    /// A new record with a random id
+    fn from_name(name: Text) -> Self;
+    /// Clean up a name according to the config before it's stored
+    fn normalize_name(name: Text) -> Text {
+        let config = config::Config::cached();
+        let name = name.normalized(config.text.collapse_whitespace);
+        if config.text.title_case_names {
+            name.title_case()
+        } else {
+            name
+        }
+    }
+    /// Get record by name, ignoring case. The name is normalized like stored
+    /// names are, so differences in whitespace the config removes don't matter
+    async fn get_by_name(conn: &sqlx::SqlitePool, name: &str) -> Result<Option<Self>> {
+        Ok(sqlx::query_as::<_, Self>(&format!(
+            "SELECT * FROM {} WHERE TRIM(name) = ?1 COLLATE NOCASE AND deleted = 0;",
+            Self::TABLE_NAME
+        ))
+        .bind(Self::normalize_name(Text(name.to_string())))
+        .fetch_optional(conn)
+        .await?)
+    }
+    /// Insert a record for every name which doesn't exist yet, returning the
+    /// created records and the names that were skipped
+    async fn insert_by_names(
+        conn: &sqlx::SqlitePool,
+        names: &[String],
+    ) -> Result<(Vec<Self>, Vec<String>)> {
+        let mut created: Vec<Self> = vec![];
+        let mut skipped = vec![];
+        let names = names.iter().map(|x| Self::normalize_name(Text(x.clone())));
+        for name in names.filter(|x| !x.0.is_empty()) {
+            let duplicate = created
+                .iter()
+                .any(|x| x.name().0.eq_ignore_ascii_case(&name.0));
+            if duplicate || Self::get_by_name(conn, &name.0).await?.is_some() {
+                skipped.push(name.0);
+                continue;
+            }
+            let x = Self::from_name(name);
+            x.insert(conn).await?;
+            created.push(x);
+        }
+        Ok((created, skipped))
+    }
+}
+
+/// Reference data other records link to, seeded with defaults when its table
+/// is created
+pub trait Referenced
+where
+    Self: Queryable,
+    Self: Removeable,
+    Self: Id,
+{
+    /// Built-in default names and their fixed uuids
+    fn defaults() -> Vec<(&'static str, uuid::Uuid)>;
+    /// Query for the id of every record linked to from a non-deleted record,
+    /// along with how often it is
+    fn usage_query() -> String;
+    /// How often each record is linked to, unused ones are missing
+    async fn usage_counts(conn: &sqlx::SqlitePool) -> Result<std::collections::HashMap<Uuid, i64>> {
+        let counts: Vec<(Uuid, i64)> = sqlx::query_as(&Self::usage_query()).fetch_all(conn).await?;
+        Ok(counts.into_iter().collect())
+    }
+    /// All records nothing links to
+    async fn get_all_unused(conn: &sqlx::SqlitePool) -> Result<Vec<Self>> {
+        let counts = Self::usage_counts(conn).await?;
+        let mut unused = vec![];
+        for x in Self::get_all(conn).await? {
+            if !counts.contains_key(&x.id().await) {
+                unused.push(x);
+            }
+        }
+        Ok(unused)
+    }
+    /// Whether this is one of the built-in defaults
+    async fn is_default(&self) -> bool {
+        let id = self.id().await;
+        Self::defaults().iter().any(|(_, x)| *x == id.0)
+    }
+    /// Insert the built-in defaults the table is missing, like ones added in a
+    /// newer version, keeping to the `configured` seed list if there is one.
+    /// Removed defaults and ones with the name of an existing record aren't
+    /// added. Returns the names of the added ones
+    ///
+    /// ```
+    /// use tomex::{traits::*, types::genre::Genre};
+    ///
+    /// # tokio_test();
+    /// # #[tokio::main]
+    /// # async fn tokio_test() {
+    /// let conn = sqlx::sqlite::SqlitePoolOptions::new()
+    ///     .max_connections(1)
+    ///     .connect("sqlite::memory:")
+    ///     .await
+    ///     .unwrap();
+    /// Genre::init_table(&conn, Some(&Default::default())).await.unwrap();
+    /// sqlx::query("DELETE FROM genres WHERE name = 'Horror';")
+    ///     .execute(&conn)
+    ///     .await
+    ///     .unwrap();
+    /// let added = Genre::insert_missing_defaults(&conn, None).await.unwrap();
+    /// assert_eq!(added, ["Horror"]);
+    /// let added = Genre::insert_missing_defaults(&conn, None).await.unwrap();
+    /// assert!(added.is_empty());
+    /// # }
+    /// ```
+    async fn insert_missing_defaults(
+        conn: &sqlx::SqlitePool,
+        configured: Option<Vec<String>>,
+    ) -> Result<Vec<String>> {
+        let defaults: Vec<uuid::Uuid> = Self::defaults().iter().map(|(_, id)| *id).collect();
+        let mut added = vec![];
+        for (name, id) in config::SeedConfig::resolve(configured, Self::defaults()) {
+            // Configured names that aren't defaults get a new id every time
+            if !defaults.contains(&id) {
+                continue;
+            }
+            let result = sqlx::query(&format!(
+                "INSERT INTO {table} ( id, name ) SELECT ?1, ?2 WHERE NOT EXISTS \
+                 (SELECT 1 FROM {table} WHERE id = ?1 OR name = ?2 COLLATE NOCASE);",
+                table = Self::TABLE_NAME
+            ))
+            .bind(Uuid(id))
+            .bind(&name)
+            .execute(conn)
+            .await?;
+            if result.rows_affected() > 0 {
+                added.push(name);
+            }
+        }
+        Ok(added)
+    }
+}
+
+/// Above this many records prompts search instead of listing all of them
+const PROMPT_MAX_OPTIONS: usize = 1000;
+/// How many matches a search prompt suggests at once
+const PROMPT_SEARCH_LIMIT: usize = 50;
+
+/// Suggests records matching the input of a text prompt, keeping the last
+/// matches so the chosen suggestion can be mapped back to its record
+struct SearchCompleter<T> {
+    conn:    sqlx::SqlitePool,
+    runtime: tokio::runtime::Handle,
+    found:   std::sync::Arc<std::sync::Mutex<Vec<T>>>,
+}
+
+impl<T> SearchCompleter<T> {
+    fn new(conn: &sqlx::SqlitePool) -> Self {
+        Self {
+            conn:    conn.clone(),
+            runtime: tokio::runtime::Handle::current(),
+            found:   Default::default(),
+        }
+    }
+}
+
+impl<T> Clone for SearchCompleter<T> {
+    fn clone(&self) -> Self {
+        Self {
+            conn:    self.conn.clone(),
+            runtime: self.runtime.clone(),
+            found:   self.found.clone(),
+        }
+    }
+}
+
+impl<T: Queryable> inquire::Autocomplete for SearchCompleter<T> {
+    fn get_suggestions(&mut self, input: &str) -> Result<Vec<String>, inquire::CustomUserError> {
+        // Prompts are synchronous, the prompt runs on a blocking thread so this
+        // can wait for the query
+        let found = self
+            .runtime
+            .block_on(T::search(&self.conn, input, PROMPT_SEARCH_LIMIT))
+            .map_err(|e| e.to_string())?;
+        let suggestions = found.iter().map(|x| x.to_string()).collect();
+        *self.found.lock().unwrap() = found;
+        Ok(suggestions)
+    }
+
+    fn get_completion(
+        &mut self,
+        _input: &str,
+        highlighted_suggestion: Option<String>,
+    ) -> Result<inquire::autocompletion::Replacement, inquire::CustomUserError> {
+        Ok(highlighted_suggestion)
+    }
+}
+
+/// Show a prompt which can't be skipped. Pressing Esc asks whether to cancel
+/// the command, failing with [crate::error::Error::Aborted] if so, and shows
+/// the prompt again otherwise
+pub fn prompt_or_abort<T>(
+    mut prompt: impl FnMut() -> inquire::error::InquireResult<T>,
+) -> Result<T> {
+    loop {
+        match prompt() {
+            Err(inquire::InquireError::OperationCanceled) => {
+                let cancel = inquire::Confirm::new("Cancel this command?")
+                    .with_default(false)
+                    .prompt_skippable()?;
+                // Pressing Esc again cancels too
+                if cancel != Some(false) {
+                    return Err(crate::error::Error::Aborted("Cancelled".into()).into());
+                }
+            }
+            result => return Ok(result?),
+        }
+    }
+}
+
+/// Whether JSON output should be indented, from `--pretty`/`--no-pretty` or
+/// else if stdout is a terminal
+fn pretty_by_clap(matches: &clap::ArgMatches) -> bool {
+    if matches.get_flag("pretty") {
+        true
+    } else if matches.get_flag("no-pretty") {
+        false
+    } else {
+        output_file_by_clap(matches).is_none()
+            && std::io::IsTerminal::is_terminal(&std::io::stdout())
+    }
+}
+
+fn output_file_by_clap(matches: &clap::ArgMatches) -> Option<&std::path::PathBuf> {
+    matches
+        .try_get_one::<std::path::PathBuf>("output-file")
+        .ok()
+        .flatten()
+}
+
+/// Where the query command writes its results, `--output-file` or else stdout
+pub struct QueryOutput {
+    buf:  Vec<u8>,
+    path: Option<std::path::PathBuf>,
+}
+
+impl QueryOutput {
+    /// Write to the file given by `--output-file`, or to stdout
+    ///
+    /// Lines for a file are kept until [QueryOutput::finish], so a query that
+    /// fails leaves an existing file untouched
+    pub fn by_clap(matches: &clap::ArgMatches) -> Self {
+        Self {
+            buf:  vec![],
+            path: output_file_by_clap(matches).cloned(),
+        }
+    }
+
+    /// Write a line, without styling if it goes to a file
+    pub fn line(&mut self, line: &str) -> Result<()> {
+        if self.path.is_some() {
+            writeln!(self.buf, "{}", crate::table::strip_escapes(line))?;
+        } else {
+            println!("{line}");
+        }
+        Ok(())
+    }
+
+    /// Write the file, if there is one
+    pub fn finish(self) -> Result<()> {
+        if let Some(path) = self.path {
+            std::fs::write(path, self.buf)?;
+        }
+        Ok(())
+    }
+}
+
+/// Serialize to JSON, formatted according to the query command's arguments
+pub fn to_json_by_clap(x: &impl serde::Serialize, matches: &clap::ArgMatches) -> Result<String> {
+    to_json(x, pretty_by_clap(matches))
+}
+
+/// Names of a struct's fields as it (de)serializes them, read from its
+/// [serde::Deserialize] impl so no instance is needed
+///
+/// ```
+/// use tomex::{traits::field_names, types::author::Author};
+///
+/// assert!(field_names::<Author>().contains(&"date_born"));
+/// assert!(field_names::<u32>().is_empty());
+/// ```
+pub fn field_names<T: serde::de::DeserializeOwned>() -> &'static [&'static str] {
+    struct Introspect<'a>(&'a mut &'static [&'static str]);
+    impl<'de> serde::Deserializer<'de> for Introspect<'_> {
+        type Error = serde::de::value::Error;
+
+        serde::forward_to_deserialize_any! {
+            bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes
+            byte_buf option unit unit_struct newtype_struct seq tuple tuple_struct map enum
+            identifier ignored_any
+        }
+
+        fn deserialize_any<V: serde::de::Visitor<'de>>(
+            self,
+            _visitor: V,
+        ) -> std::result::Result<V::Value, Self::Error> {
+            Err(serde::de::Error::custom("Not a struct"))
+        }
+
+        fn deserialize_struct<V: serde::de::Visitor<'de>>(
+            self,
+            _name: &'static str,
+            fields: &'static [&'static str],
+            _visitor: V,
+        ) -> std::result::Result<V::Value, Self::Error> {
+            *self.0 = fields;
+            Err(serde::de::Error::custom("Only reading field names"))
+        }
+    }
+    let mut fields: &'static [&'static str] = &[];
+    let _ = T::deserialize(Introspect(&mut fields));
+    fields
+}
+
+/// The `--fields` to limit JSON output to, failing for ones `T` doesn't have
+pub fn fields_by_clap<T: serde::de::DeserializeOwned>(
+    matches: &clap::ArgMatches,
+) -> Result<Option<Vec<String>>> {
+    let Some(fields) = matches.get_many::<String>("fields") else {
+        return Ok(None);
+    };
+    let known = field_names::<T>();
+    let fields: Vec<String> = fields.cloned().collect();
+    if let Some(unknown) = fields.iter().find(|x| !known.contains(&x.as_str())) {
+        return Err(crate::error::Error::Usage(format!(
+            "Unknown field `{unknown}`, expected one of {}",
+            known.join(", ")
+        ))
+        .into());
+    }
+    Ok(Some(fields))
+}
+
+/// Serialize a record or a list of records to a JSON value, keeping only
+/// `fields` if set
+///
+/// ```
+/// use tomex::traits::select_fields;
+///
+/// #[derive(serde::Serialize)]
+/// struct Book { title: &'static str, pages: u32 }
+///
+/// let books = vec![Book { title: "Dune", pages: 412 }];
+/// let fields = Some(vec!["title".to_string()]);
+/// let value = select_fields(&books, &fields).unwrap();
+/// assert_eq!(value, serde_json::json!([{ "title": "Dune" }]));
+/// ```
+pub fn select_fields(
+    x: &impl serde::Serialize,
+    fields: &Option<Vec<String>>,
+) -> Result<serde_json::Value> {
+    fn prune(value: serde_json::Value, fields: &[String]) -> serde_json::Value {
+        match value {
+            serde_json::Value::Object(map) => serde_json::Value::Object(
+                map.into_iter()
+                    .filter(|(key, _)| fields.contains(key))
+                    .collect(),
+            ),
+            serde_json::Value::Array(xs) => {
+                serde_json::Value::Array(xs.into_iter().map(|x| prune(x, fields)).collect())
+            }
+            value => value,
+        }
+    }
+    let value = serde_json::to_value(x)?;
+    Ok(match fields {
+        Some(fields) => prune(value, fields),
+        None => value,
+    })
+}
+
+/// Serialize to JSON like [to_json], keeping only `fields` if set
+fn to_json_selected(
+    x: &impl serde::Serialize,
+    fields: &Option<Vec<String>>,
+    pretty: bool,
+) -> Result<String> {
+    match fields {
+        Some(_) => to_json(&select_fields(x, fields)?, pretty),
+        None => to_json(x, pretty),
+    }
+}
+
+/// Serialize to JSON, indented if `pretty`
+fn to_json(x: &impl serde::Serialize, pretty: bool) -> Result<String> {
+    Ok(if pretty {
+        serde_json::to_string_pretty(x)?
+    } else {
+        serde_json::to_string(x)?
+    })
+}
+
 /// A type which corresponds to a database table entry and can be removed
 pub trait Removeable
 where
@@ -616,11 +1518,12 @@ where
         let x = Self::query_by_prompt_skippable(conn).await?;
         match x {
             Some(x) => {
-                if !inquire::Confirm::new(&format!("Are you sure you want to remove {x}?"))
-                    .with_default(false)
-                    .prompt()?
-                {
-                    anyhow::bail!("Aborted");
+                if !prompt_or_abort(|| {
+                    inquire::Confirm::new(&format!("Are you sure you want to remove {x}?"))
+                        .with_default(false)
+                        .prompt()
+                })? {
+                    anyhow::bail!(crate::error::Error::Aborted("Aborted".into()));
                 };
                 Self::remove(&x, conn).await?;
                 println!("Deleted");
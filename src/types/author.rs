@@ -2,28 +2,35 @@ use anyhow::Result;
 use crossterm::style::Stylize;
 use serde::{Deserialize, Serialize};
 use sqlx::{
-    sqlite::{SqliteQueryResult, SqliteRow},
     FromRow, Row,
+    sqlite::{SqliteQueryResult, SqliteRow},
 };
-use std::fmt::{Display, Write};
 use std::borrow::Cow;
+use std::fmt::{Display, Write};
 
 use crate::{
     config,
     config::Styleable,
     traits::*,
-    types::{text::Text, timestamp::OptionalTimestamp, uuid::Uuid},
+    types::{
+        text::Text,
+        timestamp::{NONE_SENTINEL, OptionalTimestamp},
+        uuid::Uuid,
+    },
 };
 use derives::*;
 
 #[derive(Default, Debug, Clone, PartialEq, Eq, Names, CRUD, Id, Serialize, Deserialize)]
 pub struct Author {
-    pub id: Uuid,
-    pub name: Option<Text>,
-    pub date_born: OptionalTimestamp,
-    pub date_died: OptionalTimestamp,
-    pub deleted: bool,
-    pub special: bool,
+    pub id:              Uuid,
+    pub name:            Option<Text>,
+    pub date_born:       OptionalTimestamp,
+    pub date_died:       OptionalTimestamp,
+    /// Id on OpenLibrary, like `OL23919A`
+    pub openlibrary_key: Option<Text>,
+    pub website:         Option<Text>,
+    pub deleted:         bool,
+    pub special:         bool,
 }
 
 impl Queryable for Author {
@@ -53,6 +60,57 @@ impl Queryable for Author {
         });
         x
     }
+
+    fn filtered_by_clap(matches: &clap::ArgMatches) -> bool {
+        AuthorFilter::from_clap(matches) != AuthorFilter::default()
+    }
+
+    async fn get_all_by_clap_filtered(
+        conn: &sqlx::SqlitePool,
+        matches: &clap::ArgMatches,
+        include_deleted: bool,
+    ) -> Result<Vec<Self>> {
+        let filter = AuthorFilter::from_clap(matches);
+        match (filter == AuthorFilter::default(), include_deleted) {
+            (true, true) => Self::get_all_including_deleted(conn).await,
+            (true, false) => Self::get_all(conn).await,
+            (false, _) => Self::get_filtered(conn, &filter, include_deleted).await,
+        }
+    }
+}
+
+/// Limits on when authors were born or died, by year, all of them exclusive
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct AuthorFilter {
+    pub born_after:  Option<i32>,
+    pub born_before: Option<i32>,
+    pub died_after:  Option<i32>,
+    pub died_before: Option<i32>,
+    /// Only authors without a date of death
+    pub alive:       bool,
+}
+
+impl AuthorFilter {
+    pub fn from_clap(matches: &clap::ArgMatches) -> Self {
+        let year = |name| matches.get_one::<i32>(name).copied();
+        Self {
+            born_after:  year("born-after"),
+            born_before: year("born-before"),
+            died_after:  year("died-after"),
+            died_before: year("died-before"),
+            alive:       matches.get_flag("alive"),
+        }
+    }
+}
+
+/// Start of `year` in the milliseconds timestamps are stored as
+fn year_start(year: i32) -> Result<i64> {
+    use chrono::TimeZone;
+    chrono::Utc
+        .with_ymd_and_hms(year, 1, 1, 0, 0, 0)
+        .single()
+        .map(|x| x.timestamp_millis())
+        .ok_or_else(|| anyhow::anyhow!("Year {year} is out of range"))
 }
 
 const UUID_UNKOWN: Uuid = Uuid(uuid::uuid!("00000000-0000-0000-0000-000000000000"));
@@ -67,34 +125,182 @@ impl Author {
         .fetch_optional(conn)
         .await?)
     }
-}
 
-impl PromptType for Author {
-    async fn create_by_prompt(
-        _prompt: &str,
-        _initial_value: Option<&Self>,
+    /// Authors matching `filter`, with `include_deleted` also removed ones.
+    /// Authors without the date a filter looks at never match it, neither do
+    /// special authors like the unknown author
+    ///
+    /// ```
+    /// use tomex::traits::*;
+    /// use tomex::types::{author::*, text::Text, timestamp::*};
+    ///
+    /// # tokio_test();
+    /// # #[tokio::main]
+    /// # async fn tokio_test() {
+    /// let conn = sqlx::sqlite::SqlitePoolOptions::new()
+    ///     .max_connections(1)
+    ///     .connect("sqlite::memory:")
+    ///     .await
+    ///     .unwrap();
+    /// Author::init_table(&conn, None).await.unwrap();
+    /// let date = |x: &str| OptionalTimestamp(Some(Timestamp(x.parse().unwrap())));
+    /// let tolkien = Author {
+    ///     id: tomex::types::uuid::Uuid(uuid::Uuid::new_v4()),
+    ///     name: Some(Text("J. R. R. Tolkien".into())),
+    ///     date_born: date("1892-01-03T00:00:00Z"),
+    ///     date_died: date("1973-09-02T00:00:00Z"),
+    ///     ..Author::default()
+    /// };
+    /// let clarke = Author {
+    ///     id: tomex::types::uuid::Uuid(uuid::Uuid::new_v4()),
+    ///     name: Some(Text("Susanna Clarke".into())),
+    ///     date_born: date("1959-11-01T00:00:00Z"),
+    ///     ..Author::default()
+    /// };
+    /// tolkien.insert(&conn).await.unwrap();
+    /// clarke.insert(&conn).await.unwrap();
+    /// let filter = AuthorFilter {
+    ///     born_after: Some(1900),
+    ///     ..AuthorFilter::default()
+    /// };
+    /// assert_eq!(Author::get_filtered(&conn, &filter, false).await.unwrap(), [clarke.clone()]);
+    /// let filter = AuthorFilter {
+    ///     alive: true,
+    ///     ..AuthorFilter::default()
+    /// };
+    /// assert_eq!(Author::get_filtered(&conn, &filter, false).await.unwrap(), [clarke]);
+    /// let filter = AuthorFilter {
+    ///     died_before: Some(2000),
+    ///     ..AuthorFilter::default()
+    /// };
+    /// assert_eq!(Author::get_filtered(&conn, &filter, false).await.unwrap(), [tolkien]);
+    /// # }
+    /// ```
+    pub async fn get_filtered(
         conn: &sqlx::SqlitePool,
-    ) -> Result<Self> {
-        let id = Uuid(uuid::Uuid::new_v4());
-        let name =
-            Text::create_by_prompt_skippable("What is the authors name?", None, conn).await?;
-        Ok(Self {
-            id,
-            name,
-            date_born: OptionalTimestamp(None),
-            date_died: OptionalTimestamp(None),
-            deleted: false,
-            special: false,
-        })
+        filter: &AuthorFilter,
+        include_deleted: bool,
+    ) -> Result<Vec<Self>> {
+        let known = |column| format!("{column} IS NOT NULL AND {column} != ?1");
+        let mut conditions = vec!["id != ?2".to_string()];
+        if !include_deleted {
+            conditions.push("deleted = 0".to_string());
+        }
+        let mut bounds = vec![];
+        let limits = [
+            ("date_born", ">=", filter.born_after.map(|x| x + 1)),
+            ("date_born", "<", filter.born_before),
+            ("date_died", ">=", filter.died_after.map(|x| x + 1)),
+            ("date_died", "<", filter.died_before),
+        ];
+        for (column, operator, year) in limits {
+            if let Some(year) = year {
+                bounds.push(year_start(year)?);
+                conditions.push(format!(
+                    "{} AND {column} {operator} ?{}",
+                    known(column),
+                    bounds.len() + 2
+                ));
+            }
+        }
+        if filter.alive {
+            conditions.push(format!("NOT ({})", known("date_died")));
+        }
+        let query = format!(
+            "SELECT * FROM {} WHERE {};",
+            Self::TABLE_NAME,
+            conditions.join(" AND ")
+        );
+        let mut query = sqlx::query_as::<_, Self>(&query)
+            .bind(NONE_SENTINEL)
+            .bind(&UUID_UNKOWN);
+        for bound in bounds {
+            query = query.bind(bound);
+        }
+        Ok(query.fetch_all(conn).await?)
     }
 
-    async fn update_by_prompt(&self, _prompt: &str, conn: &sqlx::SqlitePool) -> anyhow::Result<Self>
-    where
-        Self: Display,
-    {
-        if self.special {
-            anyhow::bail!("Can't update special author");
+    pub async fn get_by_openlibrary_key(
+        conn: &sqlx::SqlitePool,
+        key: &str,
+    ) -> Result<Option<Self>> {
+        Ok(sqlx::query_as::<_, Self>(&format!(
+            "SELECT * FROM {} WHERE openlibrary_key = ?1 AND deleted = 0;",
+            Self::TABLE_NAME
+        ))
+        .bind(key)
+        .fetch_optional(conn)
+        .await?)
+    }
+
+    /// Link to the author's page on OpenLibrary
+    ///
+    /// ```
+    /// use tomex::types::{author::Author, text::Text};
+    ///
+    /// let author = Author {
+    ///     openlibrary_key: Some(Text("OL23919A".into())),
+    ///     ..Default::default()
+    /// };
+    /// assert_eq!(
+    ///     author.openlibrary_url().as_deref(),
+    ///     Some("https://openlibrary.org/authors/OL23919A")
+    /// );
+    /// ```
+    pub fn openlibrary_url(&self) -> Option<String> {
+        self.openlibrary_key
+            .as_ref()
+            .map(|x| format!("https://openlibrary.org/authors/{}", x.0))
+    }
+
+    /// Fail for special authors like the unknown author, unless `allow_special`
+    fn check_special(&self, allow_special: bool, action: &str) -> Result<()> {
+        if self.special && !allow_special {
+            anyhow::bail!("Can't {action} special author");
         }
+        Ok(())
+    }
+
+    /// Prompt for an author and update it, `allow_special` lets special authors
+    /// be edited to fix a broken seed
+    pub async fn update_by_prompt_allow_special(
+        conn: &sqlx::SqlitePool,
+        allow_special: bool,
+    ) -> Result<SqliteQueryResult> {
+        let s = Self::query_by_prompt(conn).await?;
+        s.check_special(allow_special, "update")?;
+        let new = s.prompt_update(conn).await?;
+        s.update_unchecked(conn, new).await
+    }
+
+    /// Prompt for an author and remove it, `allow_special` lets special authors
+    /// be removed to fix a broken seed
+    pub async fn remove_by_prompt_allow_special(
+        conn: &sqlx::SqlitePool,
+        allow_special: bool,
+    ) -> Result<()> {
+        let x = Self::query_by_prompt_skippable(conn).await?;
+        if let Some(x) = x.as_ref() {
+            x.check_special(allow_special, "remove")?;
+        }
+        match x {
+            Some(x) => {
+                if !prompt_or_abort(|| {
+                    inquire::Confirm::new(&format!("Are you sure you want to remove {x}?"))
+                        .with_default(false)
+                        .prompt()
+                })? {
+                    anyhow::bail!(crate::error::Error::Aborted("Aborted".into()));
+                };
+                Self::remove(&x, conn).await?;
+                println!("Deleted");
+            }
+            None => println!("Nothing selected, doing nothing"),
+        }
+        Ok(())
+    }
+
+    async fn prompt_update(&self, conn: &sqlx::SqlitePool) -> Result<Self> {
         let name =
             PromptType::update_by_prompt_skippable(&self.name, "What is the authors name?", conn)
                 .await?;
@@ -110,22 +316,67 @@ impl PromptType for Author {
             conn,
         )
         .await?;
+        let openlibrary_key = PromptType::update_by_prompt_skippable(
+            &self.openlibrary_key,
+            "What is the authors OpenLibrary id?",
+            conn,
+        )
+        .await?;
+        let website = PromptType::update_by_prompt_skippable(
+            &self.website,
+            "What is the authors website?",
+            conn,
+        )
+        .await?;
 
-        if !inquire::Confirm::new("Update author?")
-            .with_default(true)
-            .prompt()?
-        {
-            anyhow::bail!("Aborted");
+        if !prompt_or_abort(|| {
+            inquire::Confirm::new("Update author?")
+                .with_default(true)
+                .prompt()
+        })? {
+            anyhow::bail!(crate::error::Error::Aborted("Aborted".into()));
         };
 
         let new = Self {
             name,
             date_born: OptionalTimestamp(date_born),
             date_died: OptionalTimestamp(date_died),
+            openlibrary_key,
+            website,
             ..self.clone()
         };
         Ok(new)
     }
+}
+
+impl PromptType for Author {
+    async fn create_by_prompt(
+        _prompt: &str,
+        _initial_value: Option<&Self>,
+        conn: &sqlx::SqlitePool,
+    ) -> Result<Self> {
+        let id = Uuid(uuid::Uuid::new_v4());
+        let name =
+            Text::create_by_prompt_skippable("What is the authors name?", None, conn).await?;
+        Ok(Self {
+            id,
+            name,
+            date_born: OptionalTimestamp(None),
+            date_died: OptionalTimestamp(None),
+            openlibrary_key: None,
+            website: None,
+            deleted: false,
+            special: false,
+        })
+    }
+
+    async fn update_by_prompt(&self, _prompt: &str, conn: &sqlx::SqlitePool) -> anyhow::Result<Self>
+    where
+        Self: Display,
+    {
+        self.check_special(false, "update")?;
+        self.prompt_update(conn).await
+    }
 
     async fn create_by_prompt_skippable(
         _prompt: &str,
@@ -149,10 +400,7 @@ impl PromptType for Author {
 
 impl Display for Author {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let config = match config::Config::read_config() {
-            Ok(config) => config,
-            Err(_) => return Err(std::fmt::Error),
-        };
+        let config = config::Config::cached();
         if self.special {
             match self.id {
                 UUID_UNKOWN => write!(f, "{}", "UNKOWN AUTHOR".bold()),
@@ -194,51 +442,87 @@ impl DisplayTerminal for Author {
         }
         Ok(())
     }
+
+    async fn info_card(
+        &self,
+        f: &mut String,
+        conn: &sqlx::SqlitePool,
+        config: &config::Config,
+    ) -> Result<()> {
+        DisplayTerminal::fmt(self, f, conn, config).await?;
+        if let Some(url) = self.openlibrary_url() {
+            write!(f, "\nOpenLibrary: {}", url.underlined())?;
+        }
+        if let Some(website) = &self.website {
+            write!(f, "\nWebsite: {}", website.0.clone().underlined())?;
+        }
+        Ok(())
+    }
 }
 
 impl CreateTable for Author {
-    async fn create_table(conn: &sqlx::SqlitePool) -> Result<()> {
-        sqlx::query(&format!(
+    fn ddl() -> String {
+        format!(
             r#"
             CREATE TABLE IF NOT EXISTS {} (
                 id TEXT PRIMARY KEY NOT NULL,
                 name TEXT,
                 date_born INTEGER,
                 date_died INTEGER,
+                openlibrary_key TEXT,
+                website TEXT,
                 deleted BOOL DEFAULT FALSE
             );"#,
             Self::TABLE_NAME
-        ))
-        .execute(conn)
-        .await?;
+        )
+    }
+
+    async fn create_table(
+        conn: &sqlx::SqlitePool,
+        seed: Option<&config::SeedConfig>,
+    ) -> Result<()> {
+        sqlx::query(&Self::ddl()).execute(conn).await?;
+        if seed.is_none() {
+            return Ok(());
+        }
         Self::insert(
             &Self {
-                id: UUID_UNKOWN,
-                name: None,
-                date_born: OptionalTimestamp(None),
-                date_died: OptionalTimestamp(None),
-                deleted: false,
-                special: true,
+                id:              UUID_UNKOWN,
+                name:            None,
+                date_born:       OptionalTimestamp(None),
+                date_died:       OptionalTimestamp(None),
+                openlibrary_key: None,
+                website:         None,
+                deleted:         false,
+                special:         true,
             },
             conn,
         )
         .await?;
         Ok(())
     }
+
+    async fn migrate_table(conn: &sqlx::SqlitePool) -> Result<()> {
+        Self::add_column(conn, "openlibrary_key", "TEXT").await?;
+        Self::add_column(conn, "website", "TEXT").await
+    }
 }
 
 impl Insertable for Author {
-    async fn insert(&self, conn: &sqlx::SqlitePool) -> Result<SqliteQueryResult> {
+    async fn insert_with(&self, conn: &mut sqlx::SqliteConnection) -> Result<SqliteQueryResult> {
         Ok(sqlx::query(
             r#"
-            INSERT INTO authors ( id, name, date_born, date_died, deleted )
-            VALUES ( ?1, ?2, ?3, ?4, ?5 )
+            INSERT INTO authors
+                ( id, name, date_born, date_died, openlibrary_key, website, deleted )
+            VALUES ( ?1, ?2, ?3, ?4, ?5, ?6, ?7 )
             "#,
         )
         .bind(&self.id)
         .bind(&self.name)
         .bind(&self.date_born)
         .bind(&self.date_died)
+        .bind(&self.openlibrary_key)
+        .bind(&self.website)
         .bind(self.deleted)
         .execute(conn)
         .await?)
@@ -246,9 +530,18 @@ impl Insertable for Author {
 }
 impl Updateable for Author {
     async fn update(&mut self, conn: &sqlx::SqlitePool, new: Self) -> Result<SqliteQueryResult> {
-        if self.special {
-            anyhow::bail!("Can't update special author");
-        }
+        self.check_special(false, "update")?;
+        self.update_unchecked(conn, new).await
+    }
+}
+
+impl Author {
+    /// Update without refusing special authors
+    async fn update_unchecked(
+        &self,
+        conn: &sqlx::SqlitePool,
+        new: Self,
+    ) -> Result<SqliteQueryResult> {
         Ok(sqlx::query(&format!(
             r#"
             UPDATE {}
@@ -256,7 +549,9 @@ impl Updateable for Author {
                 name = ?2,
                 date_born = ?3,
                 date_died = ?4,
-                deleted = ?5
+                openlibrary_key = ?5,
+                website = ?6,
+                deleted = ?7
             WHERE
                 id = ?1;
             "#,
@@ -266,6 +561,8 @@ impl Updateable for Author {
         .bind(&new.name)
         .bind(&new.date_born)
         .bind(&new.date_died)
+        .bind(&new.openlibrary_key)
+        .bind(&new.website)
         .bind(new.deleted)
         .execute(conn)
         .await?)
@@ -277,38 +574,21 @@ impl Removeable for Author {
     where
         Self: Queryable,
     {
-        let x = Self::query_by_prompt_skippable(conn).await?;
-        if let Some(x) = x.as_ref() {
-            if x.special {
-                anyhow::bail!("Can't remove special author manually");
-            }
-        }
-        match x {
-            Some(x) => {
-                if !inquire::Confirm::new(&format!("Are you sure you want to remove {x}?"))
-                    .with_default(false)
-                    .prompt()?
-                {
-                    anyhow::bail!("Aborted");
-                };
-                Self::remove(&x, conn).await?;
-                println!("Deleted");
-            }
-            None => println!("Nothing selected, doing nothing"),
-        }
-        Ok(())
+        Self::remove_by_prompt_allow_special(conn, false).await
     }
 }
 
 impl FromRow<'_, SqliteRow> for Author {
     fn from_row(row: &SqliteRow) -> sqlx::Result<Self> {
         let s = Self {
-            id: row.try_get("id")?,
-            deleted: row.try_get("deleted")?,
-            name: row.try_get("name")?,
-            date_born: row.try_get("date_born")?,
-            date_died: row.try_get("date_died")?,
-            special: false,
+            id:              row.try_get("id")?,
+            deleted:         row.try_get("deleted")?,
+            name:            row.try_get("name")?,
+            date_born:       row.try_get("date_born")?,
+            date_died:       row.try_get("date_died")?,
+            openlibrary_key: row.try_get("openlibrary_key")?,
+            website:         row.try_get("website")?,
+            special:         false,
         };
         if s.id == UUID_UNKOWN {
             return Ok(Self {
@@ -6,7 +6,7 @@ use std::fmt::{Display, Write};
 use crate::{
     config::{self, Styleable},
     traits::*,
-    types::{text::Text, uuid::Uuid},
+    types::{edition::Edition, text::Text, uuid::Uuid},
 };
 use derives::*;
 
@@ -84,10 +84,7 @@ impl PromptType for Binding {
 
 impl Display for Binding {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let config = match config::Config::read_config() {
-            Ok(config) => config,
-            Err(_) => return Err(std::fmt::Error),
-        };
+        let config = config::Config::cached();
         let name = self
             .name
             .to_string()
@@ -119,22 +116,9 @@ impl DisplayTerminal for Binding {
     }
 }
 
-impl CreateTable for Binding {
-    async fn create_table(conn: &sqlx::SqlitePool) -> Result<()> {
-        sqlx::query(&format!(
-            r#"
-            CREATE TABLE IF NOT EXISTS {} (
-                id TEXT PRIMARY KEY NOT NULL,
-                name TEXT NOT NULL,
-                deleted BOOL DEFAULT FALSE
-            );
-            "#,
-            Self::TABLE_NAME
-        ))
-        .execute(conn)
-        .await?;
-
-        let default_bindings = vec![
+impl Referenced for Binding {
+    fn defaults() -> Vec<(&'static str, uuid::Uuid)> {
+        vec![
             (
                 "Perfect binding",
                 uuid::uuid!("11a8d073-879f-4970-871c-d1618a776784"),
@@ -175,8 +159,43 @@ impl CreateTable for Binding {
                 "Hand-stitched binding",
                 uuid::uuid!("ec5ba23c-4c1b-4950-b2d5-fad8ef85d855"),
             ),
-        ];
-        for (binding, uuid) in default_bindings {
+        ]
+    }
+
+    fn usage_query() -> String {
+        format!(
+            "SELECT binding_id, COUNT(*) FROM {} WHERE deleted = 0 AND binding_id IS NOT NULL \
+             GROUP BY binding_id;",
+            Edition::TABLE_NAME,
+        )
+    }
+}
+
+impl CreateTable for Binding {
+    fn ddl() -> String {
+        format!(
+            r#"
+            CREATE TABLE IF NOT EXISTS {} (
+                id TEXT PRIMARY KEY NOT NULL,
+                name TEXT NOT NULL,
+                deleted BOOL DEFAULT FALSE
+            );
+            "#,
+            Self::TABLE_NAME
+        )
+    }
+
+    async fn create_table(
+        conn: &sqlx::SqlitePool,
+        seed: Option<&config::SeedConfig>,
+    ) -> Result<()> {
+        sqlx::query(&Self::ddl()).execute(conn).await?;
+        let Some(seed) = seed else {
+            return Ok(());
+        };
+
+        for (binding, uuid) in config::SeedConfig::resolve(seed.bindings.clone(), Self::defaults())
+        {
             Self::insert(
                 &Self {
                     id:      Uuid(uuid),
@@ -192,13 +211,10 @@ impl CreateTable for Binding {
 }
 
 impl Insertable for Binding {
-    async fn insert(
+    async fn insert_with(
         &self,
-        conn: &sqlx::SqlitePool,
-    ) -> anyhow::Result<sqlx::sqlite::SqliteQueryResult>
-    where
-        Self: Sized,
-    {
+        conn: &mut sqlx::SqliteConnection,
+    ) -> anyhow::Result<sqlx::sqlite::SqliteQueryResult> {
         Ok(sqlx::query(&format!(
             r#"
                     INSERT INTO {} ( id, name, deleted )
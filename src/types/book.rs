@@ -1,20 +1,33 @@
 use anyhow::Result;
-use inquire::MultiSelect;
+use inquire::{MultiSelect, Select, validator::Validation};
 use serde::{Deserialize, Serialize};
-use sqlx::{sqlite::SqliteRow, FromRow, Row};
+use sqlx::{FromRow, Row, sqlite::SqliteRow};
+use std::collections::HashMap;
 use std::fmt::{Display, Write};
 
 use crate::{
     config::{self, Styleable},
     traits::*,
     types::{
-        author::Author, edition::Edition, genre::Genre, review::Review, text::Text,
-        timestamp::OptionalTimestamp, uuid::Uuid,
+        author::Author,
+        content_warning::ContentWarning,
+        edition::Edition,
+        genre::Genre,
+        option_to_create::OptionToCreate,
+        progress::{PagesProgress, Progress},
+        review::Review,
+        shelf::Shelf,
+        text::Text,
+        timestamp::OptionalTimestamp,
+        uuid::Uuid,
     },
 };
 use derives::*;
 
-use super::{book_author::BookAuthor, book_genre::BookGenre, rating::Rating, series::Series};
+use super::{
+    book_author::BookAuthor, book_content_warning::BookContentWarning, book_genre::BookGenre,
+    book_shelf::BookShelf, rating::Rating, series::Series,
+};
 
 #[derive(
     Default,
@@ -30,33 +43,258 @@ use super::{book_author::BookAuthor, book_genre::BookGenre, rating::Rating, seri
     Deserialize,
 )]
 pub struct Book {
-    pub id:           Uuid,
-    pub title:        Text,
-    pub authors:      Option<Vec<Author>>,
-    pub release_date: OptionalTimestamp,
-    pub summary:      Option<Text>,
-    pub series_id:    Option<Uuid>,
-    pub series_index: Option<u32>,
-    pub series:       Option<Series>,
-    pub editions:     Option<Vec<Edition>>,
-    pub reviews:      Option<Vec<Review>>,
-    pub genres:       Option<Vec<Genre>>,
-    pub deleted:      bool,
+    pub id:               Uuid,
+    pub title:            Text,
+    pub authors:          Option<Vec<Author>>,
+    pub release_date:     OptionalTimestamp,
+    pub summary:          Option<Text>,
+    pub series_id:        Option<Uuid>,
+    pub series_index:     Option<u32>,
+    pub series:           Option<Series>,
+    pub editions:         Option<Vec<Edition>>,
+    pub reviews:          Option<Vec<Review>>,
+    pub genres:           Option<Vec<Genre>>,
+    pub content_warnings: Option<Vec<ContentWarning>>,
+    pub shelves:          Option<Vec<Shelf>>,
+    /// Position in the to-read pile, lower numbers are read first
+    pub tbr_priority:     Option<u32>,
+    pub deleted:          bool,
 }
 
 impl Queryable for Book {
+    const GROUP_KEYS: &'static [&'static str] = &["series", "genre", "shelf", "author", "decade"];
+    const SEARCH_CONDITION: Option<&'static str> = Some("title LIKE ?1");
+    const TABLE_HEADERS: &'static [&'static str] = &["Title", "Author(s)", "Genres", "Rating"];
+
     async fn sort_for_display(x: Vec<Self>) -> Vec<Self> {
         let mut x = x.clone();
         x.sort_by(|a, b| a.title.0.partial_cmp(&b.title.0).unwrap());
         return x;
     }
+
+    async fn hydrate_for_export(&mut self, conn: &sqlx::SqlitePool) -> Result<()> {
+        self.hydrate(conn).await
+    }
+
+    async fn table_row(
+        &self,
+        conn: &sqlx::SqlitePool,
+        config: &config::Config,
+    ) -> Result<Vec<String>> {
+        let join = |names: Vec<String>, style: &config::StyleConfig| {
+            names
+                .iter()
+                .map(|x| x.style(style).to_string())
+                .collect::<Vec<String>>()
+                .join(", ")
+        };
+        let authors = self
+            .get_authors(conn)
+            .await?
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|x| x.name.map(|x| x.0))
+            .collect();
+        let genres = self
+            .get_genres(conn)
+            .await?
+            .unwrap_or_default()
+            .into_iter()
+            .map(|x| x.name.0)
+            .collect();
+        let rating = Review::get_all_for_book(conn, &self.id)
+            .await?
+            .into_iter()
+            .max_by(|a, b| {
+                a.timestamp_updated
+                    .partial_cmp(&b.timestamp_updated)
+                    .unwrap()
+            })
+            .and_then(|x| x.rating);
+        Ok(vec![
+            self.title
+                .style(&config.output_book.style_content)
+                .to_string(),
+            join(authors, &config.output_author.style_content),
+            join(genres, &config.output_genre.style_content),
+            rating
+                .map(|x| {
+                    x.to_string()
+                        .style(&config.output_rating.style_content)
+                        .to_string()
+                })
+                .unwrap_or_default(),
+        ])
+    }
+
+    async fn group_names(&self, conn: &sqlx::SqlitePool, key: &str) -> Result<Vec<String>> {
+        Ok(match key {
+            "series" => self
+                .get_series(conn)
+                .await?
+                .map(|x| x.name.0)
+                .into_iter()
+                .collect(),
+            "genre" => self
+                .get_genres(conn)
+                .await?
+                .unwrap_or_default()
+                .into_iter()
+                .map(|x| x.name.0)
+                .collect(),
+            "shelf" => self
+                .get_shelves(conn)
+                .await?
+                .unwrap_or_default()
+                .into_iter()
+                .map(|x| x.name.0)
+                .collect(),
+            "author" => self
+                .get_authors(conn)
+                .await?
+                .unwrap_or_default()
+                .into_iter()
+                .filter_map(|x| x.name.map(|x| x.0))
+                .collect(),
+            "decade" => self
+                .release_decade()
+                .map(|x| format!("{x}s"))
+                .into_iter()
+                .collect(),
+            _ => vec![],
+        })
+    }
+
+    async fn sort_by_clap(x: Vec<Self>, matches: &clap::ArgMatches) -> Vec<Self> {
+        let mut x = Self::sort_for_display(x).await;
+        if matches.contains_id("series") {
+            // Books without an index go last, the rest stays sorted by title
+            x.sort_by_key(|x| (x.series_index.is_none(), x.series_index));
+        }
+        if matches.get_flag("tbr") {
+            // Stable sort, so books with the same priority stay sorted by title
+            x.sort_by_key(|x| (x.tbr_priority.is_none(), x.tbr_priority));
+        }
+        x
+    }
+
+    async fn annotations_by_clap(
+        conn: &sqlx::SqlitePool,
+        matches: &clap::ArgMatches,
+        xs: &[Self],
+    ) -> Result<HashMap<Uuid, String>> {
+        if !matches.get_flag("editions-count") {
+            return Ok(HashMap::new());
+        }
+        let counts = Self::edition_counts(conn).await?;
+        Ok(xs
+            .iter()
+            .map(|x| {
+                let n = counts.get(&x.id).copied().unwrap_or(0);
+                (
+                    x.id.clone(),
+                    format!(" ({n} edition{})", if n == 1 { "" } else { "s" }),
+                )
+            })
+            .collect())
+    }
+
+    fn filtered_by_clap(matches: &clap::ArgMatches) -> bool {
+        ["tbr", "has-review", "no-review", "no-series"]
+            .iter()
+            .any(|x| matches.get_flag(x))
+            || ["series", "cw", "no-cw"]
+                .iter()
+                .any(|x| matches.contains_id(x))
+    }
+
+    async fn get_all_by_clap_filtered(
+        conn: &sqlx::SqlitePool,
+        matches: &clap::ArgMatches,
+        include_deleted: bool,
+    ) -> Result<Vec<Self>> {
+        let books = if matches.get_flag("tbr") {
+            Self::get_all_tbr(conn, include_deleted).await?
+        } else if matches.get_flag("has-review") {
+            Self::get_all_review_status(conn, true, include_deleted).await?
+        } else if matches.get_flag("no-review") {
+            Self::get_all_review_status(conn, false, include_deleted).await?
+        } else if include_deleted {
+            Self::get_all_including_deleted(conn).await?
+        } else {
+            Self::get_all(conn).await?
+        };
+        let series_id = match matches.get_one::<String>("series") {
+            Some(name) => Some(Series::get_by_name(conn, name).await?.id),
+            None => None,
+        };
+        let books = match (&series_id, matches.get_flag("no-series")) {
+            (Some(_), _) | (None, true) => books
+                .into_iter()
+                .filter(|x| x.series_id == series_id)
+                .collect(),
+            (None, false) => books,
+        };
+        let names = |id: &str| -> Vec<String> {
+            matches
+                .get_many::<String>(id)
+                .map(|x| x.map(|x| x.to_lowercase()).collect())
+                .unwrap_or_default()
+        };
+        let (with, without) = (names("cw"), names("no-cw"));
+        if with.is_empty() && without.is_empty() {
+            return Ok(books);
+        }
+        let mut result = Vec::new();
+        for book in books {
+            let warnings: Vec<String> = book
+                .get_content_warnings(conn)
+                .await?
+                .unwrap_or_default()
+                .into_iter()
+                .map(|x| x.name.0.to_lowercase())
+                .collect();
+            if with.iter().all(|x| warnings.contains(x))
+                && !without.iter().any(|x| warnings.contains(x))
+            {
+                result.push(book);
+            }
+        }
+        Ok(result)
+    }
 }
 
 impl Book {
+    /// Ask for a position in the to-read pile, starting from `current`
+    fn tbr_priority_by_prompt(current: Option<u32>) -> Result<Option<u32>> {
+        let mut prompt =
+            inquire::CustomType::<u32>::new("What is the books priority on the to-read pile?")
+                .with_help_message("1 is read first, Esc leaves it without a priority")
+                .with_error_message("Priority has to be a whole number")
+                .with_validator(|x: &u32| match *x {
+                    0 => Ok(Validation::Invalid("Priority has to be 1 or higher".into())),
+                    _ => Ok(Validation::Valid),
+                });
+        if let Some(current) = current {
+            prompt = prompt.with_default(current);
+        }
+        Ok(prompt.prompt_skippable()?)
+    }
+
     pub async fn hydrate(&mut self, conn: &sqlx::SqlitePool) -> Result<()> {
-        self.hydrate_authors(conn).await?;
-        self.hydrate_genres(conn).await?;
-        self.hydrate_series(conn).await?;
+        (
+            self.authors,
+            self.genres,
+            self.content_warnings,
+            self.shelves,
+            self.series,
+        ) = tokio::try_join!(
+            self.get_authors(conn),
+            self.get_genres(conn),
+            self.get_content_warnings(conn),
+            self.get_shelves(conn),
+            self.get_series(conn),
+        )?;
         Ok(())
     }
 
@@ -78,6 +316,27 @@ impl Book {
         })
     }
 
+    pub async fn get_content_warnings(
+        &self,
+        conn: &sqlx::SqlitePool,
+    ) -> Result<Option<Vec<ContentWarning>>> {
+        let result = BookContentWarning::get_all_for_a(conn, self).await?;
+        Ok(if !result.is_empty() {
+            Some(result)
+        } else {
+            None
+        })
+    }
+
+    pub async fn get_shelves(&self, conn: &sqlx::SqlitePool) -> Result<Option<Vec<Shelf>>> {
+        let result = BookShelf::get_all_for_a(conn, self).await?;
+        Ok(if !result.is_empty() {
+            Some(result)
+        } else {
+            None
+        })
+    }
+
     pub async fn get_series(&self, conn: &sqlx::SqlitePool) -> Result<Option<Series>> {
         if let Some(id) = &self.series_id {
             Ok(Some(Series::get_by_id(conn, id).await?))
@@ -96,11 +355,228 @@ impl Book {
         Ok(())
     }
 
+    pub async fn hydrate_content_warnings(&mut self, conn: &sqlx::SqlitePool) -> Result<()> {
+        self.content_warnings = self.get_content_warnings(conn).await?;
+        Ok(())
+    }
+
+    pub async fn hydrate_shelves(&mut self, conn: &sqlx::SqlitePool) -> Result<()> {
+        self.shelves = self.get_shelves(conn).await?;
+        Ok(())
+    }
+
     pub async fn hydrate_series(&mut self, conn: &sqlx::SqlitePool) -> Result<()> {
         self.series = self.get_series(conn).await?;
         Ok(())
     }
 
+    /// Get all books which have (or don't have) a review, with
+    /// `include_deleted` also removed ones
+    pub async fn get_all_review_status(
+        conn: &sqlx::SqlitePool,
+        has_review: bool,
+        include_deleted: bool,
+    ) -> Result<Vec<Self>> {
+        Ok(sqlx::query_as::<_, Self>(&format!(
+            r#"
+            SELECT * FROM {} b
+                WHERE (b.deleted = 0 OR ?1)
+                    AND {} EXISTS (
+                        SELECT 1 FROM {} r WHERE r.book_id = b.id AND r.deleted = 0
+                    );
+            "#,
+            Self::TABLE_NAME,
+            if has_review { "" } else { "NOT" },
+            Review::TABLE_NAME,
+        ))
+        .bind(include_deleted)
+        .fetch_all(conn)
+        .await?)
+    }
+
+    /// Get all books without an edition that was finished, i.e. the to-read
+    /// pile, with `include_deleted` also removed ones
+    pub async fn get_all_tbr(conn: &sqlx::SqlitePool, include_deleted: bool) -> Result<Vec<Self>> {
+        Ok(sqlx::query_as::<_, Self>(&format!(
+            r#"
+            SELECT * FROM {} b
+                WHERE (b.deleted = 0 OR ?2)
+                    AND NOT EXISTS (
+                        SELECT 1 FROM {} e JOIN {} p ON p.edition_id = e.id
+                            WHERE e.book_id = b.id
+                                AND e.deleted = 0
+                                AND p.deleted = 0
+                                AND p.pages_progress = ?1
+                    );
+            "#,
+            Self::TABLE_NAME,
+            Edition::TABLE_NAME,
+            Progress::TABLE_NAME,
+        ))
+        .bind(PagesProgress::Finished)
+        .bind(include_deleted)
+        .fetch_all(conn)
+        .await?)
+    }
+
+    /// The first year of the decade this book was released in
+    ///
+    /// ```
+    /// use tomex::types::{book::Book, timestamp::{OptionalTimestamp, Timestamp}};
+    ///
+    /// let release_date = Timestamp("1965-08-01T00:00:00Z".parse().unwrap());
+    /// let book = Book {
+    ///     release_date: OptionalTimestamp(Some(release_date)),
+    ///     ..Default::default()
+    /// };
+    /// assert_eq!(book.release_decade(), Some(1960));
+    /// assert_eq!(Book::default().release_decade(), None);
+    /// ```
+    pub fn release_decade(&self) -> Option<i32> {
+        use chrono::Datelike;
+        self.release_date
+            .0
+            .as_ref()
+            .map(|x| x.0.year().div_euclid(10) * 10)
+    }
+
+    /// The page count most of this book's editions have, ties going to the
+    /// longer one, None if no edition has one
+    pub async fn representative_pages(&self, conn: &sqlx::SqlitePool) -> Result<Option<u32>> {
+        let pages: Option<(u32,)> = sqlx::query_as(&format!(
+            r#"
+            SELECT pages FROM {}
+            WHERE book_id = ?1 AND deleted = 0 AND pages IS NOT NULL
+            GROUP BY pages
+            ORDER BY COUNT(*) DESC, pages DESC
+            LIMIT 1;
+            "#,
+            Edition::TABLE_NAME
+        ))
+        .bind(&self.id)
+        .fetch_optional(conn)
+        .await?;
+        Ok(pages.map(|(x,)| x))
+    }
+
+    /// Number of (non-deleted) editions per book, books without editions are
+    /// missing
+    pub async fn edition_counts(conn: &sqlx::SqlitePool) -> Result<HashMap<Uuid, i64>> {
+        let counts: Vec<(Uuid, i64)> = sqlx::query_as(&format!(
+            "SELECT book_id, COUNT(*) FROM {} WHERE deleted = 0 GROUP BY book_id;",
+            Edition::TABLE_NAME
+        ))
+        .fetch_all(conn)
+        .await?;
+        Ok(counts.into_iter().collect())
+    }
+
+    /// The (non-deleted) books in the series with `series_id` by their index,
+    /// or the books that aren't in any series by title for [None]
+    pub async fn get_all_by_series(
+        conn: &sqlx::SqlitePool,
+        series_id: Option<&Uuid>,
+    ) -> Result<Vec<Self>> {
+        let condition = match series_id {
+            Some(_) => "series_id = ?1 ORDER BY series_index",
+            None => "series_id IS NULL ORDER BY title",
+        };
+        Ok(sqlx::query_as::<_, Self>(&format!(
+            "SELECT * FROM {} WHERE deleted = 0 AND {condition};",
+            Self::TABLE_NAME
+        ))
+        .bind(series_id)
+        .fetch_all(conn)
+        .await?)
+    }
+
+    /// Get all books with this title, ignoring case
+    pub async fn get_all_by_title(conn: &sqlx::SqlitePool, title: &str) -> Result<Vec<Self>> {
+        Ok(sqlx::query_as::<_, Self>(&format!(
+            "SELECT * FROM {} WHERE title = ?1 COLLATE NOCASE AND deleted = 0;",
+            Self::TABLE_NAME
+        ))
+        .bind(title)
+        .fetch_all(conn)
+        .await?)
+    }
+
+    /// Number of edits between two titles, ignoring case and differences in
+    /// whitespace
+    ///
+    /// ```
+    /// use tomex::types::book::Book;
+    ///
+    /// assert_eq!(Book::title_distance("The Hobbit", "the  hobbit "), 0);
+    /// assert_eq!(Book::title_distance("The Hobbit", "The Hobit"), 1);
+    /// ```
+    pub fn title_distance(a: &str, b: &str) -> usize {
+        let normalize = |x: &str| Text(x.to_string()).normalized(true).0.to_lowercase();
+        strsim::levenshtein(&normalize(a), &normalize(b))
+    }
+
+    /// Get all books with a title at most `max_distance` edits away from
+    /// `title`, closest first
+    pub async fn get_all_similar(
+        conn: &sqlx::SqlitePool,
+        title: &str,
+        max_distance: usize,
+    ) -> Result<Vec<Self>> {
+        let mut similar: Vec<(usize, Self)> = Self::get_all(conn)
+            .await?
+            .into_iter()
+            .map(|x| (Self::title_distance(&x.title.0, title), x))
+            .filter(|(distance, _)| *distance <= max_distance)
+            .collect();
+        similar.sort_by_key(|(distance, _)| *distance);
+        Ok(similar.into_iter().map(|(_, x)| x).collect())
+    }
+
+    /// Warn about existing books with a title close to `title`, returning the
+    /// one picked instead of adding a new book
+    async fn pick_similar_by_prompt(conn: &sqlx::SqlitePool, title: &Text) -> Result<Option<Self>> {
+        let config = config::Config::cached();
+        let similar =
+            Self::get_all_similar(conn, &title.0, config.duplicate_title_distance).await?;
+        if similar.is_empty() {
+            return Ok(None);
+        }
+        println!("Found books with a similar title:");
+        for x in &similar {
+            println!(
+                "{}",
+                DisplayTerminal::fmt_to_string(x, conn, Some(" • "), config).await?
+            );
+        }
+        let options = OptionToCreate::create_option_to_create(similar);
+        let prompt = || {
+            Select::new("Use an existing book instead?", options.clone())
+                .with_starting_cursor(1)
+                .prompt()
+        };
+        match prompt_or_abort(prompt)? {
+            OptionToCreate::Value(x) => Ok(Some(x)),
+            OptionToCreate::Create => Ok(None),
+        }
+    }
+
+    /// The book with this title, asking which one if there are several
+    pub async fn get_by_title_by_prompt(conn: &sqlx::SqlitePool, title: &str) -> Result<Self> {
+        let mut books = Self::get_all_by_title(conn, title).await?;
+        match books.len() {
+            0 => {
+                let message = format!("No book titled {title}");
+                Err(crate::error::Error::NotFound(message).into())
+            }
+            1 => Ok(books.remove(0)),
+            _ => {
+                let message = format!("Which {title}?");
+                let prompt = Select::new(&message, books);
+                prompt_or_abort(|| prompt.clone().prompt())
+            }
+        }
+    }
+
     pub async fn get_by_title(conn: &sqlx::SqlitePool, title: String) -> Result<Option<Self>> {
         Ok(sqlx::query_as::<_, Self>(&format!(
             "SELECT * FROM {} WHERE title = ?1 COLLATE NOCASE AND deleted = 0;",
@@ -115,11 +591,14 @@ impl Book {
 impl PromptType for Book {
     async fn create_by_prompt(
         _prompt: &str,
-        _initial_value: Option<&Self>,
+        initial_value: Option<&Self>,
         conn: &sqlx::SqlitePool,
     ) -> Result<Self> {
         let id = Uuid(uuid::Uuid::new_v4());
-        let title = Text::create_by_prompt("What is the title of the book?", None, conn).await?;
+        let title = match initial_value {
+            Some(x) if !x.title.0.is_empty() => x.title.clone(),
+            _ => Text::create_by_prompt("What is the title of the book?", None, conn).await?,
+        };
         let author = Author::query_or_create_by_prompt_skippable(conn).await?;
         let all_genres = Genre::get_all(conn).await?;
         let mut genres =
@@ -131,6 +610,20 @@ impl PromptType for Book {
                 None
             };
         }
+        let all_content_warnings = ContentWarning::get_all(conn).await?;
+        let mut content_warnings = MultiSelect::new(
+            "Select content warnings for this book:",
+            all_content_warnings,
+        )
+        .prompt_skippable()?;
+        if let Some(content_warnings_) = content_warnings {
+            content_warnings = if !content_warnings_.is_empty() {
+                Some(content_warnings_)
+            } else {
+                None
+            };
+        }
+        let shelves = Shelf::update_for_book(&None, conn).await?;
         let series = Series::query_or_create_by_prompt_skippable(conn).await?;
         let series_id = series.clone().map(|x| x.id);
         let series_index = match series_id {
@@ -144,6 +637,7 @@ impl PromptType for Book {
             }
             None => None,
         };
+        let tbr_priority = Self::tbr_priority_by_prompt(None)?;
 
         Ok(Self {
             id,
@@ -154,6 +648,9 @@ impl PromptType for Book {
             editions: None, // TODO
             reviews: None,  // TODO
             genres,
+            content_warnings,
+            shelves,
+            tbr_priority,
             deleted: false,
             series_id,
             series_index,
@@ -176,6 +673,13 @@ impl PromptType for Book {
         )
         .await?;
         let genres = Genre::update_vec(&self.genres, conn, "Select genres for this book:").await?;
+        let content_warnings = ContentWarning::update_vec(
+            &self.content_warnings,
+            conn,
+            "Select content warnings for this book:",
+        )
+        .await?;
+        let shelves = Shelf::update_for_book(&self.shelves, conn).await?;
         let series = match Series::query_or_create_by_prompt_skippable(conn).await? {
             Some(series) => Some(series),
             None => self.series.clone(),
@@ -192,6 +696,7 @@ impl PromptType for Book {
             }
             None => None,
         };
+        let tbr_priority = Self::tbr_priority_by_prompt(self.tbr_priority)?;
         let new = Self {
             id: self.id.clone(),
             title,
@@ -201,6 +706,9 @@ impl PromptType for Book {
             editions: self.editions.clone(),
             reviews: self.reviews.clone(),
             genres,
+            content_warnings,
+            shelves,
+            tbr_priority,
             deleted: self.deleted,
             series_id,
             series_index,
@@ -231,10 +739,7 @@ impl PromptType for Book {
 
 impl Display for Book {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let config = match config::Config::read_config() {
-            Ok(config) => config,
-            Err(_) => return Err(std::fmt::Error),
-        };
+        let config = config::Config::cached();
         let title = self
             .title
             .to_string()
@@ -305,6 +810,26 @@ impl DisplayTerminal for Book {
                 config.output_genre.format_vec(genres, conn, config).await?
             )?;
         }
+        if let Some(content_warnings) = s.content_warnings {
+            write!(
+                f,
+                "{} ",
+                config
+                    .output_content_warning
+                    .format_vec(content_warnings, conn, config)
+                    .await?
+            )?;
+        }
+        if let Some(shelves) = s.shelves {
+            write!(
+                f,
+                "{} ",
+                config
+                    .output_shelf
+                    .format_vec(shelves, conn, config)
+                    .await?
+            )?;
+        }
         if config.output_book.display_uuid {
             write!(f, "({})", s.id)?;
         }
@@ -313,8 +838,8 @@ impl DisplayTerminal for Book {
 }
 
 impl CreateTable for Book {
-    async fn create_table(conn: &sqlx::SqlitePool) -> Result<()> {
-        sqlx::query(&format!(
+    fn ddl() -> String {
+        format!(
             r#"
             CREATE TABLE IF NOT EXISTS {} (
                 id TEXT PRIMARY KEY NOT NULL,
@@ -323,28 +848,56 @@ impl CreateTable for Book {
                 summary TEXT,
                 series_id TEXT,
                 series_index INTEGER,
+                tbr_priority INTEGER,
                 deleted BOOL DEFAULT FALSE
             );"#,
             Self::TABLE_NAME
-        ))
-        .execute(conn)
-        .await?;
+        )
+    }
+
+    async fn create_table(
+        conn: &sqlx::SqlitePool,
+        _seed: Option<&config::SeedConfig>,
+    ) -> Result<()> {
+        sqlx::query(&Self::ddl()).execute(conn).await?;
         Ok(())
     }
+
+    async fn migrate_table(conn: &sqlx::SqlitePool) -> Result<()> {
+        Self::add_column(conn, "tbr_priority", "INTEGER").await
+    }
 }
 
 impl Insertable for Book {
-    async fn insert(
+    async fn insert_by_prompt(conn: &sqlx::SqlitePool) -> Result<Self> {
+        let title = Text::create_by_prompt("What is the title of the book?", None, conn).await?;
+        if let Some(existing) = Self::pick_similar_by_prompt(conn, &title).await? {
+            return Ok(existing);
+        }
+        let initial_value = Self {
+            title,
+            ..Self::default()
+        };
+        let x = Self::create_by_prompt("", Some(&initial_value), conn).await?;
+        if !prompt_or_abort(|| {
+            inquire::Confirm::new("Add to database?")
+                .with_default(true)
+                .prompt()
+        })? {
+            anyhow::bail!(crate::error::Error::Aborted("Aborted".into()));
+        };
+        x.insert(conn).await?;
+        Ok(x)
+    }
+
+    async fn insert_with(
         &self,
-        conn: &sqlx::SqlitePool,
-    ) -> anyhow::Result<sqlx::sqlite::SqliteQueryResult>
-    where
-        Self: Sized,
-    {
+        conn: &mut sqlx::SqliteConnection,
+    ) -> anyhow::Result<sqlx::sqlite::SqliteQueryResult> {
         let result = sqlx::query(
             r#"
-            INSERT INTO books ( id, title, release_date, series_id, series_index, deleted )
-            VALUES ( ?1, ?2, ?3, ?4, ?5, ?6 );
+            INSERT INTO books ( id, title, release_date, series_id, series_index, tbr_priority, deleted, summary )
+            VALUES ( ?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8 );
             "#,
         )
         .bind(&self.id)
@@ -352,18 +905,30 @@ impl Insertable for Book {
         .bind(&self.release_date)
         .bind(&self.series_id)
         .bind(&self.series_index)
+        .bind(self.tbr_priority)
         .bind(self.deleted)
-        .execute(conn)
+        .bind(&self.summary)
+        .execute(&mut *conn)
         .await?;
 
         if let Some(authors) = &self.authors {
             for author in authors {
-                BookAuthor::insert(conn, self, author).await?;
+                BookAuthor::insert(&mut *conn, self, author).await?;
             }
         }
         if let Some(genres) = &self.genres {
             for genre in genres {
-                BookGenre::insert(conn, self, genre).await?;
+                BookGenre::insert(&mut *conn, self, genre).await?;
+            }
+        }
+        if let Some(content_warnings) = &self.content_warnings {
+            for content_warning in content_warnings {
+                BookContentWarning::insert(&mut *conn, self, content_warning).await?;
+            }
+        }
+        if let Some(shelves) = &self.shelves {
+            for shelf in shelves {
+                BookShelf::insert(&mut *conn, self, shelf).await?;
             }
         }
 
@@ -379,6 +944,9 @@ impl Updateable for Book {
         self.hydrate(conn).await?;
         BookAuthor::update(conn, self, &self.authors, &new.authors).await?;
         BookGenre::update(conn, self, &self.genres, &new.genres).await?;
+        BookContentWarning::update(conn, self, &self.content_warnings, &new.content_warnings)
+            .await?;
+        BookShelf::update(conn, self, &self.shelves, &new.shelves).await?;
         Ok(sqlx::query(&format!(
             r#"
             UPDATE {}
@@ -387,7 +955,9 @@ impl Updateable for Book {
                 release_date = ?3,
                 series_id = ?4,
                 series_index = ?5,
-                deleted = ?6
+                tbr_priority = ?6,
+                deleted = ?7,
+                summary = ?8
             WHERE
                 id = ?1;
             "#,
@@ -398,7 +968,9 @@ impl Updateable for Book {
         .bind(&new.release_date)
         .bind(&new.series_id)
         .bind(&new.series_index)
+        .bind(new.tbr_priority)
         .bind(new.deleted)
+        .bind(&new.summary)
         .execute(conn)
         .await?)
     }
@@ -407,18 +979,21 @@ impl Updateable for Book {
 impl FromRow<'_, SqliteRow> for Book {
     fn from_row(row: &SqliteRow) -> sqlx::Result<Self> {
         Ok(Self {
-            id:           row.try_get("id")?,
-            title:        row.try_get("title")?,
-            authors:      None,
-            release_date: row.try_get("release_date")?,
-            summary:      row.try_get("summary")?,
-            editions:     None, // TODO
-            reviews:      None,
-            genres:       None,
-            deleted:      row.try_get("deleted")?,
-            series_id:    row.try_get("series_id")?,
-            series_index: row.try_get("series_index")?,
-            series:       None,
+            id:               row.try_get("id")?,
+            title:            row.try_get("title")?,
+            authors:          None,
+            release_date:     row.try_get("release_date")?,
+            summary:          row.try_get("summary")?,
+            editions:         None, // TODO
+            reviews:          None,
+            genres:           None,
+            content_warnings: None,
+            shelves:          None,
+            tbr_priority:     row.try_get("tbr_priority")?,
+            deleted:          row.try_get("deleted")?,
+            series_id:        row.try_get("series_id")?,
+            series_index:     row.try_get("series_index")?,
+            series:           None,
         })
     }
 }
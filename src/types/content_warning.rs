@@ -0,0 +1,292 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use std::fmt::{Display, Write};
+
+use crate::{
+    config::{self, Styleable},
+    traits::*,
+    types::{book::Book, book_content_warning::BookContentWarning, text::Text, uuid::Uuid},
+};
+use derives::*;
+
+#[derive(
+    Default,
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    FromRow,
+    Id,
+    Names,
+    CRUD,
+    Removeable,
+    Serialize,
+    Deserialize,
+)]
+pub struct ContentWarning {
+    pub id:      Uuid,
+    pub name:    Text,
+    pub deleted: bool,
+}
+
+impl Queryable for ContentWarning {
+    async fn sort_for_display(x: Vec<Self>) -> Vec<Self> {
+        let mut x = x.clone();
+        x.sort_by(|a, b| a.name.0.partial_cmp(&b.name.0).unwrap());
+        x
+    }
+}
+
+impl UpdateVec for ContentWarning {
+}
+
+impl PromptType for ContentWarning {
+    async fn create_by_prompt(
+        _prompt: &str,
+        _initial_value: Option<&Self>,
+        conn: &sqlx::SqlitePool,
+    ) -> Result<Self> {
+        let id = Uuid(uuid::Uuid::new_v4());
+        let name = Text::create_by_prompt("What is the content warning?", None, conn).await?;
+        Ok(Self {
+            id,
+            name: Self::normalize_name(name),
+            deleted: false,
+        })
+    }
+
+    async fn update_by_prompt(&self, _prompt: &str, conn: &sqlx::SqlitePool) -> anyhow::Result<Self>
+    where
+        Self: Display,
+    {
+        let name = self
+            .name
+            .update_by_prompt("Change content warning to:", conn)
+            .await?;
+        let new = Self {
+            id: Uuid(uuid::Uuid::nil()),
+            name,
+            deleted: self.deleted,
+        };
+        Ok(new)
+    }
+
+    async fn create_by_prompt_skippable(
+        _prompt: &str,
+        _initial_value: Option<&Self>,
+        _conn: &sqlx::SqlitePool,
+    ) -> Result<Option<Self>> {
+        unreachable!("Can't skip creation of this type")
+    }
+
+    async fn update_by_prompt_skippable(
+        _s: &Option<Self>,
+        _prompt: &str,
+        _conn: &sqlx::SqlitePool,
+    ) -> anyhow::Result<Option<Self>>
+    where
+        Self: Display,
+    {
+        unreachable!("Can't skip updating this type")
+    }
+}
+
+impl ReferenceType for ContentWarning {
+    fn name(&self) -> &Text {
+        &self.name
+    }
+
+    fn from_name(name: Text) -> Self {
+        Self {
+            id: Uuid(uuid::Uuid::new_v4()),
+            name,
+            deleted: false,
+        }
+    }
+}
+
+impl Display for ContentWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let config = config::Config::cached();
+        let name = self
+            .name
+            .to_string()
+            .style(&config.output_content_warning.style_content);
+        if config.output_content_warning.display_uuid {
+            write!(f, "{} ({})", name, self.id)
+        } else {
+            write!(f, "{}", name)
+        }
+    }
+}
+impl DisplayTerminal for ContentWarning {
+    async fn fmt(
+        &self,
+        f: &mut String,
+        _conn: &sqlx::SqlitePool,
+        config: &config::Config,
+    ) -> Result<()> {
+        let name = self
+            .name
+            .to_string()
+            .style(&config.output_content_warning.style_content);
+        if config.output_content_warning.display_uuid {
+            write!(f, "{} ({})", name, self.id)?;
+        } else {
+            write!(f, "{}", name)?;
+        }
+        Ok(())
+    }
+}
+
+impl Referenced for ContentWarning {
+    fn defaults() -> Vec<(&'static str, uuid::Uuid)> {
+        vec![
+            ("Abuse", uuid::uuid!("3e2a3b10-d5c2-49ad-9a07-402925824be2")),
+            (
+                "Addiction",
+                uuid::uuid!("b202bdc5-0777-495f-8d83-81736a634f8f"),
+            ),
+            (
+                "Animal Death",
+                uuid::uuid!("24d444ea-dfdf-4a2d-b052-0167e734949a"),
+            ),
+            (
+                "Child Abuse",
+                uuid::uuid!("8b8fd374-21f9-4a5e-a5a4-5c51dec8ee0f"),
+            ),
+            ("Death", uuid::uuid!("7718bc3d-480c-49ed-aa2e-9ade4a6495b6")),
+            (
+                "Domestic Abuse",
+                uuid::uuid!("53590c43-58fe-4a7f-8bb1-795780dcb536"),
+            ),
+            (
+                "Eating Disorder",
+                uuid::uuid!("7c7d6c06-900d-404b-bf24-0a8ed98f0fbe"),
+            ),
+            ("Gore", uuid::uuid!("d27a153b-b1ae-409e-873b-cc80b88e740e")),
+            (
+                "Homophobia",
+                uuid::uuid!("b08c95b2-df28-45ae-8b3b-9caed5267ace"),
+            ),
+            (
+                "Mental Illness",
+                uuid::uuid!("5187c03f-355e-47b7-9b46-ab0e27697a91"),
+            ),
+            (
+                "Racism",
+                uuid::uuid!("4a570124-1deb-41cb-bbec-30f6d3c811a2"),
+            ),
+            (
+                "Self-harm",
+                uuid::uuid!("317941a3-2977-4610-8295-8020286ac81d"),
+            ),
+            (
+                "Sexual Assault",
+                uuid::uuid!("e63bc607-0330-4a34-a50a-510d796583c9"),
+            ),
+            (
+                "Suicide",
+                uuid::uuid!("bd26c852-7dd4-405c-b404-f87f2ca68d8b"),
+            ),
+            (
+                "Torture",
+                uuid::uuid!("556bbdde-a8b0-4513-bb1b-9479185ffc1b"),
+            ),
+            ("War", uuid::uuid!("6506a11a-a5b3-4a9f-9829-17d970a9e8b0")),
+        ]
+    }
+
+    fn usage_query() -> String {
+        format!(
+            "SELECT j.contentwarning_id, COUNT(*) FROM {} j JOIN {} b ON b.id = j.book_id \
+             WHERE b.deleted = 0 GROUP BY j.contentwarning_id;",
+            <BookContentWarning as JunctionTable<Book, ContentWarning>>::TABLE_NAME,
+            Book::TABLE_NAME,
+        )
+    }
+}
+
+impl CreateTable for ContentWarning {
+    fn ddl() -> String {
+        format!(
+            r#"
+            CREATE TABLE IF NOT EXISTS {} (
+                id TEXT PRIMARY KEY NOT NULL,
+                name TEXT NOT NULL,
+                deleted BOOL DEFAULT FALSE
+            );
+            "#,
+            Self::TABLE_NAME
+        )
+    }
+
+    async fn create_table(
+        conn: &sqlx::SqlitePool,
+        seed: Option<&config::SeedConfig>,
+    ) -> Result<()> {
+        sqlx::query(&Self::ddl()).execute(conn).await?;
+        let Some(seed) = seed else {
+            return Ok(());
+        };
+        for (name, uuid) in
+            config::SeedConfig::resolve(seed.content_warnings.clone(), Self::defaults())
+        {
+            Self::insert(
+                &Self {
+                    id:      Uuid(uuid),
+                    name:    Text(name.to_string()),
+                    deleted: false,
+                },
+                conn,
+            )
+            .await?;
+        }
+        Ok(())
+    }
+}
+
+impl Insertable for ContentWarning {
+    async fn insert_with(
+        &self,
+        conn: &mut sqlx::SqliteConnection,
+    ) -> anyhow::Result<sqlx::sqlite::SqliteQueryResult> {
+        Ok(sqlx::query(&format!(
+            r#"
+                    INSERT INTO {} ( id, name, deleted )
+                    VALUES ( ?1, ?2, ?3 )
+                    "#,
+            Self::TABLE_NAME
+        ))
+        .bind(&self.id)
+        .bind(&self.name)
+        .bind(self.deleted)
+        .execute(conn)
+        .await?)
+    }
+}
+impl Updateable for ContentWarning {
+    async fn update(
+        &mut self,
+        conn: &sqlx::SqlitePool,
+        new: Self,
+    ) -> Result<sqlx::sqlite::SqliteQueryResult> {
+        Ok(sqlx::query(&format!(
+            r#"
+            UPDATE {}
+            SET 
+                name = ?2,
+                deleted = ?3
+            WHERE
+                id = ?1;
+            "#,
+            Self::TABLE_NAME
+        ))
+        .bind(&self.id)
+        .bind(&new.name)
+        .bind(new.deleted)
+        .execute(conn)
+        .await?)
+    }
+}
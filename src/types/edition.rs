@@ -1,17 +1,25 @@
 use anyhow::Result;
 use crossterm::style::Stylize;
-use inquire::{validator::Validation, Select};
+use inquire::{Select, validator::Validation};
 use serde::{Deserialize, Serialize};
-use sqlx::{sqlite::SqliteRow, FromRow, Row};
+use sqlx::{FromRow, Row, sqlite::SqliteRow};
 use std::fmt::{Display, Write};
 
 use crate::{
     config::{self, Styleable},
     traits::*,
     types::{
-        book::Book, edition_language::EditionLanguage, edition_publisher::EditionPublisher,
-        edition_review::EditionReview, isbn::Isbn, language::Language, progress::Progress,
-        publisher::Publisher, text::Text, timestamp::OptionalTimestamp, uuid::Uuid,
+        book::Book,
+        edition_language::EditionLanguage,
+        edition_publisher::EditionPublisher,
+        edition_review::EditionReview,
+        isbn::{Isbn, canonicalize, display_stored},
+        language::Language,
+        progress::Progress,
+        publisher::Publisher,
+        text::Text,
+        timestamp::{OptionalTimestamp, Timestamp},
+        uuid::Uuid,
     },
 };
 use derives::*;
@@ -19,17 +27,7 @@ use derives::*;
 use super::{binding::Binding, format::EditionFormat, rating::Rating};
 
 #[derive(
-    Default,
-    Debug,
-    Clone,
-    PartialEq,
-    Eq,
-    Names,
-    Id,
-    Removeable,
-    CRUD,
-    Serialize,
-    Deserialize,
+    Default, Debug, Clone, PartialEq, Eq, Names, Id, Removeable, CRUD, Serialize, Deserialize,
 )]
 pub struct Edition {
     pub id:                  Uuid,
@@ -51,6 +49,16 @@ pub struct Edition {
     pub publishers:          Option<Vec<Publisher>>,
     pub cover:               Option<String>,
     pub part_index:          Option<u32>,
+    #[serde(default)]
+    pub acquired_from:       Option<Text>,
+    #[serde(default)]
+    pub acquired_date:       OptionalTimestamp,
+    /// Id on OpenLibrary, like `OL7353617M`, unset for editions entered by hand
+    #[serde(default)]
+    pub openlibrary_key:     Option<Text>,
+    /// Short facts about this copy, like "signed" or "water damage"
+    #[serde(default)]
+    pub notes:               Option<Text>,
     pub reviews:             Option<Vec<EditionReview>>,
     pub progress:            Option<Vec<Progress>>,
     pub deleted:             bool,
@@ -58,25 +66,65 @@ pub struct Edition {
 }
 
 impl Queryable for Edition {
+    const SEARCH_CONDITION: Option<&'static str> =
+        Some("COALESCE(edition_title, book_title) LIKE ?1 OR isbn LIKE ?1");
+
+    async fn hydrate_for_export(&mut self, conn: &sqlx::SqlitePool) -> Result<()> {
+        self.hydrate(conn).await
+    }
+
     async fn sort_for_display(x: Vec<Self>) -> Vec<Self> {
         let mut x = x.clone();
-        x.sort_by(|a, b| match &a.edition_title {
-            Some(title) => title.0.clone(),
-            None => a.book_title.0.clone(),
-        }.partial_cmp(match &b.edition_title {
-            Some(title) => &title.0,
-            None => &b.book_title.0,
-        }).unwrap());
+        x.sort_by(|a, b| {
+            match &a.edition_title {
+                Some(title) => title.0.clone(),
+                None => a.book_title.0.clone(),
+            }
+            .partial_cmp(match &b.edition_title {
+                Some(title) => &title.0,
+                None => &b.book_title.0,
+            })
+            .unwrap()
+        });
         return x;
     }
+
+    fn filtered_by_clap(matches: &clap::ArgMatches) -> bool {
+        matches.get_one::<String>("book").is_some()
+    }
+
+    async fn get_all_by_clap_filtered(
+        conn: &sqlx::SqlitePool,
+        matches: &clap::ArgMatches,
+        include_deleted: bool,
+    ) -> Result<Vec<Self>> {
+        match (matches.get_one::<String>("book"), include_deleted) {
+            (Some(title), false) => {
+                let book = Book::get_by_title_by_prompt(conn, title).await?;
+                Self::get_all_for_book(conn, &book.id).await
+            }
+            (Some(title), true) => {
+                let book = Book::get_by_title_by_prompt(conn, title).await?;
+                let editions = Self::get_all_including_deleted(conn).await?;
+                Ok(editions
+                    .into_iter()
+                    .filter(|x| x.book_id == book.id)
+                    .collect())
+            }
+            (None, true) => Self::get_all_including_deleted(conn).await,
+            (None, false) => Self::get_all(conn).await,
+        }
+    }
 }
 
 impl Edition {
     pub async fn hydrate(&mut self, conn: &sqlx::SqlitePool) -> Result<()> {
-        self.hydrate_languages(conn).await?;
-        self.hydrate_publishers(conn).await?;
-        self.hydrate_format(conn).await?;
-        self.hydrate_binding(conn).await?;
+        (self.languages, self.publishers, self.format, self.binding) = tokio::try_join!(
+            self.get_languages(conn),
+            self.get_publishers(conn),
+            self.get_format(conn),
+            self.get_binding(conn),
+        )?;
         Ok(())
     }
 
@@ -131,18 +179,496 @@ impl Edition {
         self.binding = self.get_binding(conn).await?;
         Ok(())
     }
+
+    /// Get all editions of a book
+    pub async fn get_all_for_book(conn: &sqlx::SqlitePool, book_id: &Uuid) -> Result<Vec<Self>> {
+        Ok(sqlx::query_as::<_, Self>(&format!(
+            "SELECT * FROM {} WHERE book_id = ?1 AND deleted = 0;",
+            Self::TABLE_NAME
+        ))
+        .bind(book_id)
+        .fetch_all(conn)
+        .await?)
+    }
+
+    /// Get the edition with the given isbn, ignoring hyphens and formatting
+    ///
+    /// ```
+    /// use tomex::{traits::*, types::{edition::Edition, text::Text}};
+    ///
+    /// # tokio_test();
+    /// # #[tokio::main]
+    /// # async fn tokio_test() {
+    /// let conn = sqlx::sqlite::SqlitePoolOptions::new()
+    ///     .max_connections(1)
+    ///     .connect("sqlite::memory:")
+    ///     .await
+    ///     .unwrap();
+    /// # tomex::types::book::Book::init_table(&conn, Some(&Default::default())).await.unwrap();
+    /// # sqlx::query("PRAGMA foreign_keys = OFF").execute(&conn).await.unwrap();
+    /// Edition::init_table(&conn, Some(&Default::default())).await.unwrap();
+    /// let edition = Edition {
+    ///     isbn: Some(Text("9780441172719".into())),
+    ///     ..Default::default()
+    /// };
+    /// edition.insert(&conn).await.unwrap();
+    /// let found = Edition::get_by_isbn(&conn, "0-441-17271-7").await.unwrap();
+    /// assert_eq!(found.map(|x| x.id), Some(edition.id));
+    /// # }
+    /// ```
+    pub async fn get_by_isbn(conn: &sqlx::SqlitePool, isbn: &str) -> Result<Option<Self>> {
+        let isbn = canonicalize(isbn.trim());
+        if isbn.is_empty() {
+            return Ok(None);
+        }
+        Ok(sqlx::query_as::<_, Self>(&format!(
+            "SELECT * FROM {} WHERE isbn = ?1 AND deleted = 0;",
+            Self::TABLE_NAME
+        ))
+        .bind(isbn)
+        .fetch_optional(conn)
+        .await?)
+    }
+
+    /// Databases before version 2 stored isbns hyphenated, or styled for the
+    /// terminal. Store them as plain ISBN-13 once.
+    ///
+    /// ```
+    /// use tomex::{traits::*, types::{edition::Edition, text::Text}};
+    ///
+    /// # tokio_test();
+    /// # #[tokio::main]
+    /// # async fn tokio_test() {
+    /// let conn = sqlx::sqlite::SqlitePoolOptions::new()
+    ///     .max_connections(1)
+    ///     .connect("sqlite::memory:")
+    ///     .await
+    ///     .unwrap();
+    /// # tomex::types::book::Book::init_table(&conn, Some(&Default::default())).await.unwrap();
+    /// # sqlx::query("PRAGMA foreign_keys = OFF").execute(&conn).await.unwrap();
+    /// Edition::init_table(&conn, Some(&Default::default())).await.unwrap();
+    /// let styled = Edition {
+    ///     isbn: Some(Text("\x1b[38;5;3m978-0-441-17271-9\x1b[39m".into())),
+    ///     ..Default::default()
+    /// };
+    /// styled.insert(&conn).await.unwrap();
+    /// Edition::canonicalize_isbns(&conn).await.unwrap();
+    /// let isbn: String = sqlx::query_scalar("SELECT isbn FROM editions")
+    ///     .fetch_one(&conn)
+    ///     .await
+    ///     .unwrap();
+    /// assert_eq!(isbn, "9780441172719");
+    /// let version: i64 = sqlx::query_scalar("PRAGMA user_version")
+    ///     .fetch_one(&conn)
+    ///     .await
+    ///     .unwrap();
+    /// assert_eq!(version, 2);
+    /// # }
+    /// ```
+    pub async fn canonicalize_isbns(conn: &sqlx::SqlitePool) -> Result<()> {
+        let version: i64 = sqlx::query_scalar("PRAGMA user_version;")
+            .fetch_one(conn)
+            .await?;
+        if version >= 2 {
+            return Ok(());
+        }
+        let mut tx = conn.begin().await?;
+        let isbns: Vec<(Uuid, String)> = sqlx::query_as(&format!(
+            "SELECT id, isbn FROM {} WHERE isbn IS NOT NULL;",
+            Self::TABLE_NAME
+        ))
+        .fetch_all(&mut tx)
+        .await?;
+        for (id, isbn) in isbns {
+            let canonical = canonicalize(&isbn);
+            if canonical != isbn {
+                sqlx::query(&format!(
+                    "UPDATE {} SET isbn = ?2 WHERE id = ?1;",
+                    Self::TABLE_NAME
+                ))
+                .bind(&id)
+                .bind(canonical)
+                .execute(&mut tx)
+                .await?;
+            }
+        }
+        sqlx::query("PRAGMA user_version = 2;")
+            .execute(&mut tx)
+            .await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Warn if another edition already has this edition's isbn, or fail if
+    /// `enforce_unique_isbn` is set. Removed editions don't count
+    ///
+    /// ```
+    /// use tomex::{config::Config, traits::*, types::{edition::Edition, text::Text}};
+    ///
+    /// # tokio_test();
+    /// # #[tokio::main]
+    /// # async fn tokio_test() {
+    /// let conn = sqlx::sqlite::SqlitePoolOptions::new()
+    ///     .max_connections(1)
+    ///     .connect("sqlite::memory:")
+    ///     .await
+    ///     .unwrap();
+    /// # tomex::types::book::Book::init_table(&conn, Some(&Default::default())).await.unwrap();
+    /// # sqlx::query("PRAGMA foreign_keys = OFF").execute(&conn).await.unwrap();
+    /// Edition::init_table(&conn, Some(&Default::default())).await.unwrap();
+    /// let existing = Edition {
+    ///     isbn: Some(Text("978-0-441-17271-9".into())),
+    ///     ..Default::default()
+    /// };
+    /// existing.insert(&conn).await.unwrap();
+    /// let config = Config { enforce_unique_isbn: true, ..Default::default() };
+    /// let new = Edition {
+    ///     id: tomex::types::uuid::Uuid(uuid::Uuid::new_v4()),
+    ///     isbn: Some(Text("9780441172719".into())),
+    ///     ..Default::default()
+    /// };
+    /// assert!(new.check_unique_isbn(&conn, &config).await.is_err());
+    /// assert!(new.check_unique_isbn(&conn, &Config::default()).await.is_ok());
+    /// existing.remove(&conn).await.unwrap();
+    /// assert!(new.check_unique_isbn(&conn, &config).await.is_ok());
+    /// # }
+    /// ```
+    pub async fn check_unique_isbn(
+        &self,
+        conn: &sqlx::SqlitePool,
+        config: &config::Config,
+    ) -> Result<()> {
+        let Some(isbn) = &self.isbn else {
+            return Ok(());
+        };
+        match Self::get_by_isbn(conn, &isbn.0).await? {
+            Some(existing) if existing.id != self.id => {
+                let problem = format!(
+                    "An edition with isbn {} already exists: {existing} ({})",
+                    isbn.0, existing.id.0
+                );
+                if config.enforce_unique_isbn {
+                    anyhow::bail!(problem);
+                }
+                eprintln!("Warning: {problem}");
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    pub async fn get_by_openlibrary_key(
+        conn: &sqlx::SqlitePool,
+        key: &str,
+    ) -> Result<Option<Self>> {
+        Ok(sqlx::query_as::<_, Self>(&format!(
+            "SELECT * FROM {} WHERE openlibrary_key = ?1 AND deleted = 0;",
+            Self::TABLE_NAME
+        ))
+        .bind(key)
+        .fetch_optional(conn)
+        .await?)
+    }
+
+    /// Get the edition whose uuid starts with `prefix`, fails if it's ambiguous
+    pub async fn get_by_id_prefix(conn: &sqlx::SqlitePool, prefix: &str) -> Result<Option<Self>> {
+        let mut results = sqlx::query_as::<_, Self>(&format!(
+            "SELECT * FROM {} WHERE id LIKE ?1 AND deleted = 0;",
+            Self::TABLE_NAME
+        ))
+        .bind(format!("{}%", prefix.to_lowercase()))
+        .fetch_all(conn)
+        .await?;
+        if results.len() > 1 {
+            anyhow::bail!("More than one edition matches {prefix}");
+        }
+        Ok(results.pop())
+    }
+
+    /// Resolve an edition from either an isbn or a (possibly shortened) uuid
+    pub async fn get_by_isbn_or_id(conn: &sqlx::SqlitePool, s: &str) -> Result<Self> {
+        if let Some(edition) = Self::get_by_isbn(conn, s).await? {
+            return Ok(edition);
+        }
+        match Self::get_by_id_prefix(conn, s).await? {
+            Some(edition) => Ok(edition),
+            None => Err(crate::error::Error::NotFound(format!("No edition found for {s}")).into()),
+        }
+    }
+
+    /// Set the physical fields passed on the command line, leaving the others
+    /// untouched. Returns whether any field was set.
+    pub fn apply_clap(&mut self, matches: &clap::ArgMatches) -> bool {
+        let mut changed = false;
+        for (name, field) in [
+            ("pages", &mut self.pages),
+            ("height", &mut self.height),
+            ("width", &mut self.width),
+            ("thickness", &mut self.thickness),
+            ("weight", &mut self.weight),
+        ] {
+            if let Ok(Some(value)) = matches.try_get_one::<u32>(name) {
+                *field = Some(*value);
+                changed = true;
+            }
+        }
+        changed
+    }
+
+    /// Attach or clear the cover according to `--cover`/`--cover-clear`,
+    /// returns whether anything changed
+    pub fn apply_cover_clap(
+        &mut self,
+        matches: &clap::ArgMatches,
+        config: &config::Config,
+    ) -> Result<bool> {
+        if let Ok(Some(path)) = matches.try_get_one::<std::path::PathBuf>("cover") {
+            self.set_cover_from_file(path, config)?;
+            Ok(true)
+        } else if matches.try_get_one::<bool>("cover-clear").ok().flatten() == Some(&true) {
+            self.cover = None;
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    /// The page count stats and estimates should use for this edition,
+    /// depending on the configured `pages_source`
+    pub async fn pages_by_config(
+        &self,
+        conn: &sqlx::SqlitePool,
+        config: &config::Config,
+    ) -> Result<Option<u32>> {
+        let book = || async { Book::get_by_id(conn, &self.book_id).await };
+        Ok(match config.pages_source {
+            config::PagesSource::Edition => match self.pages {
+                Some(pages) => Some(pages),
+                None => book().await?.representative_pages(conn).await?,
+            },
+            config::PagesSource::Book => match book().await?.representative_pages(conn).await? {
+                Some(pages) => Some(pages),
+                None => self.pages,
+            },
+        })
+    }
+
+    /// When reading this edition will likely be finished at the current pace,
+    /// None without a page count or enough page updates
+    pub async fn estimated_finish(
+        &self,
+        conn: &sqlx::SqlitePool,
+        config: &config::Config,
+    ) -> Result<Option<Timestamp>> {
+        let Some(total) = self.pages_by_config(conn, config).await? else {
+            return Ok(None);
+        };
+        let progress = Progress::get_all_for_edition(conn, &self.id).await?;
+        Ok(Progress::estimate_finish(&progress, total))
+    }
+
+    /// Open the cover with the system's default viewer, returns false if there
+    /// is no cover
+    pub fn open_cover(&self) -> Result<bool> {
+        let Some(cover) = &self.cover else {
+            return Ok(false);
+        };
+        if !std::path::Path::new(cover).is_file() {
+            anyhow::bail!("Cover {cover} doesn't exist anymore");
+        }
+        let mut command = if cfg!(target_os = "macos") {
+            std::process::Command::new("open")
+        } else if cfg!(target_os = "windows") {
+            let mut command = std::process::Command::new("cmd");
+            command.args(["/C", "start", ""]);
+            command
+        } else {
+            std::process::Command::new("xdg-open")
+        };
+        command
+            .arg(cover)
+            .stdout(std::process::Stdio::null())
+            .stderr(std::process::Stdio::null())
+            .spawn()
+            .map_err(|e| anyhow::anyhow!("Couldn't open {cover}: {e}"))?;
+        Ok(true)
+    }
+
+    /// Copy an image into the configured `cover_dir` and use it as the cover.
+    /// Covers are named by the hash of their content, so editions with the
+    /// same image share one file
+    pub fn set_cover_from_file(
+        &mut self,
+        path: &std::path::Path,
+        config: &config::Config,
+    ) -> Result<()> {
+        if !path.is_file() {
+            anyhow::bail!("No file at {}", path.display());
+        }
+        let extension = path
+            .extension()
+            .and_then(|x| x.to_str())
+            .map(|x| x.to_lowercase())
+            .unwrap_or_default();
+        let bytes = std::fs::read(path)?;
+        let kind = image_kind(&bytes[..bytes.len().min(12)]);
+        let is_image = match kind {
+            Some("jpg") => ["jpg", "jpeg"].contains(&extension.as_str()),
+            Some(kind) => kind == extension,
+            None => false,
+        };
+        if !is_image {
+            anyhow::bail!("{} isn't a jpg, png, gif or webp image", path.display());
+        }
+        let dir = config.cover_path()?;
+        std::fs::create_dir_all(&dir)?;
+        let name = format!("{}.{}", cover_hash(&bytes), kind.unwrap_or_default());
+        let destination = dir.join(name);
+        // An existing file with other content is broken, like an interrupted
+        // copy, and gets replaced
+        let intact = std::fs::read(&destination).is_ok_and(|x| x == bytes);
+        if !intact {
+            std::fs::write(&destination, &bytes)?;
+        }
+        self.cover = Some(destination.to_string_lossy().into_owned());
+        Ok(())
+    }
+}
+
+/// Hex encoded SHA-256 of a cover image, what its file in `cover_dir` is
+/// called
+///
+/// ```
+/// use tomex::types::edition::cover_hash;
+///
+/// assert_eq!(
+///     cover_hash(b"abc"),
+///     "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+/// );
+/// ```
+pub fn cover_hash(bytes: &[u8]) -> String {
+    format!("{:x}", <sha2::Sha256 as sha2::Digest>::digest(bytes))
+}
+
+/// The usual file extension of an image going by its first bytes
+fn image_kind(magic: &[u8]) -> Option<&'static str> {
+    match magic {
+        [0xff, 0xd8, 0xff, ..] => Some("jpg"),
+        [0x89, b'P', b'N', b'G', ..] => Some("png"),
+        [b'G', b'I', b'F', b'8', ..] => Some("gif"),
+        [
+            b'R',
+            b'I',
+            b'F',
+            b'F',
+            _,
+            _,
+            _,
+            _,
+            b'W',
+            b'E',
+            b'B',
+            b'P',
+            ..,
+        ] => Some("webp"),
+        _ => None,
+    }
 }
 
 const PARTS_SINGLE: &'static str = "Single-volume";
 const PARTS_MULTI: &'static str = "Multi-part";
+const DEPENDENTS_KEEP: &str = "Keep them with this edition";
+const DEPENDENTS_MOVE: &str = "Move them to another edition of the old book";
+const DEPENDENTS_DELETE: &str = "Delete them";
+
+impl Edition {
+    /// Ask what should happen to the progress updates and edition reviews of
+    /// this edition when it's moved from its book to `book`, before it's
+    /// updated
+    pub async fn update_dependents_by_prompt(
+        &self,
+        conn: &sqlx::SqlitePool,
+        book: &Book,
+    ) -> Result<()> {
+        let progress = Progress::get_all_for_edition(conn, &self.id).await?.len();
+        let reviews = EditionReview::get_all_for_edition(conn, &self.id)
+            .await?
+            .len();
+        if progress == 0 && reviews == 0 {
+            return Ok(());
+        }
+        eprintln!(
+            "Warning: this edition has {progress} progress update(s) and {reviews} edition \
+             review(s), which will move to {} along with it",
+            book.title
+        );
+        let others: Vec<Self> = Self::get_all_for_book(conn, &self.book_id)
+            .await?
+            .into_iter()
+            .filter(|x| x.id != self.id)
+            .collect();
+        let mut options = vec![DEPENDENTS_KEEP, DEPENDENTS_DELETE];
+        if !others.is_empty() {
+            options.insert(1, DEPENDENTS_MOVE);
+        }
+        let choice = prompt_or_abort(|| {
+            Select::new("What should happen to them?", options.clone()).prompt()
+        })?;
+        let edition_id = match choice {
+            DEPENDENTS_KEEP => {
+                sqlx::query(&format!(
+                    "UPDATE {} SET book_title = ?2 WHERE edition_id = ?1;",
+                    EditionReview::TABLE_NAME
+                ))
+                .bind(&self.id)
+                .bind(&book.title)
+                .execute(conn)
+                .await?;
+                return Ok(());
+            }
+            DEPENDENTS_MOVE => {
+                let edition =
+                    prompt_or_abort(|| Select::new("Move them to:", others.clone()).prompt())?;
+                edition.id
+            }
+            DEPENDENTS_DELETE => {
+                for table in [Progress::TABLE_NAME, EditionReview::TABLE_NAME] {
+                    sqlx::query(&format!(
+                        "UPDATE {table} SET deleted = 1 WHERE edition_id = ?1;"
+                    ))
+                    .bind(&self.id)
+                    .execute(conn)
+                    .await?;
+                }
+                return Ok(());
+            }
+            _ => unreachable!("Unexpected response"),
+        };
+        for table in [Progress::TABLE_NAME, EditionReview::TABLE_NAME] {
+            sqlx::query(&format!(
+                "UPDATE {table} SET edition_id = ?2 WHERE edition_id = ?1;"
+            ))
+            .bind(&self.id)
+            .bind(&edition_id)
+            .execute(conn)
+            .await?;
+        }
+        Ok(())
+    }
+}
+
 impl PromptType for Edition {
     async fn create_by_prompt(
         _prompt: &str,
-        _initial_value: Option<&Self>,
+        initial_value: Option<&Self>,
         conn: &sqlx::SqlitePool,
     ) -> Result<Self> {
         let id = Uuid(uuid::Uuid::new_v4());
-        let book = Book::query_or_create_by_prompt(conn).await?;
+        // Skip picking a book if the caller already knows which one it is
+        let book = match initial_value {
+            Some(initial_value) => Book::get_by_id(conn, &initial_value.book_id).await?,
+            None => Book::query_or_create_by_prompt(conn).await?,
+        };
         let book_id = book.id;
         let edition_title =
             Text::create_by_prompt_skippable("What is the title of this edition?", None, conn)
@@ -175,12 +701,14 @@ impl PromptType for Edition {
         };
         let format_id = format.clone().map(|x| x.id);
 
-        let multipart = Select::new(
-            "Is this edition of the book a single volume or one of several parts?",
-            vec![PARTS_SINGLE, PARTS_MULTI],
-        )
-        .with_starting_cursor(0)
-        .prompt()?;
+        let multipart = prompt_or_abort(|| {
+            Select::new(
+                "Is this edition of the book a single volume or one of several parts?",
+                vec![PARTS_SINGLE, PARTS_MULTI],
+            )
+            .with_starting_cursor(0)
+            .prompt()
+        })?;
         let part_index: Option<Rating> = match multipart {
             PARTS_SINGLE => None,
             PARTS_MULTI => Some(
@@ -188,6 +716,21 @@ impl PromptType for Edition {
             ),
             _ => unreachable!(),
         };
+        let acquired_from = Text::create_by_prompt_skippable(
+            "Where did you get this edition? (bought, gifted, library, etc)",
+            None,
+            conn,
+        )
+        .await?;
+        let acquired_date =
+            Timestamp::create_by_prompt_skippable("When did you get this edition?", None, conn)
+                .await?;
+        let notes = Text::create_by_prompt_skippable(
+            "Any notes on this copy? (signed, water damage, etc)",
+            None,
+            conn,
+        )
+        .await?;
         Ok(Self {
             id,
             book_id,
@@ -212,6 +755,10 @@ impl PromptType for Edition {
             binding_id: None, // TODO
             binding: None,
             part_index,
+            acquired_from,
+            acquired_date: OptionalTimestamp(acquired_date),
+            openlibrary_key: None,
+            notes,
         })
     }
 
@@ -221,7 +768,15 @@ impl PromptType for Edition {
     {
         let mut s = self.clone();
         s.hydrate(conn).await?;
-        let book = Book::get_by_id(conn, &s.book_id).await?;
+        let book = if prompt_or_abort(|| {
+            inquire::Confirm::new("Move this edition to a different book?")
+                .with_default(false)
+                .prompt()
+        })? {
+            Book::query_by_prompt(conn).await?
+        } else {
+            Book::get_by_id(conn, &s.book_id).await?
+        };
         let edition_title = PromptType::update_by_prompt_skippable(
             &s.edition_title,
             "What is the edition title?",
@@ -240,12 +795,14 @@ impl PromptType for Edition {
             conn,
         )
         .await?;
-        let multipart = Select::new(
-            "Is this edition of the book a single volume or one of several parts?",
-            vec![PARTS_SINGLE, PARTS_MULTI],
-        )
-        .with_starting_cursor(if s.part_index.is_some() { 1 } else { 0 })
-        .prompt()?;
+        let multipart = prompt_or_abort(|| {
+            Select::new(
+                "Is this edition of the book a single volume or one of several parts?",
+                vec![PARTS_SINGLE, PARTS_MULTI],
+            )
+            .with_starting_cursor(if s.part_index.is_some() { 1 } else { 0 })
+            .prompt()
+        })?;
         let part_index: Option<Rating> = match multipart {
             PARTS_SINGLE => None,
             PARTS_MULTI => {
@@ -254,6 +811,24 @@ impl PromptType for Edition {
             }
             _ => unreachable!(),
         };
+        let acquired_from = PromptType::update_by_prompt_skippable(
+            &s.acquired_from,
+            "Where did you get this edition? (bought, gifted, library, etc)",
+            conn,
+        )
+        .await?;
+        let acquired_date = PromptType::update_by_prompt_skippable(
+            &s.acquired_date.0,
+            "When did you get this edition?",
+            conn,
+        )
+        .await?;
+        let notes = PromptType::update_by_prompt_skippable(
+            &s.notes,
+            "Any notes on this copy? (signed, water damage, etc)",
+            conn,
+        )
+        .await?;
         // Languages
         let languages =
             Language::update_vec(&s.languages, conn, "Select languages for this edition:").await?;
@@ -280,12 +855,16 @@ impl PromptType for Edition {
             languages,
             publishers,
             deleted: self.deleted,
+            book_id: book.id,
             book_title: book.title,
             format_id,
             format,
             binding,
             binding_id,
             part_index,
+            acquired_from,
+            acquired_date: OptionalTimestamp(acquired_date),
+            notes,
             ..self.clone()
         };
         Ok(new)
@@ -313,10 +892,7 @@ impl PromptType for Edition {
 
 impl Display for Edition {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let config = match config::Config::read_config() {
-            Ok(config) => config,
-            Err(_) => return Err(std::fmt::Error),
-        };
+        let config = config::Config::cached();
         let title = match &self.edition_title {
             Some(title) => title.style(&config.output_edition.style_content),
             None => self.book_title.style(&config.output_edition.style_content),
@@ -331,7 +907,7 @@ impl Display for Edition {
         }
         if config.output_edition.display_uuid {
             match &self.isbn {
-                Some(isbn) => write!(f, " ({isbn})")?,
+                Some(isbn) => write!(f, " ({})", display_stored(&isbn.0, config.hyphenate_isbn))?,
                 None => write!(f, " ({})", self.id)?,
             }
         }
@@ -441,9 +1017,34 @@ impl DisplayTerminal for Edition {
                     .await?
             )?;
         }
+        // Acquisition
+        let acquired = match (&s.acquired_from, &s.acquired_date.0) {
+            (Some(from), Some(date)) => Some(format!("{from} {}", date.0.format("%Y-%m-%d"))),
+            (Some(from), None) => Some(from.to_string()),
+            (None, Some(date)) => Some(date.0.format("%Y-%m-%d").to_string()),
+            (None, None) => None,
+        };
+        if let Some(acquired) = acquired {
+            write!(
+                f,
+                "{} ",
+                config
+                    .output_acquired
+                    .format_str(acquired, conn, config)
+                    .await?
+            )?;
+        }
+        // Notes
+        if let Some(notes) = &s.notes {
+            write!(
+                f,
+                "{} ",
+                config.output_notes.format_str(notes, conn, config).await?
+            )?;
+        }
         // ISBN or ID
         if let Some(isbn) = s.isbn {
-            let str = isbn.to_string().italic();
+            let str = display_stored(&isbn.0, config.hyphenate_isbn).italic();
             write!(f, "({str})")?;
         } else {
             write!(f, "({})", s.id)?;
@@ -453,8 +1054,8 @@ impl DisplayTerminal for Edition {
 }
 
 impl CreateTable for Edition {
-    async fn create_table(conn: &sqlx::SqlitePool) -> Result<()> {
-        sqlx::query(&format!(
+    fn ddl() -> String {
+        format!(
             r#"
             CREATE TABLE {} (
                 id TEXT PRIMARY KEY NOT NULL,
@@ -472,38 +1073,63 @@ impl CreateTable for Edition {
                 binding_id TEXT,
             	cover	TEXT,
                 part_index INT,
+                acquired_from TEXT,
+                acquired_date INTEGER,
+                openlibrary_key TEXT,
+                notes TEXT,
             	deleted BOOL DEFAULT FALSE,
                 book_title TEXT,
             	FOREIGN KEY (book_id) REFERENCES {} (id)
             );"#,
             Self::TABLE_NAME,
             Book::TABLE_NAME,
-        ))
-        .execute(conn)
+        )
+    }
+
+    async fn create_table(
+        conn: &sqlx::SqlitePool,
+        _seed: Option<&config::SeedConfig>,
+    ) -> Result<()> {
+        sqlx::query(&Self::ddl()).execute(conn).await?;
+        Ok(())
+    }
+
+    async fn migrate_table(conn: &sqlx::SqlitePool) -> Result<()> {
+        Self::add_column(conn, "acquired_from", "TEXT").await?;
+        Self::add_column(
+            conn,
+            "acquired_date",
+            "INTEGER DEFAULT -9223372036854775808",
+        )
         .await?;
+        Self::add_column(conn, "openlibrary_key", "TEXT").await?;
+        Self::add_column(conn, "notes", "TEXT").await?;
         Ok(())
     }
 }
 
 impl Insertable for Edition {
-    async fn insert(
+    async fn insert(&self, conn: &sqlx::SqlitePool) -> Result<sqlx::sqlite::SqliteQueryResult> {
+        self.check_unique_isbn(conn, config::Config::cached())
+            .await?;
+        self.insert_with(&mut *conn.acquire().await?).await
+    }
+
+    async fn insert_with(
         &self,
-        conn: &sqlx::SqlitePool,
-    ) -> anyhow::Result<sqlx::sqlite::SqliteQueryResult>
-    where
-        Self: Sized,
-    {
+        conn: &mut sqlx::SqliteConnection,
+    ) -> anyhow::Result<sqlx::sqlite::SqliteQueryResult> {
         let result = sqlx::query(
             r#"
-            INSERT INTO editions ( id, book_id, edition_title, edition_description, isbn, pages, release_date, format_id, height, width, thickness, weight, binding_id, cover, part_index, deleted, book_title )
-            VALUES ( ?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17 );
+            INSERT INTO editions ( id, book_id, edition_title, edition_description, isbn, pages, release_date, format_id, height, width, thickness, weight, binding_id, cover, part_index, deleted, book_title, acquired_from, acquired_date, openlibrary_key, notes )
+            VALUES ( ?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20, ?21 );
             "#,
         )
         .bind(&self.id)
         .bind(&self.book_id)
         .bind(&self.edition_title)
         .bind(&self.edition_description)
-        .bind(&self.isbn)
+        .bind(self.isbn.as_ref().map(|x| canonicalize(&x.0)))
         .bind(self.pages)
         .bind(&self.release_date)
         .bind(&self.format_id)
@@ -516,11 +1142,19 @@ impl Insertable for Edition {
         .bind(&self.part_index)
         .bind(self.deleted)
         .bind(&self.book_title)
-        .execute(conn)
+        .bind(&self.acquired_from)
+        .bind(&self.acquired_date)
+        .bind(&self.openlibrary_key)
+        .bind(&self.notes)
+        .execute(&mut *conn)
         .await?;
 
-        EditionLanguage::update(conn, self, &None, &self.languages).await?;
-        EditionPublisher::update(conn, self, &None, &self.publishers).await?;
+        for language in self.languages.iter().flatten() {
+            EditionLanguage::insert(&mut *conn, self, language).await?;
+        }
+        for publisher in self.publishers.iter().flatten() {
+            EditionPublisher::insert(&mut *conn, self, publisher).await?;
+        }
 
         Ok(result)
     }
@@ -553,7 +1187,11 @@ impl Updateable for Edition {
                 cover = ?14,
                 part_index = ?15,
                 deleted = ?16,
-                book_title = ?17
+                book_title = ?17,
+                acquired_from = ?18,
+                acquired_date = ?19,
+                openlibrary_key = ?20,
+                notes = ?21
             WHERE
                 id = ?1;
             "#,
@@ -563,7 +1201,7 @@ impl Updateable for Edition {
         .bind(&new.book_id)
         .bind(&new.edition_title)
         .bind(&new.edition_description)
-        .bind(&new.isbn)
+        .bind(new.isbn.as_ref().map(|x| canonicalize(&x.0)))
         .bind(new.pages)
         .bind(&new.release_date)
         .bind(&new.format_id)
@@ -576,6 +1214,10 @@ impl Updateable for Edition {
         .bind(&new.part_index)
         .bind(new.deleted)
         .bind(&new.book_title)
+        .bind(&new.acquired_from)
+        .bind(&new.acquired_date)
+        .bind(&new.openlibrary_key)
+        .bind(&new.notes)
         .execute(conn)
         .await?)
     }
@@ -601,6 +1243,10 @@ impl FromRow<'_, SqliteRow> for Edition {
             weight:              row.try_get("weight")?,
             binding_id:          row.try_get("binding_id")?,
             part_index:          row.try_get("part_index")?,
+            acquired_from:       row.try_get("acquired_from")?,
+            acquired_date:       row.try_get("acquired_date")?,
+            openlibrary_key:     row.try_get("openlibrary_key")?,
+            notes:               row.try_get("notes")?,
             languages:           Self::default().languages,
             format:              Self::default().format,
             binding:             Self::default().binding,
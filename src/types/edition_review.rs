@@ -2,32 +2,24 @@ use anyhow::Result;
 use inquire::Confirm;
 use serde::{Deserialize, Serialize};
 use sqlx::{
-    sqlite::{SqliteQueryResult, SqliteRow},
     FromRow, Row,
+    sqlite::{SqliteQueryResult, SqliteRow},
 };
 use std::fmt::{Display, Write};
 
 use crate::{
     config::{self, Styleable},
     traits::*,
-    types::{book::Book, text::Text, timestamp::Timestamp, uuid::Uuid},
+    types::{
+        book::Book, option_to_create::OptionToCreate, text::Text, timestamp::Timestamp, uuid::Uuid,
+    },
 };
 use derives::*;
 
 use super::{edition::Edition, price::Price, rating::Rating, timestamp::OptionalTimestamp};
 
 #[derive(
-    Default,
-    Debug,
-    Clone,
-    PartialEq,
-    Eq,
-    Names,
-    CRUD,
-    Removeable,
-    Id,
-    Serialize,
-    Deserialize,
+    Default, Debug, Clone, PartialEq, Eq, Names, CRUD, Removeable, Id, Serialize, Deserialize,
 )]
 pub struct EditionReview {
     pub id:                 Uuid,
@@ -44,6 +36,47 @@ pub struct EditionReview {
     pub price_rating:       Option<u32>,
     pub price_text:         Option<Text>,
     pub price_info:         Option<Price>,
+    /// When the review was written, updates never change it
+    ///
+    /// ```
+    /// use tomex::{traits::*, types::{edition_review::EditionReview, timestamp::Timestamp}};
+    ///
+    /// # tokio_test();
+    /// # #[tokio::main]
+    /// # async fn tokio_test() {
+    /// let conn = sqlx::sqlite::SqlitePoolOptions::new()
+    ///     .max_connections(1)
+    ///     .connect("sqlite::memory:")
+    ///     .await
+    ///     .unwrap();
+    /// # tomex::types::book::Book::init_table(&conn, None).await.unwrap();
+    /// # tomex::types::edition::Edition::init_table(&conn, None).await.unwrap();
+    /// EditionReview::init_table(&conn, None).await.unwrap();
+    /// # let edition = tomex::types::edition::Edition::default();
+    /// # tomex::types::book::Book {
+    /// #     id: edition.book_id.clone(),
+    /// #     ..Default::default()
+    /// # }
+    /// # .insert(&conn)
+    /// # .await
+    /// # .unwrap();
+    /// # edition.insert(&conn).await.unwrap();
+    /// let mut review = EditionReview {
+    ///     edition_id: edition.id.clone(),
+    ///     ..EditionReview::default()
+    /// };
+    /// review.insert(&conn).await.unwrap();
+    /// let new = EditionReview {
+    ///     rating: Some(80),
+    ///     timestamp_created: Timestamp(chrono::Utc::now()),
+    ///     ..review.clone()
+    /// };
+    /// review.update(&conn, new).await.unwrap();
+    /// let updated = EditionReview::get_by_id(&conn, &review.id).await.unwrap();
+    /// assert_eq!(updated.rating, Some(80));
+    /// assert_eq!(updated.timestamp_created, review.timestamp_created);
+    /// # }
+    /// ```
     pub timestamp_created:  Timestamp,
     pub timestamp_updated:  Timestamp,
     pub deleted:            bool,
@@ -62,6 +95,88 @@ impl EditionReview {
     pub async fn hydrate(&mut self, _conn: &sqlx::SqlitePool) -> Result<()> {
         Ok(())
     }
+
+    /// Prompt for the rating of one aspect, like the cover, and a review of it
+    /// if it was rated. Both can be skipped
+    async fn create_aspect_by_prompt(
+        aspect: &str,
+        conn: &sqlx::SqlitePool,
+    ) -> Result<(Option<u32>, Option<Text>)> {
+        let rating: Option<Rating> = PromptType::create_by_prompt_skippable(
+            &format!("What rating would you give this edition's {aspect}? (0-100)"),
+            None::<&Rating>,
+            conn,
+        )
+        .await?;
+        if rating.is_none() {
+            return Ok((None, None));
+        }
+        let text = inquire::Editor::new(&format!(
+            "Write a detailed a review for this edition's {aspect}:"
+        ))
+        .with_file_extension(".md")
+        .prompt_skippable()?
+        .map(Text);
+        Ok((rating, text))
+    }
+
+    /// The aspect ratings that were given, labeled by their initial
+    ///
+    /// ```
+    /// use tomex::types::edition_review::EditionReview;
+    ///
+    /// let review = EditionReview {
+    ///     cover_rating: Some(90),
+    ///     price_rating: Some(85),
+    ///     ..EditionReview::default()
+    /// };
+    /// assert_eq!(review.aspects(), [("C", 90), ("P", 85)]);
+    /// ```
+    pub fn aspects(&self) -> Vec<(&'static str, u32)> {
+        [
+            ("C", self.cover_rating),
+            ("T", self.typesetting_rating),
+            ("M", self.material_rating),
+            ("P", self.price_rating),
+        ]
+        .into_iter()
+        .filter_map(|(label, rating)| rating.map(|x| (label, x)))
+        .collect()
+    }
+
+    /// All edition reviews of an edition
+    pub async fn get_all_for_edition(
+        conn: &sqlx::SqlitePool,
+        edition_id: &Uuid,
+    ) -> Result<Vec<Self>> {
+        Ok(sqlx::query_as::<_, Self>(&format!(
+            "SELECT * FROM {} WHERE edition_id = ?1 AND deleted = 0;",
+            Self::TABLE_NAME
+        ))
+        .bind(edition_id)
+        .fetch_all(conn)
+        .await?)
+    }
+
+    /// Pick a book, then one of its editions or create a new one
+    async fn query_or_create_edition_by_prompt(conn: &sqlx::SqlitePool) -> Result<Edition> {
+        let book = Book::query_by_prompt(conn).await?;
+        let editions = Edition::get_all_for_book(conn, &book.id).await?;
+        let options = OptionToCreate::create_option_to_create(editions);
+        let prompt = || inquire::Select::new("Select edition:", options.clone()).prompt();
+        match prompt_or_abort(prompt)? {
+            OptionToCreate::Value(edition) => Ok(edition),
+            OptionToCreate::Create => {
+                let initial_value = Edition {
+                    book_id: book.id,
+                    ..Edition::default()
+                };
+                let edition = Edition::create_by_prompt("", Some(&initial_value), conn).await?;
+                edition.insert(conn).await?;
+                Ok(edition)
+            }
+        }
+    }
 }
 
 impl PromptType for EditionReview {
@@ -71,7 +186,7 @@ impl PromptType for EditionReview {
         conn: &sqlx::SqlitePool,
     ) -> Result<Self> {
         let id = Uuid(uuid::Uuid::new_v4());
-        let edition = Edition::query_by_prompt(conn).await?;
+        let edition = Self::query_or_create_edition_by_prompt(conn).await?;
         let edition_id = edition.id;
         let rating: Option<Rating> = PromptType::create_by_prompt_skippable(
             "What rating would you give this edition? (0-100)",
@@ -82,6 +197,13 @@ impl PromptType for EditionReview {
         let recommend = Confirm::new("Would you recommend this edition?")
             .with_default(true)
             .prompt_skippable()?;
+        let (cover_rating, cover_text) = Self::create_aspect_by_prompt("cover", conn).await?;
+        let (typesetting_rating, typesetting_text) =
+            Self::create_aspect_by_prompt("typesetting", conn).await?;
+        let (material_rating, material_text) =
+            Self::create_aspect_by_prompt("material", conn).await?;
+        let (price_rating, price_text) = Self::create_aspect_by_prompt("price", conn).await?;
+        let price_info = PromptType::create_by_prompt_skippable("", None::<&Price>, conn).await?;
 
         Ok(Self {
             id,
@@ -93,15 +215,15 @@ impl PromptType for EditionReview {
             timestamp_updated: Timestamp(chrono::Utc::now()),
             book_title: edition.book_title,
             deleted: false,
-            cover_rating: None,
-            cover_text: None,
-            typesetting_rating: None,
-            typesetting_text: None,
-            material_rating: None,
-            material_text: None,
-            price_rating: None,
-            price_text: None,
-            price_info: None,
+            cover_rating,
+            cover_text,
+            typesetting_rating,
+            typesetting_text,
+            material_rating,
+            material_text,
+            price_rating,
+            price_text,
+            price_info,
         })
     }
 
@@ -204,11 +326,12 @@ impl PromptType for EditionReview {
 
         let price_info = PromptType::update_by_prompt_skippable(&self.price_info, "", conn).await?;
 
-        if !inquire::Confirm::new("Update review?")
-            .with_default(true)
-            .prompt()?
-        {
-            anyhow::bail!("Aborted");
+        if !prompt_or_abort(|| {
+            inquire::Confirm::new("Update review?")
+                .with_default(true)
+                .prompt()
+        })? {
+            anyhow::bail!(crate::error::Error::Aborted("Aborted".into()));
         };
 
         let new = Self {
@@ -253,10 +376,7 @@ impl PromptType for EditionReview {
 
 impl Display for EditionReview {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let config = match config::Config::read_config() {
-            Ok(config) => config,
-            Err(_) => return Err(std::fmt::Error),
-        };
+        let config = config::Config::cached();
         write!(
             f,
             "{}",
@@ -294,6 +414,28 @@ impl DisplayTerminal for EditionReview {
                     .await?
             )?;
         }
+        // Aspect ratings
+        let aspects = s.aspects();
+        if config.edition_review_aspects && !aspects.is_empty() {
+            let output = &config.output_aspect_ratings;
+            let aspects: Vec<String> = aspects
+                .into_iter()
+                .map(|(label, rating)| {
+                    format!(
+                        "{label}:{}",
+                        rating.to_string().style(&output.style_content)
+                    )
+                })
+                .collect();
+            write!(
+                f,
+                "{}{} {}{} ",
+                output.prefix.style(&output.style_prefix),
+                output.description.style(&output.style_description),
+                aspects.join(" "),
+                output.suffix.style(&output.style_suffix),
+            )?;
+        }
         // Recommended
         if let Some(recommended) = s.recommend {
             let str = match recommended {
@@ -345,8 +487,8 @@ impl DisplayTerminal for EditionReview {
 }
 
 impl CreateTable for EditionReview {
-    async fn create_table(conn: &sqlx::SqlitePool) -> Result<()> {
-        sqlx::query(&format!(
+    fn ddl() -> String {
+        format!(
             r#"
             CREATE TABLE {} (
             	id TEXT PRIMARY KEY NOT NULL,
@@ -372,15 +514,20 @@ impl CreateTable for EditionReview {
             );"#,
             Self::TABLE_NAME,
             Edition::TABLE_NAME,
-        ))
-        .execute(conn)
-        .await?;
+        )
+    }
+
+    async fn create_table(
+        conn: &sqlx::SqlitePool,
+        _seed: Option<&config::SeedConfig>,
+    ) -> Result<()> {
+        sqlx::query(&Self::ddl()).execute(conn).await?;
         Ok(())
     }
 }
 
 impl Insertable for EditionReview {
-    async fn insert(&self, conn: &sqlx::SqlitePool) -> Result<SqliteQueryResult> {
+    async fn insert_with(&self, conn: &mut sqlx::SqliteConnection) -> Result<SqliteQueryResult> {
         Ok(sqlx::query(&format!(
             r#"
             INSERT INTO {} ( 
@@ -437,10 +584,9 @@ impl Updateable for EditionReview {
             	price_text = ?13,
             	price_value = ?14,
             	price_timestamp = ?15,
-                timestamp_created = ?16,
-                timestamp_updated = ?17,
-                deleted = ?18,
-                book_title = ?19
+                timestamp_updated = ?16,
+                deleted = ?17,
+                book_title = ?18
             WHERE
                 id = ?1;
             "#,
@@ -463,7 +609,6 @@ impl Updateable for EditionReview {
         .bind(OptionalTimestamp(
             new.price_info.clone().map(|x| x.timestamp.0).flatten(),
         ))
-        .bind(&new.timestamp_created)
         .bind(&new.timestamp_updated)
         .bind(new.deleted)
         .bind(&new.book_title)
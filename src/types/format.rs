@@ -6,7 +6,7 @@ use std::fmt::{Display, Write};
 use crate::{
     config::{self, Styleable},
     traits::*,
-    types::{text::Text, uuid::Uuid},
+    types::{edition::Edition, text::Text, uuid::Uuid},
 };
 use derives::*;
 
@@ -84,10 +84,7 @@ impl PromptType for EditionFormat {
 
 impl Display for EditionFormat {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let config = match config::Config::read_config() {
-            Ok(config) => config,
-            Err(_) => return Err(std::fmt::Error),
-        };
+        let config = config::Config::cached();
         let name = self
             .name
             .to_string()
@@ -119,21 +116,9 @@ impl DisplayTerminal for EditionFormat {
     }
 }
 
-impl CreateTable for EditionFormat {
-    async fn create_table(conn: &sqlx::SqlitePool) -> Result<()> {
-        sqlx::query(&format!(
-            r#"
-            CREATE TABLE IF NOT EXISTS {} (
-                id TEXT PRIMARY KEY NOT NULL,
-                name TEXT NOT NULL,
-                deleted BOOL DEFAULT FALSE
-            );
-            "#,
-            Self::TABLE_NAME
-        ))
-        .execute(conn)
-        .await?;
-        let default_formats = vec![
+impl Referenced for EditionFormat {
+    fn defaults() -> Vec<(&'static str, uuid::Uuid)> {
+        vec![
             (
                 "Paperback",
                 uuid::uuid!("93b3f802-21df-486c-b15b-3da96f533c01"),
@@ -147,8 +132,41 @@ impl CreateTable for EditionFormat {
                 "Audiobook",
                 uuid::uuid!("ed96d107-3ba7-4328-9e15-c9b583863a17"),
             ),
-        ];
-        for (format, uuid) in default_formats {
+        ]
+    }
+
+    fn usage_query() -> String {
+        format!(
+            "SELECT format_id, COUNT(*) FROM {} WHERE deleted = 0 AND format_id IS NOT NULL \
+             GROUP BY format_id;",
+            Edition::TABLE_NAME,
+        )
+    }
+}
+
+impl CreateTable for EditionFormat {
+    fn ddl() -> String {
+        format!(
+            r#"
+            CREATE TABLE IF NOT EXISTS {} (
+                id TEXT PRIMARY KEY NOT NULL,
+                name TEXT NOT NULL,
+                deleted BOOL DEFAULT FALSE
+            );
+            "#,
+            Self::TABLE_NAME
+        )
+    }
+
+    async fn create_table(
+        conn: &sqlx::SqlitePool,
+        seed: Option<&config::SeedConfig>,
+    ) -> Result<()> {
+        sqlx::query(&Self::ddl()).execute(conn).await?;
+        let Some(seed) = seed else {
+            return Ok(());
+        };
+        for (format, uuid) in config::SeedConfig::resolve(seed.formats.clone(), Self::defaults()) {
             Self::insert(
                 &Self {
                     id:      Uuid(uuid),
@@ -164,13 +182,10 @@ impl CreateTable for EditionFormat {
 }
 
 impl Insertable for EditionFormat {
-    async fn insert(
+    async fn insert_with(
         &self,
-        conn: &sqlx::SqlitePool,
-    ) -> anyhow::Result<sqlx::sqlite::SqliteQueryResult>
-    where
-        Self: Sized,
-    {
+        conn: &mut sqlx::SqliteConnection,
+    ) -> anyhow::Result<sqlx::sqlite::SqliteQueryResult> {
         Ok(sqlx::query(&format!(
             r#"
                     INSERT INTO {} ( id, name, deleted )
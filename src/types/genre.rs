@@ -6,7 +6,7 @@ use std::fmt::{Display, Write};
 use crate::{
     config::{self, Styleable},
     traits::*,
-    types::{text::Text, uuid::Uuid},
+    types::{book::Book, book_genre::BookGenre, text::Text, uuid::Uuid},
 };
 use derives::*;
 
@@ -51,7 +51,7 @@ impl PromptType for Genre {
         let name = Text::create_by_prompt("What is the name of the genre?", None, conn).await?;
         Ok(Self {
             id,
-            name,
+            name: Self::normalize_name(name),
             deleted: false,
         })
     }
@@ -92,12 +92,23 @@ impl PromptType for Genre {
     }
 }
 
+impl ReferenceType for Genre {
+    fn name(&self) -> &Text {
+        &self.name
+    }
+
+    fn from_name(name: Text) -> Self {
+        Self {
+            id: Uuid(uuid::Uuid::new_v4()),
+            name,
+            deleted: false,
+        }
+    }
+}
+
 impl Display for Genre {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let config = match config::Config::read_config() {
-            Ok(config) => config,
-            Err(_) => return Err(std::fmt::Error),
-        };
+        let config = config::Config::cached();
         let name = self
             .name
             .to_string()
@@ -129,21 +140,9 @@ impl DisplayTerminal for Genre {
     }
 }
 
-impl CreateTable for Genre {
-    async fn create_table(conn: &sqlx::SqlitePool) -> Result<()> {
-        sqlx::query(&format!(
-            r#"
-            CREATE TABLE IF NOT EXISTS {} (
-                id TEXT PRIMARY KEY NOT NULL,
-                name TEXT NOT NULL,
-                deleted BOOL DEFAULT FALSE
-            );
-            "#,
-            Self::TABLE_NAME
-        ))
-        .execute(conn)
-        .await?;
-        let default_genres = vec![
+impl Referenced for Genre {
+    fn defaults() -> Vec<(&'static str, uuid::Uuid)> {
+        vec![
             (
                 "Fantasy",
                 uuid::uuid!("26f223a0-879b-4581-9f43-393ff0bf1dbb"),
@@ -237,8 +236,42 @@ impl CreateTable for Genre {
                 "Religion & Spirituality",
                 uuid::uuid!("3f04f6f8-59b9-4afa-beb0-164a45afbbb5"),
             ),
-        ];
-        for (genre, uuid) in default_genres {
+        ]
+    }
+
+    fn usage_query() -> String {
+        format!(
+            "SELECT j.genre_id, COUNT(*) FROM {} j JOIN {} b ON b.id = j.book_id \
+             WHERE b.deleted = 0 GROUP BY j.genre_id;",
+            <BookGenre as JunctionTable<Book, Genre>>::TABLE_NAME,
+            Book::TABLE_NAME,
+        )
+    }
+}
+
+impl CreateTable for Genre {
+    fn ddl() -> String {
+        format!(
+            r#"
+            CREATE TABLE IF NOT EXISTS {} (
+                id TEXT PRIMARY KEY NOT NULL,
+                name TEXT NOT NULL,
+                deleted BOOL DEFAULT FALSE
+            );
+            "#,
+            Self::TABLE_NAME
+        )
+    }
+
+    async fn create_table(
+        conn: &sqlx::SqlitePool,
+        seed: Option<&config::SeedConfig>,
+    ) -> Result<()> {
+        sqlx::query(&Self::ddl()).execute(conn).await?;
+        let Some(seed) = seed else {
+            return Ok(());
+        };
+        for (genre, uuid) in config::SeedConfig::resolve(seed.genres.clone(), Self::defaults()) {
             Self::insert(
                 &Self {
                     id:      Uuid(uuid),
@@ -254,13 +287,10 @@ impl CreateTable for Genre {
 }
 
 impl Insertable for Genre {
-    async fn insert(
+    async fn insert_with(
         &self,
-        conn: &sqlx::SqlitePool,
-    ) -> anyhow::Result<sqlx::sqlite::SqliteQueryResult>
-    where
-        Self: Sized,
-    {
+        conn: &mut sqlx::SqliteConnection,
+    ) -> anyhow::Result<sqlx::sqlite::SqliteQueryResult> {
         Ok(sqlx::query(&format!(
             r#"
                     INSERT INTO {} ( id, name, deleted )
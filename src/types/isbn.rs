@@ -1,9 +1,9 @@
-use inquire::{validator::Validation, CustomUserError};
+use inquire::{CustomUserError, validator::Validation};
 use std::fmt::Display;
 
 use crate::{
     config::{self, Styleable},
-    traits::PromptType,
+    traits::{PromptType, prompt_or_abort},
 };
 
 use super::text::Text;
@@ -12,8 +12,57 @@ use super::text::Text;
 pub struct Isbn(pub isbn2::Isbn);
 
 impl Isbn {
+    /// The plain ISBN-13 isbns are stored as, ISBN-10s are converted
+    ///
+    /// ```
+    /// use tomex::types::isbn::Isbn;
+    ///
+    /// let isbn = Isbn("0-441-17271-7".parse().unwrap());
+    /// assert_eq!(isbn.canonical(), "9780441172719");
+    /// ```
+    pub fn canonical(&self) -> String {
+        match &self.0 {
+            isbn2::Isbn::_10(x) => isbn2::Isbn13::from(*x).to_string(),
+            isbn2::Isbn::_13(x) => x.to_string(),
+        }
+    }
+
+    /// The form the isbn is stored in
     pub fn to_text(&self) -> Text {
-        Text(self.to_string())
+        Text(self.canonical())
+    }
+}
+
+/// Bring an isbn into the form it's stored in, also if it was stored styled
+/// like older versions did, leaving text that isn't a valid isbn as it is
+///
+/// ```
+/// use tomex::types::isbn::canonicalize;
+///
+/// assert_eq!(canonicalize("978-0-441-17271-9"), "9780441172719");
+/// assert_eq!(canonicalize("0441172717"), "9780441172719");
+/// assert_eq!(canonicalize("\x1b[38;5;3m978-0-441-17271-9\x1b[39m"), "9780441172719");
+/// assert_eq!(canonicalize("not an isbn"), "not an isbn");
+/// ```
+pub fn canonicalize(s: &str) -> String {
+    match crate::table::strip_escapes(s).trim().parse::<isbn2::Isbn>() {
+        Ok(isbn) => Isbn(isbn).canonical(),
+        Err(_) => s.to_string(),
+    }
+}
+
+/// Show a stored isbn, hyphenated if `hyphenate` is set and it's valid
+///
+/// ```
+/// use tomex::types::isbn::display_stored;
+///
+/// assert_eq!(display_stored("9780441172719", true), "978-0-441-17271-9");
+/// assert_eq!(display_stored("9780441172719", false), "9780441172719");
+/// ```
+pub fn display_stored(s: &str, hyphenate: bool) -> String {
+    match s.parse::<isbn2::Isbn>() {
+        Ok(isbn) if hyphenate => isbn.hyphenate().map_or(s.to_string(), |x| x.to_string()),
+        _ => s.to_string(),
     }
 }
 
@@ -37,8 +86,7 @@ impl PromptType for Isbn {
         if let Some(initial_value) = &initial_value {
             prompt = prompt.with_initial_value(initial_value);
         }
-        let isbn = prompt
-            .prompt()?
+        let isbn = prompt_or_abort(|| prompt.clone().prompt())?
             .parse::<isbn2::Isbn>()
             .expect("Unreachable");
         Ok(Self(isbn))
@@ -82,14 +130,8 @@ impl PromptType for Isbn {
 
 impl Display for Isbn {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let config = match config::Config::read_config() {
-            Ok(config) => config,
-            Err(_) => return Err(std::fmt::Error),
-        };
-        let s = self
-            .0
-            .hyphenate()
-            .expect("Hyphenating isbn failed")
+        let config = config::Config::cached();
+        let s = display_stored(&self.canonical(), config.hyphenate_isbn)
             .style(&config.output_isbn.style_content);
         write!(f, "{s}")
     }
@@ -106,11 +148,7 @@ impl<'q> sqlx::Encode<'q, sqlx::Sqlite> for Isbn {
         &self,
         args: &mut Vec<sqlx::sqlite::SqliteArgumentValue<'q>>,
     ) -> sqlx::encode::IsNull {
-        let s: String = self
-            .0
-            .hyphenate()
-            .expect("Hyphenating isbn failed")
-            .to_string();
+        let s = self.canonical();
         args.push(sqlx::sqlite::SqliteArgumentValue::Text(
             std::borrow::Cow::Owned(s),
         ));
@@ -119,6 +157,27 @@ impl<'q> sqlx::Encode<'q, sqlx::Sqlite> for Isbn {
     }
 }
 
+/// Text that isn't a valid isbn is an error rather than a panic, imports can
+/// store isbns as they came
+///
+/// ```
+/// use tomex::types::isbn::Isbn;
+///
+/// # tokio_test();
+/// # #[tokio::main]
+/// # async fn tokio_test() {
+/// let conn = sqlx::SqlitePool::connect("sqlite::memory:").await.unwrap();
+/// let valid: Isbn = sqlx::query_scalar("SELECT '9780441172719';")
+///     .fetch_one(&conn)
+///     .await
+///     .unwrap();
+/// assert_eq!(valid.canonical(), "9780441172719");
+/// let invalid = sqlx::query_scalar::<_, Isbn>("SELECT '978-not-an-isbn';")
+///     .fetch_one(&conn)
+///     .await;
+/// assert!(invalid.is_err());
+/// # }
+/// ```
 impl<'r, DB: sqlx::Database> sqlx::Decode<'r, DB> for Isbn
 where
     &'r str: sqlx::Decode<'r, DB>,
@@ -127,7 +186,9 @@ where
         value: <DB as sqlx::database::HasValueRef<'r>>::ValueRef,
     ) -> Result<Self, Box<dyn std::error::Error + 'static + Send + Sync>> {
         let value = <&str as sqlx::Decode<DB>>::decode(value)?;
-        let id = value.parse().expect("Invalid isbn stored in database");
+        let id = value
+            .parse()
+            .map_err(|_| format!("Invalid isbn {value} stored in database"))?;
         Ok(Self(id))
     }
 }
@@ -0,0 +1,27 @@
+use const_format::formatcp;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+use crate::{
+    traits::*,
+    types::{book::Book, content_warning::ContentWarning, uuid::Uuid},
+};
+
+#[derive(Default, Debug, Clone, PartialEq, Eq, FromRow, Serialize, Deserialize)]
+pub struct BookContentWarning {
+    pub book_id:           Uuid,
+    pub contentwarning_id: Uuid,
+}
+
+impl JunctionTable<Book, ContentWarning> for BookContentWarning {
+    const TABLE_NAME: &'static str =
+        formatcp!("{}_{}", Book::NAME_SINGULAR, ContentWarning::NAME_SINGULAR);
+
+    async fn get_id_a(&self) -> &Uuid {
+        &self.book_id
+    }
+
+    async fn get_id_b(&self) -> &Uuid {
+        &self.contentwarning_id
+    }
+}
@@ -0,0 +1,46 @@
+use anyhow::Result;
+use const_format::formatcp;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+use crate::{
+    traits::*,
+    types::{book::Book, shelf::Shelf, uuid::Uuid},
+};
+
+#[derive(Default, Debug, Clone, PartialEq, Eq, FromRow, Serialize, Deserialize)]
+pub struct BookShelf {
+    pub book_id:  Uuid,
+    pub shelf_id: Uuid,
+}
+
+impl JunctionTable<Book, Shelf> for BookShelf {
+    const TABLE_NAME: &'static str = formatcp!("{}_{}", Book::NAME_SINGULAR, Shelf::NAME_SINGULAR);
+
+    async fn get_id_a(&self) -> &Uuid {
+        &self.book_id
+    }
+
+    async fn get_id_b(&self) -> &Uuid {
+        &self.shelf_id
+    }
+}
+
+impl BookShelf {
+    /// Position of a book on a shelf, counting from 1 in the order books were
+    /// added to it
+    pub async fn position(conn: &sqlx::SqlitePool, book: &Book, shelf: &Shelf) -> Result<u32> {
+        Ok(sqlx::query_scalar(&format!(
+            r#"
+            SELECT COUNT(*) FROM {table} WHERE shelf_id = ?2 AND rowid <= (
+                SELECT rowid FROM {table} WHERE book_id = ?1 AND shelf_id = ?2
+            );
+            "#,
+            table = <Self as JunctionTable<Book, Shelf>>::TABLE_NAME,
+        ))
+        .bind(&book.id)
+        .bind(&shelf.id)
+        .fetch_one(conn)
+        .await?)
+    }
+}
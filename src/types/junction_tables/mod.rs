@@ -1,5 +1,7 @@
 pub mod book_author;
+pub mod book_content_warning;
 pub mod book_genre;
+pub mod book_shelf;
 pub mod edition_language;
 pub mod edition_publisher;
 pub mod review_mood;
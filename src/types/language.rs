@@ -6,7 +6,7 @@ use std::fmt::{Display, Write};
 use crate::{
     config::{self, Styleable},
     traits::*,
-    types::{text::Text, uuid::Uuid},
+    types::{edition::Edition, edition_language::EditionLanguage, text::Text, uuid::Uuid},
 };
 use derives::*;
 
@@ -51,7 +51,7 @@ impl PromptType for Language {
         let name = Text::create_by_prompt("What is the name of the language?", None, conn).await?;
         Ok(Self {
             id,
-            name,
+            name: Self::normalize_name(name),
             deleted: false,
         })
     }
@@ -92,12 +92,23 @@ impl PromptType for Language {
     }
 }
 
+impl ReferenceType for Language {
+    fn name(&self) -> &Text {
+        &self.name
+    }
+
+    fn from_name(name: Text) -> Self {
+        Self {
+            id: Uuid(uuid::Uuid::new_v4()),
+            name,
+            deleted: false,
+        }
+    }
+}
+
 impl Display for Language {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let config = match config::Config::read_config() {
-            Ok(config) => config,
-            Err(_) => return Err(std::fmt::Error),
-        };
+        let config = config::Config::cached();
         write!(
             f,
             "{}",
@@ -133,9 +144,27 @@ impl DisplayTerminal for Language {
     }
 }
 
+impl Referenced for Language {
+    fn defaults() -> Vec<(&'static str, uuid::Uuid)> {
+        vec![(
+            "English",
+            uuid::uuid!("a95f5f6e-8560-4b02-9443-14f7502d28fe"),
+        )]
+    }
+
+    fn usage_query() -> String {
+        format!(
+            "SELECT j.language_id, COUNT(*) FROM {} j JOIN {} e ON e.id = j.edition_id \
+             WHERE e.deleted = 0 GROUP BY j.language_id;",
+            <EditionLanguage as JunctionTable<Edition, Language>>::TABLE_NAME,
+            Edition::TABLE_NAME,
+        )
+    }
+}
+
 impl CreateTable for Language {
-    async fn create_table(conn: &sqlx::SqlitePool) -> Result<()> {
-        sqlx::query(&format!(
+    fn ddl() -> String {
+        format!(
             r#"
             CREATE TABLE IF NOT EXISTS {} (
                 id TEXT PRIMARY KEY NOT NULL,
@@ -144,15 +173,21 @@ impl CreateTable for Language {
             );
             "#,
             Self::TABLE_NAME
-        ))
-        .execute(conn)
-        .await?;
+        )
+    }
 
-        let default_languages = vec![(
-            "English",
-            uuid::uuid!("a95f5f6e-8560-4b02-9443-14f7502d28fe"),
-        )];
-        for (language, uuid) in default_languages {
+    async fn create_table(
+        conn: &sqlx::SqlitePool,
+        seed: Option<&config::SeedConfig>,
+    ) -> Result<()> {
+        sqlx::query(&Self::ddl()).execute(conn).await?;
+        let Some(seed) = seed else {
+            return Ok(());
+        };
+
+        for (language, uuid) in
+            config::SeedConfig::resolve(seed.languages.clone(), Self::defaults())
+        {
             Self::insert(
                 &Self {
                     id:      Uuid(uuid),
@@ -168,13 +203,10 @@ impl CreateTable for Language {
 }
 
 impl Insertable for Language {
-    async fn insert(
+    async fn insert_with(
         &self,
-        conn: &sqlx::SqlitePool,
-    ) -> anyhow::Result<sqlx::sqlite::SqliteQueryResult>
-    where
-        Self: Sized,
-    {
+        conn: &mut sqlx::SqliteConnection,
+    ) -> anyhow::Result<sqlx::sqlite::SqliteQueryResult> {
         Ok(sqlx::query(&format!(
             r#"
                     INSERT INTO {} ( id, name, deleted )
@@ -9,6 +9,7 @@ pub mod uuid;
 pub mod author;
 pub mod binding;
 pub mod book;
+pub mod content_warning;
 pub mod edition;
 pub mod edition_review;
 pub mod format;
@@ -20,6 +21,7 @@ pub mod progress;
 pub mod publisher;
 pub mod review;
 pub mod series;
+pub mod shelf;
 
 pub mod junction_tables;
 pub use junction_tables::*;
@@ -6,7 +6,7 @@ use std::fmt::{Display, Write};
 use crate::{
     config::{self, Styleable},
     traits::*,
-    types::{text::Text, uuid::Uuid},
+    types::{review::Review, review_mood::ReviewMood, text::Text, uuid::Uuid},
 };
 use derives::*;
 
@@ -51,7 +51,7 @@ impl PromptType for Mood {
         let name = Text::create_by_prompt(prompt, initial_value.map(|x| &x.name), conn).await?;
         Ok(Self {
             id,
-            name,
+            name: Self::normalize_name(name),
             deleted: false,
         })
     }
@@ -87,12 +87,23 @@ impl PromptType for Mood {
     }
 }
 
+impl ReferenceType for Mood {
+    fn name(&self) -> &Text {
+        &self.name
+    }
+
+    fn from_name(name: Text) -> Self {
+        Self {
+            id: Uuid(uuid::Uuid::new_v4()),
+            name,
+            deleted: false,
+        }
+    }
+}
+
 impl Display for Mood {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let config = match config::Config::read_config() {
-            Ok(config) => config,
-            Err(_) => return Err(std::fmt::Error),
-        };
+        let config = config::Config::cached();
         write!(
             f,
             "{}",
@@ -128,22 +139,9 @@ impl DisplayTerminal for Mood {
     }
 }
 
-impl CreateTable for Mood {
-    async fn create_table(conn: &sqlx::SqlitePool) -> Result<()> {
-        sqlx::query(&format!(
-            r#"
-            CREATE TABLE IF NOT EXISTS {} (
-                id TEXT PRIMARY KEY NOT NULL,
-                name TEXT NOT NULL,
-                deleted BOOL DEFAULT FALSE
-            );
-            "#,
-            Self::TABLE_NAME
-        ))
-        .execute(conn)
-        .await?;
-
-        let default_moods = vec![
+impl Referenced for Mood {
+    fn defaults() -> Vec<(&'static str, uuid::Uuid)> {
+        vec![
             (
                 "Adventurous",
                 uuid::uuid!("e7291183-ba90-48a3-b102-b21e732fd2c0"),
@@ -188,8 +186,43 @@ impl CreateTable for Mood {
             ),
             ("Sad", uuid::uuid!("bb2c5921-eee5-4a62-aa83-cb7834e558c2")),
             ("Tense", uuid::uuid!("7f584f2d-35f1-4fec-aeba-e62c7212398f")),
-        ];
-        for (mood, uuid) in default_moods {
+        ]
+    }
+
+    fn usage_query() -> String {
+        format!(
+            "SELECT j.mood_id, COUNT(*) FROM {} j JOIN {} r ON r.id = j.review_id \
+             WHERE r.deleted = 0 GROUP BY j.mood_id;",
+            <ReviewMood as JunctionTable<Review, Mood>>::TABLE_NAME,
+            Review::TABLE_NAME,
+        )
+    }
+}
+
+impl CreateTable for Mood {
+    fn ddl() -> String {
+        format!(
+            r#"
+            CREATE TABLE IF NOT EXISTS {} (
+                id TEXT PRIMARY KEY NOT NULL,
+                name TEXT NOT NULL,
+                deleted BOOL DEFAULT FALSE
+            );
+            "#,
+            Self::TABLE_NAME
+        )
+    }
+
+    async fn create_table(
+        conn: &sqlx::SqlitePool,
+        seed: Option<&config::SeedConfig>,
+    ) -> Result<()> {
+        sqlx::query(&Self::ddl()).execute(conn).await?;
+        let Some(seed) = seed else {
+            return Ok(());
+        };
+
+        for (mood, uuid) in config::SeedConfig::resolve(seed.moods.clone(), Self::defaults()) {
             Self::insert(
                 &Self {
                     id:      Uuid(uuid),
@@ -205,13 +238,10 @@ impl CreateTable for Mood {
 }
 
 impl Insertable for Mood {
-    async fn insert(
+    async fn insert_with(
         &self,
-        conn: &sqlx::SqlitePool,
-    ) -> anyhow::Result<sqlx::sqlite::SqliteQueryResult>
-    where
-        Self: Sized,
-    {
+        conn: &mut sqlx::SqliteConnection,
+    ) -> anyhow::Result<sqlx::sqlite::SqliteQueryResult> {
         Ok(sqlx::query(&format!(
             r#"
                     INSERT INTO {} ( id, name, deleted )
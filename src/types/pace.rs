@@ -41,16 +41,16 @@ impl Queryable for Pace {
 impl PromptType for Pace {
     async fn create_by_prompt(
         _prompt: &str,
-        _initial_value: Option<&Self>,
+        initial_value: Option<&Self>,
         conn: &sqlx::SqlitePool,
     ) -> Result<Self> {
-        let id = Uuid(uuid::Uuid::new_v4());
-        let name = Text::create_by_prompt("What is the name of the pace?", None, conn).await?;
-        Ok(Self {
-            id,
-            name,
-            deleted: false,
-        })
+        let name = Text::create_by_prompt(
+            "What is the name of the pace?",
+            initial_value.map(|x| &x.name),
+            conn,
+        )
+        .await?;
+        Ok(Self::new(Self::normalize_name(name)))
     }
 
     async fn update_by_prompt(&self, _prompt: &str, conn: &sqlx::SqlitePool) -> anyhow::Result<Self>
@@ -58,40 +58,83 @@ impl PromptType for Pace {
         Self: Display,
     {
         let name = PromptType::update_by_prompt(&self.name, "Change pace name to:", conn).await?;
-        let new = Self {
-            id: Uuid(uuid::Uuid::nil()),
+        Ok(Self {
             name,
-            deleted: self.deleted,
-        };
-        Ok(new)
+            ..self.clone()
+        })
     }
 
     async fn create_by_prompt_skippable(
         _prompt: &str,
-        _initial_value: Option<&Self>,
-        _conn: &sqlx::SqlitePool,
+        initial_value: Option<&Self>,
+        conn: &sqlx::SqlitePool,
     ) -> Result<Option<Self>> {
-        unreachable!("Can't skip creation of this type")
+        let name = Text::create_by_prompt_skippable(
+            "What is the name of the pace?",
+            initial_value.map(|x| &x.name),
+            conn,
+        )
+        .await?;
+        Ok(name.map(|x| Self::new(Self::normalize_name(x))))
     }
 
     async fn update_by_prompt_skippable(
-        _s: &Option<Self>,
+        s: &Option<Self>,
         _prompt: &str,
-        _conn: &sqlx::SqlitePool,
+        conn: &sqlx::SqlitePool,
     ) -> anyhow::Result<Option<Self>>
     where
         Self: Display,
     {
-        unreachable!("Can't skip updating this type")
+        let name = Text::update_by_prompt_skippable(
+            &s.as_ref().map(|x| x.name.clone()),
+            "Change pace name to:",
+            conn,
+        )
+        .await?;
+        Ok(match (s, name) {
+            (Some(s), Some(name)) => Some(Self { name, ..s.clone() }),
+            (None, Some(name)) => Some(Self::new(name)),
+            (s, None) => s.clone(),
+        })
+    }
+}
+
+impl Pace {
+    /// A new pace with a random id, used when creating one by prompt
+    ///
+    /// ```
+    /// use tomex::types::{pace::Pace, text::Text};
+    ///
+    /// let a = Pace::new(Text("Leisurely".into()));
+    /// let b = Pace::new(Text("Leisurely".into()));
+    /// assert_eq!(a.name, Text("Leisurely".into()));
+    /// assert_eq!(a.name, b.name);
+    /// assert_ne!(a.id, b.id);
+    /// assert!(!a.deleted);
+    /// ```
+    pub fn new(name: Text) -> Self {
+        Self {
+            id: Uuid(uuid::Uuid::new_v4()),
+            name,
+            deleted: false,
+        }
+    }
+}
+
+impl ReferenceType for Pace {
+    fn name(&self) -> &Text {
+        &self.name
+    }
+
+    fn from_name(name: Text) -> Self {
+        Self::new(name)
     }
 }
 
 impl Display for Pace {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let config = match config::Config::read_config() {
-            Ok(config) => config,
-            Err(_) => return Err(std::fmt::Error),
-        };
+        let config = config::Config::cached();
         write!(
             f,
             "{}",
@@ -128,8 +171,8 @@ impl DisplayTerminal for Pace {
 }
 
 impl CreateTable for Pace {
-    async fn create_table(conn: &sqlx::SqlitePool) -> Result<()> {
-        sqlx::query(&format!(
+    fn ddl() -> String {
+        format!(
             r#"
             CREATE TABLE IF NOT EXISTS {} (
                 id TEXT PRIMARY KEY NOT NULL,
@@ -138,9 +181,17 @@ impl CreateTable for Pace {
             );
             "#,
             Self::TABLE_NAME
-        ))
-        .execute(conn)
-        .await?;
+        )
+    }
+
+    async fn create_table(
+        conn: &sqlx::SqlitePool,
+        seed: Option<&config::SeedConfig>,
+    ) -> Result<()> {
+        sqlx::query(&Self::ddl()).execute(conn).await?;
+        let Some(seed) = seed else {
+            return Ok(());
+        };
 
         let default_paces = vec![
             ("Slow", uuid::uuid!("7b0f2901-e058-4901-a527-307d4be12baf")),
@@ -150,7 +201,7 @@ impl CreateTable for Pace {
             ),
             ("Fast", uuid::uuid!("65bef1a9-75a6-490c-a1f0-68b6026192fa")),
         ];
-        for (pace, uuid) in default_paces {
+        for (pace, uuid) in config::SeedConfig::resolve(seed.paces.clone(), default_paces) {
             Self::insert(
                 &Self {
                     id:      Uuid(uuid),
@@ -166,13 +217,10 @@ impl CreateTable for Pace {
 }
 
 impl Insertable for Pace {
-    async fn insert(
+    async fn insert_with(
         &self,
-        conn: &sqlx::SqlitePool,
-    ) -> anyhow::Result<sqlx::sqlite::SqliteQueryResult>
-    where
-        Self: Sized,
-    {
+        conn: &mut sqlx::SqliteConnection,
+    ) -> anyhow::Result<sqlx::sqlite::SqliteQueryResult> {
         Ok(sqlx::query(&format!(
             r#"
                     INSERT INTO {} ( id, name, deleted )
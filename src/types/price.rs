@@ -1,12 +1,12 @@
 use std::fmt::Display;
 
-use inquire::{validator::Validation, CustomUserError};
+use inquire::{CustomUserError, validator::Validation};
 use liquidity_check::validate;
 use serde::{Deserialize, Serialize};
 
 use crate::{
     config::{self, Styleable},
-    traits::PromptType,
+    traits::{PromptType, prompt_or_abort},
 };
 
 use super::{text::Text, timestamp::OptionalTimestamp};
@@ -19,10 +19,7 @@ pub struct Price {
 
 impl Display for Price {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let config = match config::Config::read_config() {
-            Ok(config) => config,
-            Err(_) => return Err(std::fmt::Error),
-        };
+        let config = config::Config::cached();
         let s = self
             .value
             .to_string()
@@ -60,7 +57,7 @@ impl PromptType for Price {
         if let Some(s) = init_value {
             prompt = prompt.with_initial_value(&s.0);
         }
-        let value = prompt.prompt()?;
+        let value = prompt_or_abort(|| prompt.clone().prompt())?;
         let timestamp = PromptType::create_by_prompt(
             "When did you purchase the edition for this price?",
             init_timestamp.as_ref(),
@@ -7,7 +7,7 @@ use std::fmt::{Display, Write};
 use crate::{
     config::{self, Styleable},
     traits::*,
-    types::{edition::Edition, timestamp::Timestamp, uuid::Uuid},
+    types::{edition::Edition, text::Text, timestamp::Timestamp, uuid::Uuid},
 };
 use derives::*;
 
@@ -31,6 +31,8 @@ pub struct Progress {
     pub edition_id:     Uuid,
     pub timestamp:      Timestamp,
     pub pages_progress: PagesProgress,
+    #[serde(default)]
+    pub progress_note:  Option<Text>,
     pub deleted:        bool,
 }
 
@@ -40,6 +42,183 @@ impl Queryable for Progress {
         x.sort_by(|a, b| a.timestamp.partial_cmp(&b.timestamp).unwrap());
         return x;
     }
+
+    /// Like [Progress::query_by_prompt_skippable] but can't be skipped
+    async fn query_by_prompt(conn: &sqlx::SqlitePool) -> Result<Self> {
+        Self::query_by_prompt_skippable(conn)
+            .await?
+            .ok_or_else(|| crate::error::Error::Aborted("Cancelled".into()).into())
+    }
+
+    /// Select an edition, then one of its progress updates, latest preselected
+    async fn query_by_prompt_skippable(conn: &sqlx::SqlitePool) -> Result<Option<Self>> {
+        let Some(edition) = Edition::query_by_prompt_skippable(conn).await? else {
+            return Ok(None);
+        };
+        let progress = Self::get_all_for_edition(conn, &edition.id).await?;
+        if progress.is_empty() {
+            return Err(crate::error::Error::NotFound(format!(
+                "No progress updates for {edition}"
+            ))
+            .into());
+        }
+        let latest = progress.len() - 1;
+        Ok(inquire::Select::new("Select progress update:", progress)
+            .with_starting_cursor(latest)
+            .prompt_skippable()?)
+    }
+}
+
+impl Progress {
+    /// A new progress update for an edition, timestamped now
+    pub fn now(edition_id: Uuid, pages_progress: PagesProgress) -> Self {
+        Self {
+            id: Uuid(uuid::Uuid::new_v4()),
+            edition_id,
+            timestamp: Timestamp(chrono::Utc::now()),
+            pages_progress,
+            progress_note: None,
+            deleted: false,
+        }
+    }
+
+    /// All progress updates for an edition, oldest first
+    pub async fn get_all_for_edition(
+        conn: &sqlx::SqlitePool,
+        edition_id: &Uuid,
+    ) -> Result<Vec<Self>> {
+        Ok(sqlx::query_as::<_, Self>(&format!(
+            "SELECT * FROM {} WHERE edition_id = ?1 AND deleted = 0 ORDER BY timestamp;",
+            Self::TABLE_NAME
+        ))
+        .bind(edition_id)
+        .fetch_all(conn)
+        .await?)
+    }
+
+    /// Project when reading of an edition with `total` pages will finish, from
+    /// the pace between the first and last page update since it was last
+    /// started. `progress` must be oldest first, returns None if the edition
+    /// isn't being read or there are fewer than two page updates
+    ///
+    /// ```
+    /// use tomex::types::{progress::{PagesProgress, Progress}, timestamp::Timestamp, uuid::Uuid};
+    ///
+    /// let at = |day: u32, pages| Progress {
+    ///     timestamp: Timestamp(
+    ///         "2024-01-01T00:00:00Z".parse::<chrono::DateTime<chrono::Utc>>().unwrap()
+    ///             + chrono::Duration::days(day.into()),
+    ///     ),
+    ///     ..Progress::now(Uuid::default(), pages)
+    /// };
+    /// let mut progress = vec![at(0, PagesProgress::Started), at(1, PagesProgress::Pages(50))];
+    /// assert_eq!(Progress::estimate_finish(&progress, 300), None);
+    /// progress.push(at(3, PagesProgress::Pages(150)));
+    /// let finish = Progress::estimate_finish(&progress, 300).unwrap();
+    /// assert_eq!(finish.0.to_rfc3339(), "2024-01-07T00:00:00+00:00");
+    /// progress.push(at(4, PagesProgress::Finished));
+    /// assert_eq!(Progress::estimate_finish(&progress, 300), None);
+    /// ```
+    pub fn estimate_finish(progress: &[Self], total: u32) -> Option<Timestamp> {
+        let current = match progress
+            .iter()
+            .rposition(|x| x.pages_progress == PagesProgress::Started)
+        {
+            Some(i) => &progress[i..],
+            None => progress,
+        };
+        if matches!(
+            current.last()?.pages_progress,
+            PagesProgress::Finished | PagesProgress::Abandoned
+        ) {
+            return None;
+        }
+        let mut pages = current.iter().filter_map(|x| match x.pages_progress {
+            PagesProgress::Pages(n) => Some((&x.timestamp, n)),
+            _ => None,
+        });
+        let (first_time, first_pages) = pages.next()?;
+        let (last_time, last_pages) = pages.next_back()?;
+        let seconds = (last_time.0 - first_time.0).num_seconds();
+        if last_pages <= first_pages || seconds <= 0 {
+            return None;
+        }
+        let remaining = i64::from(total.saturating_sub(last_pages));
+        let left = remaining * seconds / i64::from(last_pages - first_pages);
+        Some(Timestamp(last_time.0 + chrono::Duration::seconds(left)))
+    }
+
+    /// Describe this update as part of an edition's timeline, pages relative to
+    /// the edition's `total` pages if known
+    pub fn fmt_timeline(&self, total: Option<u32>, config: &config::Config) -> String {
+        let style = &config.output_progress.style_content;
+        let mut s = match (&self.pages_progress, total) {
+            (PagesProgress::Started, _) => format!("{} {}", self.timestamp, "Started".style(style)),
+            (PagesProgress::Finished, _) => {
+                format!("{} {}", self.timestamp, "Finished".style(style))
+            }
+            (PagesProgress::Abandoned, _) => {
+                format!("{} {}", self.timestamp, "Stopped reading".style(style))
+            }
+            (PagesProgress::Pages(n), Some(total)) if total > 0 => format!(
+                "{} page {} of {}, {}%",
+                self.timestamp,
+                n.to_string().style(style),
+                total,
+                n * 100 / total
+            ),
+            (PagesProgress::Pages(n), _) => {
+                format!("{} page {}", self.timestamp, n.to_string().style(style))
+            }
+        };
+        if let Some(note) = &self.progress_note {
+            s.push_str(&format!(" ({note})"));
+        }
+        s
+    }
+
+    /// Check this update against the ones already recorded for the edition,
+    /// returning a description of anything that looks out of order
+    pub async fn check_sequence(&self, conn: &sqlx::SqlitePool) -> Result<Option<String>> {
+        let previous: Vec<Self> = Self::get_all_for_edition(conn, &self.edition_id)
+            .await?
+            .into_iter()
+            .filter(|x| x.id != self.id && x.timestamp <= self.timestamp)
+            .collect();
+        if self.pages_progress == PagesProgress::Started {
+            return Ok(None);
+        }
+        if !previous
+            .iter()
+            .any(|x| x.pages_progress == PagesProgress::Started)
+        {
+            return Ok(Some(
+                "No start of reading recorded for this edition".to_string(),
+            ));
+        }
+        if let (Some(PagesProgress::Pages(last)), PagesProgress::Pages(n)) = (
+            previous.last().map(|x| &x.pages_progress),
+            &self.pages_progress,
+        ) {
+            if n < last {
+                return Ok(Some(format!(
+                    "Page {n} is lower than the last recorded page {last}"
+                )));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Warn about out of order updates, or fail if `strict_progress` is set
+    pub async fn validate(&self, conn: &sqlx::SqlitePool, config: &config::Config) -> Result<()> {
+        if let Some(problem) = self.check_sequence(conn).await? {
+            if config.strict_progress {
+                anyhow::bail!(problem);
+            }
+            eprintln!("Warning: {problem}");
+        }
+        Ok(())
+    }
 }
 
 #[derive(Default, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -47,6 +226,8 @@ pub enum PagesProgress {
     #[default]
     Started,
     Finished,
+    /// Stopped reading without finishing (DNF)
+    Abandoned,
     Pages(u32),
 }
 impl sqlx::Type<sqlx::Sqlite> for PagesProgress {
@@ -66,6 +247,7 @@ impl<'q> sqlx::Encode<'q, sqlx::Sqlite> for PagesProgress {
         args.push(sqlx::sqlite::SqliteArgumentValue::Int64(match self {
             PagesProgress::Started => 0_i64,
             PagesProgress::Finished => -1_i64,
+            PagesProgress::Abandoned => -2_i64,
             PagesProgress::Pages(n) => i64::from(*n),
         }));
 
@@ -83,6 +265,7 @@ where
         match value {
             0 => Ok(Self::Started),
             -1 => Ok(Self::Finished),
+            -2 => Ok(Self::Abandoned),
             n if n > 0 && u32::try_from(n).is_ok() => {
                 Ok(Self::Pages(u32::try_from(n).expect("Unreachable")))
             }
@@ -101,13 +284,14 @@ impl PromptType for PagesProgress {
         const OPTION_START: &'static str = "Start reading book";
         const OPTION_PAGES: &'static str = "Input current page number";
         const OPTION_FINISH: &'static str = "Finish reading book";
-        let options: Vec<&str> = vec![OPTION_START, OPTION_PAGES, OPTION_FINISH];
+        const OPTION_ABANDON: &str = "Stop reading book (DNF)";
+        let options: Vec<&str> = vec![OPTION_START, OPTION_PAGES, OPTION_FINISH, OPTION_ABANDON];
 
-        let ans: Result<&str, inquire::InquireError> = inquire::Select::new("", options).prompt();
+        let ans = prompt_or_abort(|| inquire::Select::new("", options.clone()).prompt())?;
 
         match ans {
-            Ok(OPTION_START) => Ok(Self::Started),
-            Ok(OPTION_PAGES) => {
+            OPTION_START => Ok(Self::Started),
+            OPTION_PAGES => {
                 let edition = Edition::query_by_prompt(conn).await?;
                 let max_pages = edition.pages;
                 let validator = move |input: &str| match input.parse::<u32>() {
@@ -133,16 +317,15 @@ impl PromptType for PagesProgress {
                         ),
                     )),
                 };
-                let pages_progress = inquire::Text::new("At which page are you?")
-                    .with_validator(validator)
-                    .prompt()?
+                let prompt = inquire::Text::new("At which page are you?").with_validator(validator);
+                let pages_progress = prompt_or_abort(|| prompt.clone().prompt())?
                     .parse::<u32>()
                     .expect("Unreachable");
                 Ok(Self::Pages(pages_progress))
             }
-            Ok(OPTION_FINISH) => Ok(Self::Finished),
-            Ok(_) => unreachable!("Unexpected response"),
-            Err(_) => Err(anyhow::anyhow!("Error getting progress")),
+            OPTION_FINISH => Ok(Self::Finished),
+            OPTION_ABANDON => Ok(Self::Abandoned),
+            _ => unreachable!("Unexpected response"),
         }
     }
 
@@ -182,13 +365,22 @@ impl PromptType for Progress {
         let timestamp =
             Timestamp::create_by_prompt("For when is this progress update?", None, conn).await?;
         let pages_progress = PagesProgress::create_by_prompt("", None, conn).await?;
-        Ok(Self {
+        let progress_note = match pages_progress {
+            PagesProgress::Abandoned => {
+                Text::create_by_prompt_skippable("Why did you stop reading?", None, conn).await?
+            }
+            _ => None,
+        };
+        let progress = Self {
             id,
             edition_id: edition.id,
             timestamp,
             pages_progress,
+            progress_note,
             deleted: false,
-        })
+        };
+        progress.validate(conn, config::Config::cached()).await?;
+        Ok(progress)
     }
 
     async fn update_by_prompt(&self, _prompt: &str, conn: &sqlx::SqlitePool) -> anyhow::Result<Self>
@@ -202,9 +394,21 @@ impl PromptType for Progress {
         )
         .await?;
         let pages_progress = PagesProgress::create_by_prompt("", None, conn).await?;
+        let progress_note = match pages_progress {
+            PagesProgress::Abandoned => {
+                PromptType::update_by_prompt_skippable(
+                    &self.progress_note,
+                    "Why did you stop reading?",
+                    conn,
+                )
+                .await?
+            }
+            _ => None,
+        };
         let new = Self {
             timestamp,
             pages_progress,
+            progress_note,
             ..self.clone()
         };
         Ok(new)
@@ -232,10 +436,7 @@ impl PromptType for Progress {
 
 impl Display for Progress {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let config = match config::Config::read_config() {
-            Ok(config) => config,
-            Err(_) => return Err(std::fmt::Error),
-        };
+        let config = config::Config::cached();
         match self.pages_progress {
             PagesProgress::Started => write!(
                 f,
@@ -249,6 +450,12 @@ impl Display for Progress {
                 "Finished book".style(&config.output_progress.style_content),
                 self.timestamp,
             )?,
+            PagesProgress::Abandoned => write!(
+                f,
+                "{} {}",
+                "Stopped reading book".style(&config.output_progress.style_content),
+                self.timestamp,
+            )?,
             PagesProgress::Pages(n) => write!(
                 f,
                 "{}: {} pages",
@@ -256,6 +463,9 @@ impl Display for Progress {
                 n.to_string().style(&config.output_progress.style_content)
             )?,
         };
+        if let Some(note) = &self.progress_note {
+            write!(f, " ({note})")?;
+        }
         if config.output_progress.display_uuid {
             write!(f, " ({})", self.id)
         } else {
@@ -287,6 +497,13 @@ impl DisplayTerminal for Progress {
                 "Finished book".style(&config.output_progress.style_content),
                 self.timestamp,
             )?,
+            PagesProgress::Abandoned => write!(
+                f,
+                "{}: {} {}",
+                title,
+                "Stopped reading book".style(&config.output_progress.style_content),
+                self.timestamp,
+            )?,
             PagesProgress::Pages(n) => write!(
                 f,
                 "{}: {} pages ({})",
@@ -295,6 +512,9 @@ impl DisplayTerminal for Progress {
                 self.timestamp,
             )?,
         };
+        if let Some(note) = &self.progress_note {
+            write!(f, " ({note})")?;
+        }
         if config.output_progress.display_uuid {
             write!(f, " ({})", self.id)?;
         }
@@ -303,14 +523,15 @@ impl DisplayTerminal for Progress {
 }
 
 impl CreateTable for Progress {
-    async fn create_table(conn: &sqlx::SqlitePool) -> Result<()> {
-        sqlx::query(&format!(
+    fn ddl() -> String {
+        format!(
             r#"
             CREATE TABLE IF NOT EXISTS {} (
                 id TEXT PRIMARY KEY NOT NULL,
             	edition_id	TEXT	NOT NULL,
             	timestamp   INTEGER	NOT NULL,
             	pages_progress	BIGINT	NOT NULL,
+                progress_note   TEXT,
                 deleted BOOL DEFAULT FALSE,
             	FOREIGN KEY (edition_id) REFERENCES {} (id)
             );
@@ -318,26 +539,32 @@ impl CreateTable for Progress {
             "#,
             Self::TABLE_NAME,
             Edition::TABLE_NAME
-        ))
-        .execute(conn)
-        .await?;
+        )
+    }
+
+    async fn create_table(
+        conn: &sqlx::SqlitePool,
+        _seed: Option<&config::SeedConfig>,
+    ) -> Result<()> {
+        sqlx::query(&Self::ddl()).execute(conn).await?;
 
         Ok(())
     }
+
+    async fn migrate_table(conn: &sqlx::SqlitePool) -> Result<()> {
+        Self::add_column(conn, "progress_note", "TEXT").await
+    }
 }
 
 impl Insertable for Progress {
-    async fn insert(
+    async fn insert_with(
         &self,
-        conn: &sqlx::SqlitePool,
-    ) -> anyhow::Result<sqlx::sqlite::SqliteQueryResult>
-    where
-        Self: Sized,
-    {
+        conn: &mut sqlx::SqliteConnection,
+    ) -> anyhow::Result<sqlx::sqlite::SqliteQueryResult> {
         Ok(sqlx::query(&format!(
             r#"
-                    INSERT INTO {} ( id, edition_id, timestamp, pages_progress, deleted )
-                    VALUES ( ?1, ?2, ?3, ?4, ?5 )
+                    INSERT INTO {} ( id, edition_id, timestamp, pages_progress, progress_note, deleted )
+                    VALUES ( ?1, ?2, ?3, ?4, ?5, ?6 )
                     "#,
             Self::TABLE_NAME
         ))
@@ -345,6 +572,7 @@ impl Insertable for Progress {
         .bind(&self.edition_id)
         .bind(&self.timestamp)
         .bind(self.pages_progress.clone())
+        .bind(&self.progress_note)
         .bind(self.deleted)
         .execute(conn)
         .await?)
@@ -360,10 +588,11 @@ impl Updateable for Progress {
             r#"
             UPDATE {}
             SET 
-                editon_id = ?2,
+                edition_id = ?2,
                 timestamp = ?3,
                 pages_progress = ?4,
-                deleted = ?5
+                progress_note = ?5,
+                deleted = ?6
             WHERE
                 id = ?1;
             "#,
@@ -373,6 +602,7 @@ impl Updateable for Progress {
         .bind(&new.edition_id)
         .bind(&new.timestamp)
         .bind(new.pages_progress)
+        .bind(&new.progress_note)
         .bind(new.deleted)
         .execute(conn)
         .await?)
@@ -6,7 +6,7 @@ use std::fmt::{Display, Write};
 use crate::{
     config::{self, Styleable},
     traits::*,
-    types::{text::Text, uuid::Uuid},
+    types::{edition::Edition, edition_publisher::EditionPublisher, text::Text, uuid::Uuid},
 };
 use derives::*;
 
@@ -51,7 +51,7 @@ impl PromptType for Publisher {
         let name = Text::create_by_prompt("What is the name of the publisher?", None, conn).await?;
         Ok(Self {
             id,
-            name,
+            name: Self::normalize_name(name),
             deleted: false,
         })
     }
@@ -90,12 +90,23 @@ impl PromptType for Publisher {
     }
 }
 
+impl ReferenceType for Publisher {
+    fn name(&self) -> &Text {
+        &self.name
+    }
+
+    fn from_name(name: Text) -> Self {
+        Self {
+            id: Uuid(uuid::Uuid::new_v4()),
+            name,
+            deleted: false,
+        }
+    }
+}
+
 impl Display for Publisher {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let config = match config::Config::read_config() {
-            Ok(config) => config,
-            Err(_) => return Err(std::fmt::Error),
-        };
+        let config = config::Config::cached();
         write!(
             f,
             "{}",
@@ -131,22 +142,9 @@ impl DisplayTerminal for Publisher {
     }
 }
 
-impl CreateTable for Publisher {
-    async fn create_table(conn: &sqlx::SqlitePool) -> Result<()> {
-        sqlx::query(&format!(
-            r#"
-            CREATE TABLE IF NOT EXISTS {} (
-                id TEXT PRIMARY KEY NOT NULL,
-                name TEXT NOT NULL,
-                deleted BOOL DEFAULT FALSE
-            );
-            "#,
-            Self::TABLE_NAME
-        ))
-        .execute(conn)
-        .await?;
-
-        let default_publishers = vec![
+impl Referenced for Publisher {
+    fn defaults() -> Vec<(&'static str, uuid::Uuid)> {
+        vec![
             (
                 "Penguin Random House",
                 uuid::uuid!("2334916b-e46c-4acf-ba6c-c2145f8e4be8"),
@@ -183,8 +181,45 @@ impl CreateTable for Publisher {
                 "John Wiley & Sons",
                 uuid::uuid!("f524b405-45d0-4709-a7bd-73714239e05b"),
             ),
-        ];
-        for (publisher, uuid) in default_publishers {
+        ]
+    }
+
+    fn usage_query() -> String {
+        format!(
+            "SELECT j.publisher_id, COUNT(*) FROM {} j JOIN {} e ON e.id = j.edition_id \
+             WHERE e.deleted = 0 GROUP BY j.publisher_id;",
+            <EditionPublisher as JunctionTable<Edition, Publisher>>::TABLE_NAME,
+            Edition::TABLE_NAME,
+        )
+    }
+}
+
+impl CreateTable for Publisher {
+    fn ddl() -> String {
+        format!(
+            r#"
+            CREATE TABLE IF NOT EXISTS {} (
+                id TEXT PRIMARY KEY NOT NULL,
+                name TEXT NOT NULL,
+                deleted BOOL DEFAULT FALSE
+            );
+            "#,
+            Self::TABLE_NAME
+        )
+    }
+
+    async fn create_table(
+        conn: &sqlx::SqlitePool,
+        seed: Option<&config::SeedConfig>,
+    ) -> Result<()> {
+        sqlx::query(&Self::ddl()).execute(conn).await?;
+        let Some(seed) = seed else {
+            return Ok(());
+        };
+
+        for (publisher, uuid) in
+            config::SeedConfig::resolve(seed.publishers.clone(), Self::defaults())
+        {
             Self::insert(
                 &Self {
                     id:      Uuid(uuid),
@@ -200,13 +235,10 @@ impl CreateTable for Publisher {
 }
 
 impl Insertable for Publisher {
-    async fn insert(
+    async fn insert_with(
         &self,
-        conn: &sqlx::SqlitePool,
-    ) -> anyhow::Result<sqlx::sqlite::SqliteQueryResult>
-    where
-        Self: Sized,
-    {
+        conn: &mut sqlx::SqliteConnection,
+    ) -> anyhow::Result<sqlx::sqlite::SqliteQueryResult> {
         Ok(sqlx::query(&format!(
             r#"
                     INSERT INTO {} ( id, name, deleted )
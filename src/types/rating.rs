@@ -1,6 +1,6 @@
-use inquire::{validator::Validation, CustomUserError};
+use inquire::{CustomUserError, validator::Validation};
 
-use crate::traits::PromptType;
+use crate::traits::{PromptType, prompt_or_abort};
 
 pub type Rating = u32;
 
@@ -34,7 +34,9 @@ impl PromptType for Rating {
         if let Some(s) = &initial_value {
             prompt = prompt.with_initial_value(s);
         }
-        Ok(prompt.prompt()?.parse::<u32>().expect("Unreachable"))
+        Ok(prompt_or_abort(|| prompt.clone().prompt())?
+            .parse::<u32>()
+            .expect("Unreachable"))
     }
 
     async fn create_by_prompt_skippable(
@@ -5,12 +5,22 @@ use sqlx::{
     sqlite::{SqliteQueryResult, SqliteRow},
     FromRow, Row,
 };
+use std::collections::HashMap;
 use std::fmt::{Display, Write};
 
 use crate::{
-    config,
+    cache, config,
     traits::*,
-    types::{book::Book, mood::Mood, pace::Pace, text::Text, timestamp::Timestamp, uuid::Uuid},
+    types::{
+        book::Book,
+        edition::Edition,
+        mood::Mood,
+        pace::Pace,
+        progress::{PagesProgress, Progress},
+        text::Text,
+        timestamp::Timestamp,
+        uuid::Uuid,
+    },
 };
 use derives::*;
 
@@ -35,6 +45,51 @@ pub struct Review {
     pub rating:            Option<u32>,
     pub recommend:         Option<bool>,
     pub content:           Option<Text>,
+    pub spoiler:           bool,
+    pub private_notes:     Option<Text>,
+    pub plot_rating:       Option<u32>,
+    pub plot_text:         Option<Text>,
+    pub characters_rating: Option<u32>,
+    pub characters_text:   Option<Text>,
+    pub prose_rating:      Option<u32>,
+    pub prose_text:        Option<Text>,
+    pub pacing_rating:     Option<u32>,
+    pub pacing_text:       Option<Text>,
+    /// When the review was written, updates never change it
+    ///
+    /// ```
+    /// use tomex::{traits::*, types::{review::Review, timestamp::Timestamp}};
+    ///
+    /// # tokio_test();
+    /// # #[tokio::main]
+    /// # async fn tokio_test() {
+    /// let conn = sqlx::sqlite::SqlitePoolOptions::new()
+    ///     .max_connections(1)
+    ///     .connect("sqlite::memory:")
+    ///     .await
+    ///     .unwrap();
+    /// # tomex::types::book::Book::init_table(&conn, None).await.unwrap();
+    /// # tomex::types::pace::Pace::init_table(&conn, None).await.unwrap();
+    /// # tomex::types::review_mood::ReviewMood::create_table(&conn).await.unwrap();
+    /// Review::init_table(&conn, None).await.unwrap();
+    /// # let book = tomex::types::book::Book::default();
+    /// # book.insert(&conn).await.unwrap();
+    /// let mut review = Review {
+    ///     book_id: book.id.clone(),
+    ///     ..Review::default()
+    /// };
+    /// review.insert(&conn).await.unwrap();
+    /// let new = Review {
+    ///     rating: Some(80),
+    ///     timestamp_created: Timestamp(chrono::Utc::now()),
+    ///     ..review.clone()
+    /// };
+    /// review.update(&conn, new).await.unwrap();
+    /// let updated = Review::get_by_id(&conn, &review.id).await.unwrap();
+    /// assert_eq!(updated.rating, Some(80));
+    /// assert_eq!(updated.timestamp_created, review.timestamp_created);
+    /// # }
+    /// ```
     pub timestamp_created: Timestamp,
     pub timestamp_updated: Timestamp,
     pub pace_id:           Option<Uuid>,
@@ -50,18 +105,240 @@ impl Queryable for Review {
         x.sort_by(|a, b| a.timestamp_updated.partial_cmp(&b.timestamp_updated).unwrap());
         return x;
     }
+
+    async fn sort_by_clap(x: Vec<Self>, matches: &clap::ArgMatches) -> Vec<Self> {
+        if matches.get_one::<String>("book").is_some() {
+            // Oldest first, to see how the opinion changed across re-reads
+            let mut x = x;
+            x.sort_by(|a, b| a.timestamp_created.cmp(&b.timestamp_created));
+            x
+        } else {
+            Self::sort_for_display(x).await
+        }
+    }
+
+    async fn annotations_by_clap(
+        conn: &sqlx::SqlitePool,
+        matches: &clap::ArgMatches,
+        xs: &[Self],
+    ) -> Result<HashMap<Uuid, String>> {
+        if !matches.contains_id("book") {
+            return Ok(HashMap::new());
+        }
+        let mut finished: HashMap<Uuid, Vec<Timestamp>> = HashMap::new();
+        for x in xs {
+            if finished.contains_key(&x.book_id) {
+                continue;
+            }
+            let mut timestamps = vec![];
+            for edition in Edition::get_all_for_book(conn, &x.book_id).await? {
+                timestamps.extend(
+                    Progress::get_all_for_edition(conn, &edition.id)
+                        .await?
+                        .into_iter()
+                        .filter(|x| x.pages_progress == PagesProgress::Finished)
+                        .map(|x| x.timestamp),
+                );
+            }
+            finished.insert(x.book_id.clone(), timestamps);
+        }
+        // The read a review is about is the last one finished before it was
+        // written
+        Ok(xs
+            .iter()
+            .filter_map(|x| {
+                let read = finished[&x.book_id]
+                    .iter()
+                    .filter(|y| **y <= x.timestamp_created)
+                    .max()?;
+                Some((x.id.clone(), format!(" read {}", read.0.format("%Y-%m-%d"))))
+            })
+            .collect())
+    }
+
+    fn filtered_by_clap(matches: &clap::ArgMatches) -> bool {
+        matches.get_one::<String>("book").is_some()
+    }
+
+    async fn get_all_by_clap_filtered(
+        conn: &sqlx::SqlitePool,
+        matches: &clap::ArgMatches,
+        include_deleted: bool,
+    ) -> Result<Vec<Self>> {
+        let reviews = match (matches.get_one::<String>("book"), include_deleted) {
+            (Some(title), false) => {
+                let book = Book::get_by_title_by_prompt(conn, title).await?;
+                Self::get_all_for_book(conn, &book.id).await?
+            }
+            (Some(title), true) => {
+                let book = Book::get_by_title_by_prompt(conn, title).await?;
+                let reviews = Self::get_all_including_deleted(conn).await?;
+                reviews
+                    .into_iter()
+                    .filter(|x| x.book_id == book.id)
+                    .collect()
+            }
+            (None, true) => Self::get_all_including_deleted(conn).await?,
+            (None, false) => Self::get_all(conn).await?,
+        };
+        Ok(reviews
+            .into_iter()
+            .map(|x| x.hide_by_clap(matches))
+            .collect())
+    }
+
+    async fn get_by_id_by_clap(
+        conn: &sqlx::SqlitePool,
+        id: &Uuid,
+        matches: &clap::ArgMatches,
+    ) -> Result<Self> {
+        Ok(Self::get_by_id(conn, id).await?.hide_by_clap(matches))
+    }
 }
 
+/// Shown instead of the content of a review containing spoilers
+pub const SPOILER_HIDDEN: &str = "[spoiler hidden]";
+
 impl Review {
+    /// Replace the content with [SPOILER_HIDDEN] if it contains spoilers
+    ///
+    /// ```
+    /// use tomex::types::{review::*, text::Text};
+    ///
+    /// let review = Review {
+    ///     content: Some(Text("Everyone dies".into())),
+    ///     spoiler: true,
+    ///     ..Review::default()
+    /// };
+    /// assert_eq!(review.hide_spoiler().content, Some(Text(SPOILER_HIDDEN.into())));
+    /// ```
+    pub fn hide_spoiler(self) -> Self {
+        match (&self.content, self.spoiler) {
+            (Some(_), true) => Self {
+                content: Some(Text(SPOILER_HIDDEN.to_string())),
+                ..self
+            },
+            _ => self,
+        }
+    }
+
+    /// Drop the private notes
+    pub fn hide_private_notes(self) -> Self {
+        Self {
+            private_notes: None,
+            ..self
+        }
+    }
+
+    /// Hide spoilers and private notes, unless `--show-spoilers` or
+    /// `--show-private` were given
+    fn hide_by_clap(self, matches: &clap::ArgMatches) -> Self {
+        let s = if matches.get_flag("show-spoilers") {
+            self
+        } else {
+            self.hide_spoiler()
+        };
+        if matches.get_flag("show-private") {
+            s
+        } else {
+            s.hide_private_notes()
+        }
+    }
+
+    /// Ratings given for individual aspects of the book
+    pub fn aspect_ratings(&self) -> Vec<(&'static str, u32)> {
+        [
+            ("Plot", self.plot_rating),
+            ("Characters", self.characters_rating),
+            ("Prose", self.prose_rating),
+            ("Pacing", self.pacing_rating),
+        ]
+        .into_iter()
+        .filter_map(|(aspect, rating)| rating.map(|x| (aspect, x)))
+        .collect()
+    }
+
+    /// Reviews written for individual aspects of the book
+    pub fn aspect_texts(&self) -> Vec<(&'static str, &Text)> {
+        [
+            ("Plot", &self.plot_text),
+            ("Characters", &self.characters_text),
+            ("Prose", &self.prose_text),
+            ("Pacing", &self.pacing_text),
+        ]
+        .into_iter()
+        .filter_map(|(aspect, text)| text.as_ref().map(|x| (aspect, x)))
+        .collect()
+    }
+
+    /// Prompt for the rating of one aspect, like the plot, and a review of it,
+    /// starting from the current ones. An empty review is stored as none
+    async fn update_aspect_by_prompt(
+        aspect: &str,
+        rating: &Option<u32>,
+        text: &Option<Text>,
+        conn: &sqlx::SqlitePool,
+    ) -> Result<(Option<u32>, Option<Text>)> {
+        let rating: Option<Rating> = PromptType::update_by_prompt_skippable(
+            rating,
+            &format!("What rating would you give this book's {aspect}? (0-100)"),
+            conn,
+        )
+        .await?;
+        let text =
+            inquire::Editor::new(&format!("Write a detailed review of this book's {aspect}:"))
+                .with_file_extension(".md")
+                .with_predefined_text(match text {
+                    Some(text) => &text.0,
+                    None => "",
+                })
+                .prompt_skippable()?
+                .filter(|x| !x.trim().is_empty())
+                .map(Text);
+        Ok((rating, text))
+    }
+
+    /// Average of the aspect ratings, if any were given
+    ///
+    /// ```
+    /// use tomex::types::review::Review;
+    ///
+    /// let review = Review {
+    ///     plot_rating: Some(80),
+    ///     prose_rating: Some(65),
+    ///     ..Review::default()
+    /// };
+    /// assert_eq!(review.aspect_average(), Some(73));
+    /// assert_eq!(Review::default().aspect_average(), None);
+    /// ```
+    pub fn aspect_average(&self) -> Option<u32> {
+        let ratings = self.aspect_ratings();
+        if ratings.is_empty() {
+            return None;
+        }
+        let sum: u32 = ratings.iter().map(|(_, x)| x).sum();
+        Some((sum as f64 / ratings.len() as f64).round() as u32)
+    }
+
+    /// Get all reviews of a book, oldest first
+    pub async fn get_all_for_book(conn: &sqlx::SqlitePool, book_id: &Uuid) -> Result<Vec<Self>> {
+        Ok(sqlx::query_as::<_, Self>(&format!(
+            "SELECT * FROM {} WHERE book_id = ?1 AND deleted = 0 ORDER BY timestamp_created;",
+            Self::TABLE_NAME
+        ))
+        .bind(book_id)
+        .fetch_all(conn)
+        .await?)
+    }
+
     pub async fn hydrate(&mut self, conn: &sqlx::SqlitePool) -> Result<()> {
-        self.hydrate_pace(conn).await?;
-        self.hydrate_moods(conn).await?;
+        (self.pace, self.moods) = tokio::try_join!(self.get_pace(conn), self.get_moods(conn))?;
         Ok(())
     }
 
     pub async fn get_pace(&self, conn: &sqlx::SqlitePool) -> Result<Option<Pace>> {
         match &self.pace_id {
-            Some(pace_id) => Ok(Some(Pace::get_by_id(conn, pace_id).await?)),
+            Some(pace_id) => Ok(Some(cache::get_by_id::<Pace>(conn, pace_id).await?)),
             None => Ok(None),
         }
     }
@@ -104,7 +381,7 @@ impl PromptType for Review {
         let recommend = Confirm::new("Would you recommend this book?")
             .with_default(true)
             .prompt_skippable()?;
-        let pace = Pace::query_by_prompt_skippable(conn).await?;
+        let pace = Pace::query_or_create_by_prompt_skippable(conn).await?;
         let pace_id = pace.clone().map(|x| x.id);
 
         Ok(Self {
@@ -113,6 +390,16 @@ impl PromptType for Review {
             rating,
             recommend,
             content: None,
+            spoiler: false,
+            private_notes: None,
+            plot_rating: None,
+            plot_text: None,
+            characters_rating: None,
+            characters_text: None,
+            prose_rating: None,
+            prose_text: None,
+            pacing_rating: None,
+            pacing_text: None,
             timestamp_created: Timestamp(chrono::Utc::now()),
             timestamp_updated: Timestamp(chrono::Utc::now()),
             pace_id,
@@ -158,20 +445,61 @@ impl PromptType for Review {
             })
             .prompt_skippable()?
             .map(Text);
+        let spoiler = match &content {
+            Some(_) => Confirm::new("Does the review contain spoilers?")
+                .with_default(s.spoiler)
+                .prompt_skippable()?
+                .unwrap_or(s.spoiler),
+            None => false,
+        };
+        let private_notes = inquire::Editor::new("Write private notes, never shared:")
+            .with_file_extension(".md")
+            .with_predefined_text(match &s.private_notes {
+                Some(private_notes) => &private_notes.0,
+                None => "",
+            })
+            .prompt_skippable()?
+            .filter(|x| !x.trim().is_empty())
+            .map(Text);
+
+        let (plot_rating, plot_text) =
+            Self::update_aspect_by_prompt("plot", &s.plot_rating, &s.plot_text, conn).await?;
+        let (characters_rating, characters_text) = Self::update_aspect_by_prompt(
+            "characters",
+            &s.characters_rating,
+            &s.characters_text,
+            conn,
+        )
+        .await?;
+        let (prose_rating, prose_text) =
+            Self::update_aspect_by_prompt("prose", &s.prose_rating, &s.prose_text, conn).await?;
+        let (pacing_rating, pacing_text) =
+            Self::update_aspect_by_prompt("pacing", &s.pacing_rating, &s.pacing_text, conn).await?;
 
         let moods = Mood::update_vec(&s.moods, conn, "Select moods for this edition:").await?;
 
-        if !inquire::Confirm::new("Update review?")
-            .with_default(true)
-            .prompt()?
-        {
-            anyhow::bail!("Aborted");
+        if !prompt_or_abort(|| {
+            inquire::Confirm::new("Update review?")
+                .with_default(true)
+                .prompt()
+        })? {
+            anyhow::bail!(crate::error::Error::Aborted("Aborted".into()));
         };
 
         let new = Self {
             rating,
             recommend,
             content,
+            spoiler,
+            private_notes,
+            plot_rating,
+            plot_text,
+            characters_rating,
+            characters_text,
+            prose_rating,
+            prose_text,
+            pacing_rating,
+            pacing_text,
             timestamp_updated: Timestamp(chrono::Utc::now()),
             pace_id,
             pace,
@@ -204,10 +532,7 @@ impl PromptType for Review {
 
 impl Display for Review {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let config = match config::Config::read_config() {
-            Ok(config) => config,
-            Err(_) => return Err(std::fmt::Error),
-        };
+        let config = config::Config::cached();
         if config.output_review.display_uuid {
             write!(f, "{} ({})", self.book_title, self.id)
         } else {
@@ -296,11 +621,37 @@ impl DisplayTerminal for Review {
         write!(f, "({})", s.id)?;
         Ok(())
     }
+
+    async fn info_card(
+        &self,
+        f: &mut String,
+        conn: &sqlx::SqlitePool,
+        config: &config::Config,
+    ) -> Result<()> {
+        DisplayTerminal::fmt(self, f, conn, config).await?;
+        if let Some(content) = &self.content {
+            write!(f, "\n\n{content}")?;
+        }
+        if let Some(average) = self.aspect_average() {
+            write!(f, "\n\nAspects:")?;
+            for (aspect, rating) in self.aspect_ratings() {
+                write!(f, "\n{aspect}: {rating}")?;
+            }
+            write!(f, "\nAverage: {average}")?;
+        }
+        for (aspect, text) in self.aspect_texts() {
+            write!(f, "\n\n{aspect}:\n{text}")?;
+        }
+        if let Some(private_notes) = &self.private_notes {
+            write!(f, "\n\nPrivate notes:\n{private_notes}")?;
+        }
+        Ok(())
+    }
 }
 
 impl CreateTable for Review {
-    async fn create_table(conn: &sqlx::SqlitePool) -> Result<()> {
-        sqlx::query(&format!(
+    fn ddl() -> String {
+        format!(
             r#"
             CREATE TABLE {} (
                 id TEXT PRIMARY KEY NOT NULL,
@@ -308,6 +659,16 @@ impl CreateTable for Review {
             	rating INT,
             	recommend BOOL,
             	content	TEXT,
+            	spoiler BOOL DEFAULT FALSE,
+            	private_notes TEXT,
+            	plot_rating INT,
+            	plot_text TEXT,
+            	characters_rating INT,
+            	characters_text TEXT,
+            	prose_rating INT,
+            	prose_text TEXT,
+            	pacing_rating INT,
+            	pacing_text TEXT,
             	timestamp_created INTEGER,
             	timestamp_updated INTEGER,
             	pace_id INT,
@@ -319,19 +680,43 @@ impl CreateTable for Review {
             Self::TABLE_NAME,
             Book::TABLE_NAME,
             Pace::TABLE_NAME
-        ))
-        .execute(conn)
-        .await?;
+        )
+    }
+
+    async fn create_table(
+        conn: &sqlx::SqlitePool,
+        _seed: Option<&config::SeedConfig>,
+    ) -> Result<()> {
+        sqlx::query(&Self::ddl()).execute(conn).await?;
         Ok(())
     }
+
+    async fn migrate_table(conn: &sqlx::SqlitePool) -> Result<()> {
+        Self::add_column(conn, "spoiler", "BOOL DEFAULT FALSE").await?;
+        Self::add_column(conn, "private_notes", "TEXT").await?;
+        Self::add_column(conn, "plot_rating", "INT").await?;
+        Self::add_column(conn, "plot_text", "TEXT").await?;
+        Self::add_column(conn, "characters_rating", "INT").await?;
+        Self::add_column(conn, "characters_text", "TEXT").await?;
+        Self::add_column(conn, "prose_rating", "INT").await?;
+        Self::add_column(conn, "prose_text", "TEXT").await?;
+        Self::add_column(conn, "pacing_rating", "INT").await?;
+        Self::add_column(conn, "pacing_text", "TEXT").await
+    }
 }
 
 impl Insertable for Review {
-    async fn insert(&self, conn: &sqlx::SqlitePool) -> Result<SqliteQueryResult> {
+    async fn insert_with(&self, conn: &mut sqlx::SqliteConnection) -> Result<SqliteQueryResult> {
         let result = sqlx::query(
             r#"
-            INSERT INTO reviews ( id, book_id, rating, recommend, content, timestamp_created, timestamp_updated, pace_id, deleted, book_title )
-            VALUES ( ?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10 )
+            INSERT INTO reviews (
+                id, book_id, rating, recommend, content, timestamp_created, timestamp_updated,
+                pace_id, deleted, book_title, spoiler, private_notes, plot_rating, plot_text,
+                characters_rating, characters_text, prose_rating, prose_text, pacing_rating,
+                pacing_text )
+            VALUES (
+                ?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18,
+                ?19, ?20 )
             "#,
         )
         .bind(&self.id)
@@ -344,10 +729,22 @@ impl Insertable for Review {
         .bind(&self.pace_id)
         .bind(self.deleted)
         .bind(&self.book_title)
-        .execute(conn)
+        .bind(self.spoiler)
+        .bind(&self.private_notes)
+        .bind(self.plot_rating)
+        .bind(&self.plot_text)
+        .bind(self.characters_rating)
+        .bind(&self.characters_text)
+        .bind(self.prose_rating)
+        .bind(&self.prose_text)
+        .bind(self.pacing_rating)
+        .bind(&self.pacing_text)
+        .execute(&mut *conn)
         .await?;
 
-        ReviewMood::update(conn, self, &None, &self.moods).await?;
+        for mood in self.moods.iter().flatten() {
+            ReviewMood::insert(&mut *conn, self, mood).await?;
+        }
 
         Ok(result)
     }
@@ -363,11 +760,20 @@ impl Updateable for Review {
                 rating = ?3,
                 recommend = ?4,
                 content = ?5,
-                timestamp_created = ?6,
-                timestamp_updated = ?7,
-                pace_id = ?8,
-                deleted = ?9,
-                book_title = ?10
+                timestamp_updated = ?6,
+                pace_id = ?7,
+                deleted = ?8,
+                book_title = ?9,
+                spoiler = ?10,
+                private_notes = ?11,
+                plot_rating = ?12,
+                plot_text = ?13,
+                characters_rating = ?14,
+                characters_text = ?15,
+                prose_rating = ?16,
+                prose_text = ?17,
+                pacing_rating = ?18,
+                pacing_text = ?19
             WHERE
                 id = ?1;
             "#,
@@ -378,11 +784,20 @@ impl Updateable for Review {
         .bind(new.rating)
         .bind(new.recommend)
         .bind(&new.content)
-        .bind(&new.timestamp_created)
         .bind(&new.timestamp_updated)
         .bind(&new.pace_id)
         .bind(new.deleted)
         .bind(&new.book_title)
+        .bind(new.spoiler)
+        .bind(&new.private_notes)
+        .bind(new.plot_rating)
+        .bind(&new.plot_text)
+        .bind(new.characters_rating)
+        .bind(&new.characters_text)
+        .bind(new.prose_rating)
+        .bind(&new.prose_text)
+        .bind(new.pacing_rating)
+        .bind(&new.pacing_text)
         .execute(conn)
         .await?)
     }
@@ -397,6 +812,16 @@ impl FromRow<'_, SqliteRow> for Review {
             rating:            row.try_get("rating")?,
             recommend:         row.try_get("recommend")?,
             content:           row.try_get("content")?,
+            spoiler:           row.try_get("spoiler")?,
+            private_notes:     row.try_get("private_notes")?,
+            plot_rating:       row.try_get("plot_rating")?,
+            plot_text:         row.try_get("plot_text")?,
+            characters_rating: row.try_get("characters_rating")?,
+            characters_text:   row.try_get("characters_text")?,
+            prose_rating:      row.try_get("prose_rating")?,
+            prose_text:        row.try_get("prose_text")?,
+            pacing_rating:     row.try_get("pacing_rating")?,
+            pacing_text:       row.try_get("pacing_text")?,
             timestamp_created: row.try_get("timestamp_created")?,
             timestamp_updated: row.try_get("timestamp_updated")?,
             pace_id:           row.try_get("pace_id")?,
@@ -1,4 +1,5 @@
 use anyhow::Result;
+use inquire::validator::Validation;
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 use std::fmt::{Display, Write};
@@ -6,7 +7,7 @@ use std::fmt::{Display, Write};
 use crate::{
     config::{self, Styleable},
     traits::*,
-    types::uuid::Uuid,
+    types::{book::Book, uuid::Uuid},
 };
 use derives::*;
 
@@ -40,6 +41,215 @@ impl Queryable for Series {
     }
 }
 
+impl Series {
+    /// The (non-deleted) books in this series, by their index
+    pub async fn get_books(&self, conn: &sqlx::SqlitePool) -> Result<Vec<Book>> {
+        Book::get_all_by_series(conn, Some(&self.id)).await
+    }
+
+    /// The series called `name`, ignoring case, prompting for which one if
+    /// several are
+    pub async fn get_by_name(conn: &sqlx::SqlitePool, name: &str) -> Result<Self> {
+        let mut series = sqlx::query_as::<_, Self>(&format!(
+            "SELECT * FROM {} WHERE name = ?1 COLLATE NOCASE AND deleted = 0;",
+            Self::TABLE_NAME
+        ))
+        .bind(name)
+        .fetch_all(conn)
+        .await?;
+        match series.len() {
+            0 => Err(crate::error::Error::NotFound(format!("No series called {name}")).into()),
+            1 => Ok(series.remove(0)),
+            _ => {
+                let message = format!("Which {name}?");
+                let prompt = inquire::Select::new(&message, series);
+                prompt_or_abort(|| prompt.clone().prompt())
+            }
+        }
+    }
+
+    /// Move the books of `other` into this series and remove `other`, in one
+    /// transaction. `indices` gives books new indices first, like ones another
+    /// book of this series already has. Returns how many books were moved
+    ///
+    /// ```
+    /// use tomex::traits::*;
+    /// use tomex::types::{book::Book, series::Series, text::Text, uuid::Uuid};
+    ///
+    /// # tokio_test();
+    /// # #[tokio::main]
+    /// # async fn tokio_test() {
+    /// let conn = sqlx::sqlite::SqlitePoolOptions::new()
+    ///     .max_connections(1)
+    ///     .connect("sqlite::memory:")
+    ///     .await
+    ///     .unwrap();
+    /// Book::init_table(&conn, None).await.unwrap();
+    /// Series::init_table(&conn, None).await.unwrap();
+    /// let new_uuid = || Uuid(uuid::Uuid::new_v4());
+    /// let keep = Series { id: new_uuid(), name: Text("Dune".into()), deleted: false };
+    /// let other = Series { id: new_uuid(), name: Text("Dune Chronicles".into()), deleted: false };
+    /// keep.insert(&conn).await.unwrap();
+    /// other.insert(&conn).await.unwrap();
+    /// let book = |title: &str, series: &Series, index| Book {
+    ///     id: new_uuid(),
+    ///     title: Text(title.into()),
+    ///     series_id: Some(series.id.clone()),
+    ///     series_index: Some(index),
+    ///     ..Book::default()
+    /// };
+    /// let dune = book("Dune", &keep, 1);
+    /// let messiah = book("Dune Messiah", &other, 2);
+    /// let children = book("Children of Dune", &other, 1);
+    /// for x in [&dune, &messiah, &children] {
+    ///     x.insert(&conn).await.unwrap();
+    /// }
+    ///
+    /// let indices = [(children.id.clone(), Some(3))];
+    /// assert_eq!(keep.merge(&conn, &other, &indices).await.unwrap(), 2);
+    /// let titles: Vec<String> =
+    ///     keep.get_books(&conn).await.unwrap().into_iter().map(|x| x.title.0).collect();
+    /// assert_eq!(titles, ["Dune", "Dune Messiah", "Children of Dune"]);
+    /// assert!(!Series::exists(&conn, &other.id).await.unwrap());
+    /// # }
+    /// ```
+    pub async fn merge(
+        &self,
+        conn: &sqlx::SqlitePool,
+        other: &Self,
+        indices: &[(Uuid, Option<u32>)],
+    ) -> Result<u64> {
+        let mut tx = conn.begin().await?;
+        for (book_id, index) in indices {
+            sqlx::query(&format!(
+                "UPDATE {} SET series_index = ?2 WHERE id = ?1;",
+                Book::TABLE_NAME
+            ))
+            .bind(book_id)
+            .bind(index)
+            .execute(&mut tx)
+            .await?;
+        }
+        let moved = sqlx::query(&format!(
+            "UPDATE {} SET series_id = ?1 WHERE series_id = ?2;",
+            Book::TABLE_NAME
+        ))
+        .bind(&self.id)
+        .bind(&other.id)
+        .execute(&mut tx)
+        .await?
+        .rows_affected();
+        sqlx::query(&format!(
+            "UPDATE {} SET deleted = 1 WHERE id = ?1;",
+            Self::TABLE_NAME
+        ))
+        .bind(&other.id)
+        .execute(&mut tx)
+        .await?;
+        tx.commit().await?;
+        Ok(moved)
+    }
+
+    /// Prompt for a series to keep and one to merge into it, asking for new
+    /// indices of books whose index is taken in the kept series
+    pub async fn merge_by_prompt(conn: &sqlx::SqlitePool) -> Result<()> {
+        let all = Self::sort_for_display(Self::get_all(conn).await?).await;
+        let prompt = inquire::Select::new("Select the series to keep:", all.clone());
+        let keep = prompt_or_abort(|| prompt.clone().prompt())?;
+        let others: Vec<Self> = all.into_iter().filter(|x| x.id != keep.id).collect();
+        if others.is_empty() {
+            return Err(crate::error::Error::NotFound("No other series to merge".into()).into());
+        }
+        let message = format!("Select the series to merge into {keep}:");
+        let prompt = inquire::Select::new(&message, others);
+        let other = prompt_or_abort(|| prompt.clone().prompt())?;
+
+        let mut taken: Vec<u32> = keep
+            .get_books(conn)
+            .await?
+            .into_iter()
+            .filter_map(|x| x.series_index)
+            .collect();
+        let mut indices = Vec::new();
+        for book in other.get_books(conn).await? {
+            let Some(index) = book.series_index else {
+                continue;
+            };
+            if !taken.contains(&index) {
+                taken.push(index);
+                continue;
+            }
+            let free = (1..).find(|x| !taken.contains(x)).expect("Unbounded range");
+            let validator_taken = taken.clone();
+            let message = format!("{book} is #{index} in both series, which index should it get?");
+            let prompt = inquire::CustomType::<u32>::new(&message)
+                .with_default(free)
+                .with_help_message("Esc leaves it without an index")
+                .with_validator(move |x: &u32| match validator_taken.contains(x) {
+                    true => Ok(Validation::Invalid(format!("#{x} is taken").into())),
+                    false => Ok(Validation::Valid),
+                });
+            let new = prompt.prompt_skippable()?;
+            taken.extend(new);
+            indices.push((book.id, new));
+        }
+
+        let message = format!("Merge {other} into {keep}?");
+        let confirm = inquire::Confirm::new(&message).with_default(true);
+        if !prompt_or_abort(|| confirm.clone().prompt())? {
+            return Err(crate::error::Error::Aborted("Not merged".into()).into());
+        }
+        let moved = keep.merge(conn, &other, &indices).await?;
+        println!("Moved {moved} books from {other} into {keep}");
+        Ok(())
+    }
+
+    /// Prompt for books to put in this series and each one's index. Returns
+    /// how many books were assigned
+    pub async fn assign_books_by_prompt(&self, conn: &sqlx::SqlitePool) -> Result<usize> {
+        let mut taken: Vec<u32> = self
+            .get_books(conn)
+            .await?
+            .into_iter()
+            .filter_map(|x| x.series_index)
+            .collect();
+        let books: Vec<Book> = Book::sort_for_display(Book::get_all(conn).await?)
+            .await
+            .into_iter()
+            .filter(|x| x.series_id.as_ref() != Some(&self.id))
+            .collect();
+        if books.is_empty() {
+            return Ok(0);
+        }
+        let message = format!("Select books to add to {self}:");
+        let books = inquire::MultiSelect::new(&message, books)
+            .prompt_skippable()?
+            .unwrap_or_default();
+        for mut book in books.iter().cloned() {
+            let free = (1..).find(|x| !taken.contains(x)).expect("Unbounded range");
+            let validator_taken = taken.clone();
+            let message = format!("What is the position of {book} in the series?");
+            let prompt = inquire::CustomType::<u32>::new(&message)
+                .with_default(free)
+                .with_help_message("Esc leaves it without an index")
+                .with_validator(move |x: &u32| match validator_taken.contains(x) {
+                    true => Ok(Validation::Invalid(format!("#{x} is taken").into())),
+                    false => Ok(Validation::Valid),
+                });
+            let index = prompt.prompt_skippable()?;
+            taken.extend(index);
+            book.hydrate(conn).await?;
+            let new = Book {
+                series_id: Some(self.id.clone()),
+                series_index: index,
+                ..book.clone()
+            };
+            book.update(conn, new).await?;
+        }
+        Ok(books.len())
+    }
+}
+
 impl PromptType for Series {
     async fn create_by_prompt(
         _prompt: &str,
@@ -93,10 +303,7 @@ impl PromptType for Series {
 
 impl Display for Series {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let config = match config::Config::read_config() {
-            Ok(config) => config,
-            Err(_) => return Err(std::fmt::Error),
-        };
+        let config = config::Config::cached();
         write!(
             f,
             "{}",
@@ -133,8 +340,8 @@ impl DisplayTerminal for Series {
 }
 
 impl CreateTable for Series {
-    async fn create_table(conn: &sqlx::SqlitePool) -> Result<()> {
-        sqlx::query(&format!(
+    fn ddl() -> String {
+        format!(
             r#"
             CREATE TABLE IF NOT EXISTS {} (
                 id TEXT PRIMARY KEY NOT NULL,
@@ -143,22 +350,24 @@ impl CreateTable for Series {
             );
             "#,
             Self::TABLE_NAME,
-        ))
-        .execute(conn)
-        .await?;
+        )
+    }
+
+    async fn create_table(
+        conn: &sqlx::SqlitePool,
+        _seed: Option<&config::SeedConfig>,
+    ) -> Result<()> {
+        sqlx::query(&Self::ddl()).execute(conn).await?;
 
         Ok(())
     }
 }
 
 impl Insertable for Series {
-    async fn insert(
+    async fn insert_with(
         &self,
-        conn: &sqlx::SqlitePool,
-    ) -> anyhow::Result<sqlx::sqlite::SqliteQueryResult>
-    where
-        Self: Sized,
-    {
+        conn: &mut sqlx::SqliteConnection,
+    ) -> anyhow::Result<sqlx::sqlite::SqliteQueryResult> {
         Ok(sqlx::query(&format!(
             r#"
                     INSERT INTO {} ( id, name, deleted )
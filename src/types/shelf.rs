@@ -0,0 +1,296 @@
+use anyhow::Result;
+use inquire::{MultiSelect, Select};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use std::fmt::{Display, Write};
+
+use crate::{
+    config::{self, Styleable},
+    traits::*,
+    types::{text::Text, uuid::Uuid},
+};
+use derives::*;
+
+/// Shelves a book can only be on one of at a time, like on Goodreads
+pub const EXCLUSIVE_SHELVES: [&str; 3] = ["read", "currently-reading", "to-read"];
+
+#[derive(
+    Default, Debug, Clone, PartialEq, Eq, FromRow, Id, CRUD, Removeable, Serialize, Deserialize,
+)]
+pub struct Shelf {
+    pub id:        Uuid,
+    pub name:      Text,
+    /// A book is on at most one exclusive shelf
+    pub exclusive: bool,
+    pub deleted:   bool,
+}
+
+impl Names for Shelf {
+    const NAME_PLURAL: &'static str = "shelves";
+    const NAME_SINGULAR: &'static str = "shelf";
+}
+
+impl Queryable for Shelf {
+    async fn sort_for_display(x: Vec<Self>) -> Vec<Self> {
+        let mut x = x.clone();
+        x.sort_by(|a, b| (!a.exclusive, &a.name.0).cmp(&(!b.exclusive, &b.name.0)));
+        x
+    }
+}
+
+impl Shelf {
+    /// Prompt for the exclusive shelf and any other shelves of a book,
+    /// skipping a prompt keeps what's in `current`
+    pub async fn update_for_book(
+        current: &Option<Vec<Self>>,
+        conn: &sqlx::SqlitePool,
+    ) -> Result<Option<Vec<Self>>> {
+        let current = current.clone().unwrap_or_default();
+        let (exclusive, other): (Vec<Self>, Vec<Self>) = Self::get_all(conn)
+            .await?
+            .into_iter()
+            .partition(|x| x.exclusive);
+
+        let current_exclusive = current.iter().find(|x| x.exclusive).cloned();
+        let cursor = exclusive
+            .iter()
+            .position(|x| Some(x) == current_exclusive.as_ref())
+            .unwrap_or(0);
+        let exclusive = match exclusive.is_empty() {
+            true => current_exclusive,
+            false => Select::new("Which shelf is this book on?", exclusive)
+                .with_starting_cursor(cursor)
+                .prompt_skippable()?
+                .or(current_exclusive),
+        };
+
+        let current_other: Vec<Self> = current.into_iter().filter(|x| !x.exclusive).collect();
+        let selected = other
+            .iter()
+            .enumerate()
+            .filter(|(_, x)| current_other.contains(x))
+            .map(|(i, _)| i)
+            .collect::<Vec<usize>>();
+        let other = match other.is_empty() {
+            true => current_other,
+            false => MultiSelect::new("Select other shelves for this book:", other)
+                .with_default(&selected)
+                .prompt_skippable()?
+                .unwrap_or(current_other),
+        };
+
+        let shelves: Vec<Self> = exclusive.into_iter().chain(other).collect();
+        Ok(if shelves.is_empty() {
+            None
+        } else {
+            Some(shelves)
+        })
+    }
+}
+
+impl PromptType for Shelf {
+    async fn create_by_prompt(
+        _prompt: &str,
+        _initial_value: Option<&Self>,
+        conn: &sqlx::SqlitePool,
+    ) -> Result<Self> {
+        let id = Uuid(uuid::Uuid::new_v4());
+        let name = Text::create_by_prompt("What is the name of the shelf?", None, conn).await?;
+        let exclusive = prompt_or_abort(|| {
+            inquire::Confirm::new("Is the shelf exclusive, like read or to-read?")
+                .with_default(false)
+                .prompt()
+        })?;
+        Ok(Self {
+            id,
+            name: Self::normalize_name(name),
+            exclusive,
+            deleted: false,
+        })
+    }
+
+    async fn update_by_prompt(&self, _prompt: &str, conn: &sqlx::SqlitePool) -> anyhow::Result<Self>
+    where
+        Self: Display,
+    {
+        let name = self
+            .name
+            .update_by_prompt("Change shelf name to:", conn)
+            .await?;
+        let exclusive = prompt_or_abort(|| {
+            inquire::Confirm::new("Is the shelf exclusive, like read or to-read?")
+                .with_default(self.exclusive)
+                .prompt()
+        })?;
+        Ok(Self {
+            name: Self::normalize_name(name),
+            exclusive,
+            ..self.clone()
+        })
+    }
+
+    async fn create_by_prompt_skippable(
+        _prompt: &str,
+        _initial_value: Option<&Self>,
+        _conn: &sqlx::SqlitePool,
+    ) -> Result<Option<Self>> {
+        unreachable!("Can't skip creation of this type")
+    }
+
+    async fn update_by_prompt_skippable(
+        _s: &Option<Self>,
+        _prompt: &str,
+        _conn: &sqlx::SqlitePool,
+    ) -> anyhow::Result<Option<Self>>
+    where
+        Self: Display,
+    {
+        unreachable!("Can't skip updating this type")
+    }
+}
+
+impl ReferenceType for Shelf {
+    fn name(&self) -> &Text {
+        &self.name
+    }
+
+    fn from_name(name: Text) -> Self {
+        Self {
+            id: Uuid(uuid::Uuid::new_v4()),
+            name,
+            exclusive: false,
+            deleted: false,
+        }
+    }
+}
+
+impl Display for Shelf {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let config = config::Config::cached();
+        let name = self
+            .name
+            .to_string()
+            .style(&config.output_shelf.style_content);
+        if config.output_shelf.display_uuid {
+            write!(f, "{} ({})", name, self.id)
+        } else {
+            write!(f, "{}", name)
+        }
+    }
+}
+impl DisplayTerminal for Shelf {
+    async fn fmt(
+        &self,
+        f: &mut String,
+        _conn: &sqlx::SqlitePool,
+        config: &config::Config,
+    ) -> Result<()> {
+        let name = self
+            .name
+            .to_string()
+            .style(&config.output_shelf.style_content);
+        if config.output_shelf.display_uuid {
+            write!(f, "{} ({})", name, self.id)?;
+        } else {
+            write!(f, "{}", name)?;
+        }
+        Ok(())
+    }
+}
+
+impl CreateTable for Shelf {
+    fn ddl() -> String {
+        format!(
+            r#"
+            CREATE TABLE IF NOT EXISTS {} (
+                id TEXT PRIMARY KEY NOT NULL,
+                name TEXT NOT NULL,
+                exclusive BOOL DEFAULT FALSE,
+                deleted BOOL DEFAULT FALSE
+            );
+            "#,
+            Self::TABLE_NAME
+        )
+    }
+
+    async fn create_table(
+        conn: &sqlx::SqlitePool,
+        seed: Option<&config::SeedConfig>,
+    ) -> Result<()> {
+        sqlx::query(&Self::ddl()).execute(conn).await?;
+        let Some(seed) = seed else {
+            return Ok(());
+        };
+        let default_shelves = vec![
+            ("read", uuid::uuid!("7d2f62b9-b882-4ca9-84dd-e24a4d48abfa")),
+            (
+                "currently-reading",
+                uuid::uuid!("da8e24ef-4003-4df2-b93d-1946420975bd"),
+            ),
+            (
+                "to-read",
+                uuid::uuid!("114ecf1c-1b60-4647-ba94-8f60f7a47e2b"),
+            ),
+        ];
+        for (shelf, uuid) in config::SeedConfig::resolve(seed.shelves.clone(), default_shelves) {
+            Self::insert(
+                &Self {
+                    id:        Uuid(uuid),
+                    exclusive: EXCLUSIVE_SHELVES.contains(&shelf.as_str()),
+                    name:      Text(shelf),
+                    deleted:   false,
+                },
+                conn,
+            )
+            .await?;
+        }
+        Ok(())
+    }
+}
+
+impl Insertable for Shelf {
+    async fn insert_with(
+        &self,
+        conn: &mut sqlx::SqliteConnection,
+    ) -> anyhow::Result<sqlx::sqlite::SqliteQueryResult> {
+        Ok(sqlx::query(&format!(
+            r#"
+                    INSERT INTO {} ( id, name, exclusive, deleted )
+                    VALUES ( ?1, ?2, ?3, ?4 )
+                    "#,
+            Self::TABLE_NAME
+        ))
+        .bind(&self.id)
+        .bind(&self.name)
+        .bind(self.exclusive)
+        .bind(self.deleted)
+        .execute(conn)
+        .await?)
+    }
+}
+impl Updateable for Shelf {
+    async fn update(
+        &mut self,
+        conn: &sqlx::SqlitePool,
+        new: Self,
+    ) -> Result<sqlx::sqlite::SqliteQueryResult> {
+        Ok(sqlx::query(&format!(
+            r#"
+            UPDATE {}
+            SET
+                name = ?2,
+                exclusive = ?3,
+                deleted = ?4
+            WHERE
+                id = ?1;
+            "#,
+            Self::TABLE_NAME
+        ))
+        .bind(&self.id)
+        .bind(&new.name)
+        .bind(new.exclusive)
+        .bind(new.deleted)
+        .execute(conn)
+        .await?)
+    }
+}
@@ -3,7 +3,10 @@ use std::fmt::Display;
 
 use inquire::validator::StringValidator;
 
-use crate::traits::PromptType;
+use crate::{
+    config,
+    traits::{PromptType, prompt_or_abort},
+};
 
 #[derive(Default, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Text(pub String);
@@ -14,6 +17,53 @@ impl Display for Text {
     }
 }
 
+impl Text {
+    /// Trimmed, and with runs of whitespace replaced by a single space if
+    /// `collapse_whitespace`
+    ///
+    /// ```
+    /// use tomex::types::text::Text;
+    ///
+    /// let text = Text("  Ursula K.  Le Guin\n".into());
+    /// assert_eq!(text.normalized(false).0, "Ursula K.  Le Guin");
+    /// assert_eq!(text.normalized(true).0, "Ursula K. Le Guin");
+    /// ```
+    pub fn normalized(&self, collapse_whitespace: bool) -> Self {
+        if collapse_whitespace {
+            Self(self.0.split_whitespace().collect::<Vec<&str>>().join(" "))
+        } else {
+            Self(self.0.trim().to_string())
+        }
+    }
+
+    /// Every word starting with an uppercase letter, the rest is left alone
+    ///
+    /// ```
+    /// use tomex::types::text::Text;
+    ///
+    /// assert_eq!(Text("cozy mystery".into()).title_case().0, "Cozy Mystery");
+    /// assert_eq!(Text("LitRPG".into()).title_case().0, "LitRPG");
+    /// ```
+    pub fn title_case(&self) -> Self {
+        let mut s = String::with_capacity(self.0.len());
+        let mut word_start = true;
+        for c in self.0.chars() {
+            if word_start {
+                s.extend(c.to_uppercase());
+            } else {
+                s.push(c);
+            }
+            word_start = c.is_whitespace() || c == '-';
+        }
+        Self(s)
+    }
+
+    /// Normalized according to the config
+    fn normalized_by_config(text: String) -> Self {
+        Self(text).normalized(config::Config::cached().text.collapse_whitespace)
+    }
+}
+
 impl sqlx::Type<sqlx::Sqlite> for Text {
     fn type_info() -> sqlx::sqlite::SqliteTypeInfo {
         <&String as sqlx::Type<sqlx::Sqlite>>::type_info()
@@ -46,7 +96,9 @@ impl PromptType for Text {
         if let Some(s) = initial_value {
             prompt = prompt.with_initial_value(&s.0);
         }
-        Ok(Text(prompt.prompt()?))
+        Ok(Self::normalized_by_config(prompt_or_abort(|| {
+            prompt.clone().prompt()
+        })?))
     }
 
     async fn create_by_prompt_skippable(
@@ -59,7 +111,7 @@ impl PromptType for Text {
             prompt = prompt.with_initial_value(&s.0);
         }
         match prompt.prompt_skippable()? {
-            Some(text) => Ok(Some(Text(text))),
+            Some(text) => Ok(Some(Self::normalized_by_config(text))),
             None => Ok(None),
         }
     }
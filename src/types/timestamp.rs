@@ -7,29 +7,152 @@ use crossterm::style::Stylize;
 use crate::{
     config::{self, Styleable},
     default_colors::COLOR_DIMMED,
-    traits::PromptType,
+    traits::{Names, PromptType, prompt_or_abort},
+    types::{author::Author, book::Book, edition::Edition},
 };
 
-#[derive(Default, Debug, Clone, PartialEq, Eq, Serialize, Deserialize, PartialOrd, Ord)]
+/// How a missing [OptionalTimestamp] is stored, so that 0 can be the Unix
+/// epoch
+pub const NONE_SENTINEL: i64 = i64::MIN;
+
+/// A point in time, serialized as ISO-8601 in UTC independent of how it's
+/// displayed, so backups stay stable
+///
+/// ```
+/// use tomex::types::timestamp::{OptionalTimestamp, Timestamp};
+///
+/// let ts: Timestamp = serde_json::from_str(r#""2023-11-14T12:30:00.5+01:00""#).unwrap();
+/// let json = serde_json::to_string(&ts).unwrap();
+/// assert_eq!(json, r#""2023-11-14T11:30:00.500Z""#);
+/// let again: Timestamp = serde_json::from_str(&json).unwrap();
+/// assert_eq!(again, ts);
+/// assert_eq!(serde_json::to_string(&again).unwrap(), json);
+///
+/// let none = serde_json::to_string(&OptionalTimestamp(None)).unwrap();
+/// assert_eq!(none, "null");
+/// let some = serde_json::to_string(&OptionalTimestamp(Some(ts))).unwrap();
+/// assert_eq!(some, json);
+/// ```
+#[derive(Default, Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Timestamp(pub chrono::DateTime<chrono::Utc>);
 
-impl Display for Timestamp {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl Serialize for Timestamp {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.0.to_rfc3339_opts(chrono::SecondsFormat::AutoSi, true))
+    }
+}
+
+impl<'de> Deserialize<'de> for Timestamp {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        DateTime::parse_from_rfc3339(&s)
+            .map(|x| Self(x.with_timezone(&Utc)))
+            .or_else(|_| s.parse::<DateTime<Utc>>().map(Self))
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+impl Timestamp {
+    /// Parse dates the way they show up in the wild, falling back to the
+    /// month or only the year if there's nothing more precise
+    ///
+    /// ```
+    /// use tomex::types::timestamp::Timestamp;
+    ///
+    /// let date = |s| Timestamp::parse_forgiving(s).map(|x| x.0.date_naive().to_string());
+    /// assert_eq!(date("1998?").as_deref(), Some("1998-01-01"));
+    /// assert_eq!(date("[c1998]").as_deref(), Some("1998-01-01"));
+    /// assert_eq!(date("Jan 1998").as_deref(), Some("1998-01-01"));
+    /// assert_eq!(date("September, 2004").as_deref(), Some("2004-09-01"));
+    /// assert_eq!(date("unknown"), None);
+    /// ```
+    pub fn parse_forgiving(s: &str) -> Option<Self> {
+        if let Ok(ts) = dateparser::parse(s) {
+            return Some(Self(ts));
+        }
+        let cleaned: String = s
+            .chars()
+            .filter(|c| !matches!(c, '?' | '[' | ']' | ',' | '.'))
+            .collect();
+        let cleaned = cleaned.split_whitespace().collect::<Vec<&str>>().join(" ");
+        let midnight = |date: chrono::NaiveDate| {
+            date.and_hms_opt(0, 0, 0)
+                .map(|x| Self(DateTime::from_naive_utc_and_offset(x, Utc)))
+        };
+        for format in ["%d %B %Y", "%d %b %Y"] {
+            if let Ok(date) = chrono::NaiveDate::parse_from_str(&format!("1 {cleaned}"), format) {
+                return midnight(date);
+            }
+        }
+        // The first thing that looks like a year, e.g. in "c1998" or "1998-99"
+        let digits: Vec<char> = cleaned.chars().collect();
+        digits
+            .windows(4)
+            .enumerate()
+            .filter(|(i, w)| {
+                w.iter().all(|c| c.is_ascii_digit())
+                    && !digits.get(i + 4).is_some_and(|c| c.is_ascii_digit())
+                    && !(*i > 0 && digits[i - 1].is_ascii_digit())
+            })
+            .find_map(|(_, w)| w.iter().collect::<String>().parse::<i32>().ok())
+            .and_then(|year| chrono::NaiveDate::from_ymd_opt(year, 1, 1))
+            .and_then(midnight)
+    }
+
+    /// Describe relative to now, in the future tense if it hasn't happened yet
+    ///
+    /// ```
+    /// use chrono::{Duration, Utc};
+    /// use tomex::{config::TimestampAccuracy, types::timestamp::Timestamp};
+    ///
+    /// let past = Timestamp(Utc::now() - Duration::days(90));
+    /// assert_eq!(past.humanize(TimestampAccuracy::Rough), "3 months ago");
+    /// let future = Timestamp(Utc::now() + Duration::days(91));
+    /// assert_eq!(future.humanize(TimestampAccuracy::Rough), "in 3 months");
+    /// ```
+    pub fn humanize(&self, accuracy: config::TimestampAccuracy) -> String {
         use chrono_humanize::{Accuracy, HumanTime, Tense};
-        let config = match config::Config::read_config() {
-            Ok(config) => config,
-            Err(_) => return Err(std::fmt::Error),
+        let accuracy = match accuracy {
+            config::TimestampAccuracy::Rough => Accuracy::Rough,
+            config::TimestampAccuracy::Precise => Accuracy::Precise,
+        };
+        let tense = if self.0 > Utc::now() {
+            Tense::Future
+        } else {
+            Tense::Past
         };
-        let ht = HumanTime::from(self.0);
-        let s = ht.to_text_en(Accuracy::Rough, Tense::Past);
+        HumanTime::from(self.0).to_text_en(accuracy, tense)
+    }
+}
+
+impl Display for Timestamp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let config = config::Config::cached();
+        let s = self.humanize(config.timestamp_accuracy);
         let s = s.style(&config.output_timestamp.style_content);
         write!(f, "{s}")
     }
 }
 
-#[derive(Default, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+/// Serialized like [Timestamp], or `null`
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
 pub struct OptionalTimestamp(pub Option<Timestamp>);
 
+impl Serialize for OptionalTimestamp {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match &self.0 {
+            Some(ts) => serializer.serialize_some(ts),
+            None => serializer.serialize_none(),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for OptionalTimestamp {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Option::<Timestamp>::deserialize(deserializer).map(Self)
+    }
+}
+
 impl Display for OptionalTimestamp {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
@@ -64,11 +187,16 @@ impl PromptType for Timestamp {
         const OPTION_TIMESTAMP: &'static str = "Timestamp";
         let options: Vec<&str> = vec![OPTION_DATEPICKER, OPTION_TIMESTAMP];
 
-        let ans: Result<&str, inquire::InquireError> =
-            inquire::Select::new("How would you like to input the timestamp?", options).prompt();
+        let ans = prompt_or_abort(|| {
+            inquire::Select::new(
+                "How would you like to input the timestamp?",
+                options.clone(),
+            )
+            .prompt()
+        })?;
 
         match ans {
-            Ok(OPTION_DATEPICKER) => {
+            OPTION_DATEPICKER => {
                 let mut prompt = inquire::DateSelect::new(prompt);
                 if let Some(s) = initial_value {
                     prompt = inquire::DateSelect {
@@ -78,13 +206,13 @@ impl PromptType for Timestamp {
                 }
                 Ok(Timestamp(chrono::DateTime::from_utc(
                     chrono::NaiveDateTime::new(
-                        prompt.prompt()?,
+                        prompt_or_abort(|| prompt.clone().prompt())?,
                         chrono::NaiveTime::from_hms_opt(12, 0, 0).unwrap(),
                     ),
                     chrono::Utc,
                 )))
             }
-            Ok(OPTION_TIMESTAMP) => {
+            OPTION_TIMESTAMP => {
                 fn prompt_for_timestamp() -> DateTime<Utc> {
                     loop {
                         let prompt = match inquire::Text::new("Timestamp:").prompt() {
@@ -109,8 +237,7 @@ impl PromptType for Timestamp {
                 let timestamp = prompt_for_timestamp();
                 Ok(Timestamp(timestamp))
             }
-            Ok(_) => unreachable!("Unexpected response"),
-            Err(_) => Err(anyhow::anyhow!("Error getting progress")),
+            _ => unreachable!("Unexpected response"),
         }
     }
 
@@ -178,7 +305,7 @@ impl<'q> sqlx::Encode<'q, sqlx::Sqlite> for OptionalTimestamp {
     ) -> sqlx::encode::IsNull {
         args.push(sqlx::sqlite::SqliteArgumentValue::Int64(
             match self.0.clone() {
-                None => 0_i64,
+                None => NONE_SENTINEL,
                 Some(ts) => ts.0.timestamp_millis(),
             },
         ));
@@ -205,19 +332,37 @@ where
         }
     }
 }
+/// Missing values are stored as [NONE_SENTINEL], or `NULL` in columns added
+/// after a row was written
+///
+/// ```
+/// use chrono::TimeZone;
+/// use tomex::types::timestamp::{OptionalTimestamp, Timestamp};
+///
+/// # tokio_test();
+/// # #[tokio::main]
+/// # async fn tokio_test() {
+/// let conn = sqlx::SqlitePool::connect("sqlite::memory:").await.unwrap();
+/// let epoch = Timestamp(chrono::Utc.with_ymd_and_hms(1970, 1, 1, 0, 0, 0).unwrap());
+/// for x in [OptionalTimestamp(Some(epoch)), OptionalTimestamp(None)] {
+///     let (y,): (OptionalTimestamp,) =
+///         sqlx::query_as("SELECT ?1;").bind(&x).fetch_one(&conn).await.unwrap();
+///     assert_eq!(x, y);
+/// }
+/// # }
+/// ```
 impl<'r, DB: sqlx::Database> sqlx::Decode<'r, DB> for OptionalTimestamp
 where
-    i64: sqlx::Decode<'r, DB>,
+    Option<i64>: sqlx::Decode<'r, DB>,
 {
     fn decode(
         value: <DB as sqlx::database::HasValueRef<'r>>::ValueRef,
     ) -> Result<Self, Box<dyn std::error::Error + 'static + Send + Sync>> {
-        let value = <i64 as sqlx::Decode<DB>>::decode(value)?;
-        if value == 0_i64 {
-            return Ok(Self(None));
-        }
+        let value = match <Option<i64> as sqlx::Decode<DB>>::decode(value)? {
+            None | Some(NONE_SENTINEL) => return Ok(Self(None)),
+            Some(value) => value,
+        };
         let ts = chrono::NaiveDateTime::from_timestamp_millis(value)
-            // .filter(|x| *x != chrono::NaiveDateTime::from_timestamp_millis(0).unwrap())
             .map(|x| chrono::DateTime::from_utc(x, chrono::Utc));
         match ts {
             Some(ts) => Ok(Self(Some(Timestamp(ts)))),
@@ -225,3 +370,35 @@ where
         }
     }
 }
+
+/// Databases before version 1 stored a missing [OptionalTimestamp] as 0,
+/// which is also the Unix epoch. Move those to [NONE_SENTINEL] once.
+pub async fn migrate_none_sentinel(conn: &sqlx::SqlitePool) -> anyhow::Result<()> {
+    let version: i64 = sqlx::query_scalar("PRAGMA user_version;")
+        .fetch_one(conn)
+        .await?;
+    if version >= 1 {
+        return Ok(());
+    }
+    let columns = [
+        (Book::TABLE_NAME, "release_date"),
+        (Edition::TABLE_NAME, "release_date"),
+        (Edition::TABLE_NAME, "acquired_date"),
+        (Author::TABLE_NAME, "date_born"),
+        (Author::TABLE_NAME, "date_died"),
+    ];
+    let mut tx = conn.begin().await?;
+    for (table, column) in columns {
+        sqlx::query(&format!(
+            "UPDATE {table} SET {column} = ?1 WHERE {column} = 0 OR {column} IS NULL;"
+        ))
+        .bind(NONE_SENTINEL)
+        .execute(&mut tx)
+        .await?;
+    }
+    sqlx::query("PRAGMA user_version = 1;")
+        .execute(&mut tx)
+        .await?;
+    tx.commit().await?;
+    Ok(())
+}